@@ -11,7 +11,9 @@ import sounddevice as sd
 from pynput import keyboard
 import json
 import pyautogui
+import glob
 import queue
+import re
 import threading
 import time
 import ctypes
@@ -29,13 +31,38 @@ except Exception:
         return np.interp(target_times, src_times, audio).astype(np.float32)
 
 # --- CONFIGURATION (defaults; override via CLI args) ---
+# Bump when the stdout/stdin JSON contract with the Tauri app changes
+ENGINE_PROTOCOL = 4
+# A one-shot dictation ends after this much quiet once the level showed speech
+DICTATE_ONCE_SILENCE_S = 0.8
+DICTATE_ONCE_SPEECH_LEVEL = 0.15
 MODEL_DIR = "../data/parakeet_model"
 MODEL_SAMPLE_RATE = 16000
+# ONNX execution provider the recognizer runs on, reported in engine_info
+MODEL_PROVIDER = "cpu"
 
 # Accept either left/right Ctrl + left/right Shift as the hotkey
 CTRL_KEYS = {keyboard.Key.ctrl_l, keyboard.Key.ctrl_r}
 SHIFT_KEYS = {keyboard.Key.shift, keyboard.Key.shift_l, keyboard.Key.shift_r}
 TYPE_INTO_ACTIVE_APP = True
+# "type", "clipboard" or "both"; the host writes the clipboard itself for the last two
+OUTPUT_MODE = "type"
+# "pushToTalk" records while the hotkey is held; "toggle" starts/stops on each press
+HOTKEY_MODE = "pushToTalk"
+# Capture format hints from the host; None uses the device default / mono
+REQUESTED_SAMPLE_RATE = None
+REQUESTED_CHANNELS = None
+# Capture device id from --list-devices; None uses the system default input
+MIC_DEVICE = None
+# Dictation language code and model name from the host; None lets the model decide
+LANGUAGE = None
+MODEL_NAME = None
+# Canonical hotkey string from the host (e.g. "Ctrl+Escape"); None disables cancelling
+CANCEL_HOTKEY = None
+# Dictation hotkey from --hotkey as (modifier key sets, key matcher); Ctrl+Shift by default
+HOTKEY = ([CTRL_KEYS, SHIFT_KEYS], None)
+# The same hotkey as virtual-key groups for the Windows poller; any key in a group counts
+WIN_HOTKEY_VKS = [[0x11], [0x10]]
 PASTE_MODE = os.getenv("JARGON_PASTE_MODE", "auto").strip().lower()
 if PASTE_MODE not in {"auto", "clipboard", "typing"}:
     PASTE_MODE = "auto"
@@ -44,6 +71,11 @@ if PASTE_MODE not in {"auto", "clipboard", "typing"}:
 pressed = set()
 recording = False
 hotkey_active = False
+# Physical hotkey state, separate from hotkey_active which tracks dictation
+hotkey_held = False
+# Keys currently down, tracked for the cancel hotkey even when the dictation hotkey is polled
+cancel_pressed = set()
+cancel_held = False
 audio_queue = queue.Queue()
 audio_stream = None
 input_sample_rate = MODEL_SAMPLE_RATE
@@ -56,23 +88,66 @@ level_stop_event = threading.Event()
 poll_thread = None
 poll_stop_event = threading.Event()
 USE_POLLING_HOTKEY = sys.platform.startswith("win")
+hotkey_armed = False
+hotkey_listener = None
+shutdown_event = threading.Event()
+# Last utterance at MODEL_SAMPLE_RATE, kept so the host can ask for a re-transcription
+last_audio = None
+last_audio_lock = threading.Lock()
+
+def emit_model_progress(stage: str, pct=None) -> None:
+    """Report model loading to the host; pct is 0..1, or None when unknown."""
+    sys.stdout.write(json.dumps({"type": "model_progress", "stage": stage, "pct": pct}) + "\n")
+    sys.stdout.flush()
 
-print("Initializing Parakeet (Sherpa-ONNX)...")
-
-recognizer = sherpa_onnx.OfflineRecognizer.from_transducer(
-    encoder=f"{MODEL_DIR}/encoder.int8.onnx",
-    decoder=f"{MODEL_DIR}/decoder.int8.onnx",
-    joiner=f"{MODEL_DIR}/joiner.int8.onnx",
-    tokens=f"{MODEL_DIR}/tokens.txt",
-    sample_rate=MODEL_SAMPLE_RATE,
-    model_type="nemo_transducer",
-    num_threads=4,
-    provider="cpu",
-)
 
-print("onionsonsale!")
-sys.stdout.write(json.dumps({"type": "engine_ready"}) + "\n")
-sys.stdout.flush()
+# Loaded in main() once the arguments are known, so --list-devices never waits on the model
+recognizer = None
+
+
+def load_recognizer(model_dir: str, language=None) -> None:
+    """Load the model in model_dir, then tell the host the engine is ready.
+
+    Whisper exports transcribe in `language` (detected when None); transducers such as
+    Parakeet always identify the language themselves.
+    """
+    global recognizer
+    print("Initializing Parakeet (Sherpa-ONNX)...")
+    # sherpa-onnx loads all three networks in one call, so only the endpoints are known
+    emit_model_progress("loading", 0.0)
+
+    # sherpa-onnx names Whisper files <size>-encoder[.int8].onnx; sorting prefers int8
+    whisper_encoders = sorted(glob.glob(os.path.join(model_dir, "*-encoder*.onnx")))
+    if whisper_encoders:
+        encoder = whisper_encoders[0]
+        size = os.path.basename(encoder).split("-encoder")[0]
+        recognizer = sherpa_onnx.OfflineRecognizer.from_whisper(
+            encoder=encoder,
+            decoder=encoder.replace("-encoder", "-decoder"),
+            tokens=os.path.join(model_dir, f"{size}-tokens.txt"),
+            language=language or "",
+            task="transcribe",
+            num_threads=4,
+            provider=MODEL_PROVIDER,
+        )
+    else:
+        if language:
+            print(f"[python] Transducer models detect the language; ignoring --language {language}", flush=True)
+        recognizer = sherpa_onnx.OfflineRecognizer.from_transducer(
+            encoder=f"{model_dir}/encoder.int8.onnx",
+            decoder=f"{model_dir}/decoder.int8.onnx",
+            joiner=f"{model_dir}/joiner.int8.onnx",
+            tokens=f"{model_dir}/tokens.txt",
+            sample_rate=MODEL_SAMPLE_RATE,
+            model_type="nemo_transducer",
+            num_threads=4,
+            provider=MODEL_PROVIDER,
+        )
+
+    print("onionsonsale!")
+    emit_model_progress("loaded", 1.0)
+    sys.stdout.write(json.dumps({"type": "engine_ready", "protocol": ENGINE_PROTOCOL}) + "\n")
+    sys.stdout.flush()
 
 # --- AUDIO CALLBACK ---
 def audio_callback(indata, frames, time, status):
@@ -92,9 +167,34 @@ def audio_callback(indata, frames, time, status):
     audio_queue.put(indata.copy())
 
 
+def _device_id(device):
+    # Name plus host API is stable across reboots and unambiguous to sounddevice
+    hostapi = sd.query_hostapis(device["hostapi"])["name"]
+    return f"{device['name']}, {hostapi}"
+
+
+def list_input_devices():
+    devices = []
+    for device in sd.query_devices():
+        if device.get("max_input_channels", 0) > 0:
+            devices.append({"id": _device_id(device), "name": device["name"]})
+    return devices
+
+
+def get_input_device():
+    if MIC_DEVICE is None:
+        return None
+    try:
+        sd.query_devices(MIC_DEVICE, kind="input")
+        return MIC_DEVICE
+    except Exception as exc:
+        print(f"Warning: microphone {MIC_DEVICE!r} unavailable; using the default input: {exc}")
+        return None
+
+
 def get_input_sample_rate():
     try:
-        device_info = sd.query_devices(kind="input")
+        device_info = sd.query_devices(get_input_device(), kind="input")
         return int(device_info.get("default_samplerate", MODEL_SAMPLE_RATE))
     except Exception as exc:
         print(f"Warning: defaulting to {MODEL_SAMPLE_RATE} Hz; failed to read input sample rate: {exc}")
@@ -120,11 +220,10 @@ def _init_win_key_api(user32) -> None:
 def _win_hotkey_physical_down() -> bool:
     user32 = ctypes.WinDLL("user32", use_last_error=True)
     _init_win_key_api(user32)
-    VK_CONTROL = 0x11
-    VK_SHIFT = 0x10
-    ctrl_down = (user32.GetAsyncKeyState(VK_CONTROL) & 0x8000) != 0
-    shift_down = (user32.GetAsyncKeyState(VK_SHIFT) & 0x8000) != 0
-    return ctrl_down and shift_down
+    return all(
+        any((user32.GetAsyncKeyState(vk) & 0x8000) != 0 for vk in group)
+        for group in WIN_HOTKEY_VKS
+    )
 
 
 def _init_win_clipboard_api(user32, kernel32) -> None:
@@ -243,6 +342,67 @@ def _win_try_set_clipboard_text(text: str, timeout_s: float = 0.5) -> bool:
         time.sleep(0.02)
 
 
+def _win_process_is_elevated(process_handle, advapi32) -> bool:
+    TOKEN_QUERY = 0x0008
+    TOKEN_ELEVATION_CLASS = 20
+    token = wintypes.HANDLE()
+    if not advapi32.OpenProcessToken(process_handle, TOKEN_QUERY, ctypes.byref(token)):
+        raise ctypes.WinError(ctypes.get_last_error())
+    try:
+        elevation = wintypes.DWORD()
+        returned = wintypes.DWORD()
+        if not advapi32.GetTokenInformation(
+            token, TOKEN_ELEVATION_CLASS, ctypes.byref(elevation), ctypes.sizeof(elevation), ctypes.byref(returned)
+        ):
+            raise ctypes.WinError(ctypes.get_last_error())
+        return elevation.value != 0
+    finally:
+        ctypes.windll.kernel32.CloseHandle(token)
+
+
+def _win_foreground_injection_blocker():
+    """Reason synthetic input to the foreground window would be dropped, or None.
+
+    UIPI silently discards input sent from a non-elevated process to an elevated window.
+    """
+    user32 = ctypes.WinDLL("user32", use_last_error=True)
+    kernel32 = ctypes.WinDLL("kernel32", use_last_error=True)
+    advapi32 = ctypes.WinDLL("advapi32", use_last_error=True)
+    user32.GetForegroundWindow.restype = wintypes.HWND
+    kernel32.OpenProcess.restype = wintypes.HANDLE
+    kernel32.GetCurrentProcess.restype = wintypes.HANDLE
+    advapi32.OpenProcessToken.argtypes = [wintypes.HANDLE, wintypes.DWORD, ctypes.POINTER(wintypes.HANDLE)]
+
+    hwnd = user32.GetForegroundWindow()
+    if not hwnd:
+        return "no-foreground-window"
+    pid = wintypes.DWORD()
+    user32.GetWindowThreadProcessId(hwnd, ctypes.byref(pid))
+
+    try:
+        if _win_process_is_elevated(kernel32.GetCurrentProcess(), advapi32):
+            return None
+    except OSError:
+        return None
+
+    PROCESS_QUERY_LIMITED_INFORMATION = 0x1000
+    process = kernel32.OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, False, pid.value)
+    if not process:
+        # Protected/system processes refuse even limited queries; they reject input too
+        return "elevated-target"
+    try:
+        return "elevated-target" if _win_process_is_elevated(process, advapi32) else None
+    except OSError:
+        return "elevated-target"
+    finally:
+        kernel32.CloseHandle(process)
+
+
+def emit_inject_failed(reason: str, text: str) -> None:
+    sys.stdout.write(json.dumps({"type": "inject_failed", "reason": reason, "text": text}) + "\n")
+    sys.stdout.flush()
+
+
 def _type_into_active_app(text: str) -> None:
     previous_pause = pyautogui.PAUSE
     try:
@@ -267,7 +427,9 @@ def paste_into_active_app(text: str) -> None:
             except Exception:
                 hotkey_ok = False
             time.sleep(0.12)
-            if previous is not None:
+            # In "both" mode the host leaves the transcript on the clipboard; restoring
+            # here could race with its copy
+            if previous is not None and OUTPUT_MODE != "both":
                 _win_try_set_clipboard_text(previous, timeout_s=0.2)
             if hotkey_ok:
                 return
@@ -278,35 +440,82 @@ def paste_into_active_app(text: str) -> None:
     _type_into_active_app(text)
 
 # --- RECORDING CONTROL ---
+def emit_engine_info(sample_rate=None) -> None:
+    """Report the mic, capture rate, model and compute device actually in use."""
+    try:
+        mic = sd.query_devices(get_input_device(), kind="input")["name"]
+    except Exception:
+        mic = MIC_DEVICE or "default"
+    payload = {
+        "type": "engine_info",
+        "mic": mic,
+        "sampleRate": int(sample_rate or REQUESTED_SAMPLE_RATE or get_input_sample_rate()),
+        "model": MODEL_NAME or os.path.basename(os.path.normpath(MODEL_DIR)),
+        "device": MODEL_PROVIDER,
+    }
+    sys.stdout.write(json.dumps(payload) + "\n")
+    sys.stdout.flush()
+
+
+def _open_input_stream(sample_rate, channels):
+    stream = sd.InputStream(
+        device=get_input_device(),
+        samplerate=sample_rate,
+        channels=channels,
+        callback=audio_callback,
+    )
+    stream.start()
+    return stream
+
+
 def start_recording():
     global audio_stream, input_sample_rate
     with lock:
         audio_queue.queue.clear()
-        input_sample_rate = get_input_sample_rate()
+        device_rate = get_input_sample_rate()
+        input_sample_rate = REQUESTED_SAMPLE_RATE or device_rate
+        channels = REQUESTED_CHANNELS or 1
         try:
-            audio_stream = sd.InputStream(
-                samplerate=input_sample_rate,
-                channels=1,
-                callback=audio_callback,
-            )
-            audio_stream.start()
-            start_level_emitter()
-            return True
+            audio_stream = _open_input_stream(input_sample_rate, channels)
         except Exception as exc:
             audio_stream = None
-            print(f"Unable to start recording (sample rate {input_sample_rate}): {exc}")
-            return False
+            print(f"Unable to start recording (sample rate {input_sample_rate}, channels {channels}): {exc}")
+            if (input_sample_rate, channels) == (device_rate, 1):
+                return False
+            # The hint didn't suit this device; fall back to its defaults
+            input_sample_rate = device_rate
+            try:
+                audio_stream = _open_input_stream(input_sample_rate, 1)
+            except Exception as fallback_exc:
+                audio_stream = None
+                print(f"Unable to start recording (sample rate {input_sample_rate}): {fallback_exc}")
+                return False
+        input_sample_rate = int(audio_stream.samplerate)
+        sys.stdout.write(
+            json.dumps({"type": "audio_format", "sampleRate": input_sample_rate, "channels": audio_stream.channels})
+            + "\n"
+        )
+        sys.stdout.flush()
+        # The opened rate can differ from the one announced at startup
+        emit_engine_info(input_sample_rate)
+        start_level_emitter()
+        return True
 
-def stop_recording():
+def stop_recording(discard=False, route=True):
     global audio_stream
     with lock:
         if audio_stream:
             audio_stream.stop()
             audio_stream.close()
             audio_stream = None
+    # The host measures speech-end to transcript latency from here
+    capture_ts = int(time.time() * 1000)
     stop_level_emitter()
+    if discard:
+        audio_queue.queue.clear()
+        return None
     print("Processing/.......")
-    process_audio()
+    return process_audio(capture_ts, route)
 
 
 def start_level_emitter():
@@ -347,54 +556,218 @@ def stop_level_emitter():
     sys.stdout.flush()
 
 # --- AUDIO PROCESSING ---
-def process_audio():
+def transcribe(audio_data) -> str:
+    stream = recognizer.create_stream()
+    stream.accept_waveform(MODEL_SAMPLE_RATE, audio_data)
+    recognizer.decode_stream(stream)
+    return stream.result.text.strip()
+
+
+def apply_overrides(text: str, overrides: dict) -> str:
+    """Post-process a transcript per host overrides (already validated by the host)."""
+    if overrides.get("punctuation") is False:
+        text = re.sub(r"[^\w\s'-]", "", text)
+        text = re.sub(r"\s+", " ", text).strip()
+    if overrides.get("lowercase"):
+        text = text.lower()
+    return text
+
+
+def process_audio(capture_ts=None, route=True):
+    """Transcribe the captured audio; with `route` off the text is only returned, not sent or typed."""
+    global last_audio
     samples = []
     while not audio_queue.empty():
         samples.append(audio_queue.get())
 
     if not samples:
-        return
+        return None
 
-    audio_data = np.concatenate(samples, axis=0).flatten()
+    audio_data = np.concatenate(samples, axis=0)
+    if audio_data.ndim == 2 and audio_data.shape[1] > 1:
+        # Downmix multi-channel capture; the model expects mono
+        audio_data = audio_data.mean(axis=1)
+    audio_data = audio_data.flatten()
     audio_data = resample_audio(audio_data, input_sample_rate, MODEL_SAMPLE_RATE)
     if input_sample_rate != MODEL_SAMPLE_RATE:
         print(f"Resampled from {input_sample_rate} Hz to {MODEL_SAMPLE_RATE} Hz")
 
-    stream = recognizer.create_stream()
-    stream.accept_waveform(MODEL_SAMPLE_RATE, audio_data)
-    recognizer.decode_stream(stream)
+    with last_audio_lock:
+        last_audio = audio_data
 
-    result = stream.result.text.strip()
+    result = transcribe(audio_data)
+    if not route:
+        return result
     if result:
-        sys.stdout.write(json.dumps({"type": "transcript", "text": result}) + "\n")
+        message = {"type": "transcript", "text": result}
+        if capture_ts is not None:
+            message["captureTs"] = capture_ts
+        sys.stdout.write(json.dumps(message) + "\n")
         sys.stdout.flush()
         if TYPE_INTO_ACTIVE_APP:
+            blocker = None
+            if sys.platform.startswith("win"):
+                try:
+                    blocker = _win_foreground_injection_blocker()
+                except Exception:
+                    blocker = None
+            if blocker:
+                print(f"[python] Foreground window can't receive input: {blocker}", file=sys.stderr, flush=True)
+                emit_inject_failed(blocker, result)
+                return
             try:
                 paste_into_active_app(result + " ")
             except Exception as exc:
                 print(f"[python] Warning: failed to paste into active app: {exc}", file=sys.stderr, flush=True)
+                emit_inject_failed(str(exc), result)
+    return result
 
 # --- HOTKEY HANDLERS ---
+def hotkey_matches(hotkey, keys) -> bool:
+    groups, matcher = hotkey
+    if not all(any(k in keys for k in group) for group in groups):
+        return False
+    return matcher is None or any(matcher(k) for k in keys)
+
+
 def is_hotkey_pressed() -> bool:
-    return (any(k in pressed for k in CTRL_KEYS) and any(k in pressed for k in SHIFT_KEYS))
+    return hotkey_matches(HOTKEY, pressed)
+
+
+ALT_KEYS = {keyboard.Key.alt, keyboard.Key.alt_l, keyboard.Key.alt_r, getattr(keyboard.Key, "alt_gr", keyboard.Key.alt)}
+SUPER_KEYS = {keyboard.Key.cmd, keyboard.Key.cmd_l, keyboard.Key.cmd_r}
+MODIFIER_KEYS = {"Ctrl": CTRL_KEYS, "Alt": ALT_KEYS, "Shift": SHIFT_KEYS, "Super": SUPER_KEYS}
+NAMED_KEYS = {
+    "Space": "space",
+    "Enter": "enter",
+    "Tab": "tab",
+    "Backspace": "backspace",
+    "Delete": "delete",
+    "Escape": "esc",
+    "Insert": "insert",
+    "Home": "home",
+    "End": "end",
+    "PageUp": "page_up",
+    "PageDown": "page_down",
+    "CapsLock": "caps_lock",
+    "Pause": "pause",
+}
+
+
+def parse_hotkey_spec(spec: str):
+    """Split a canonical hotkey into modifier key sets and a key matcher (None for modifiers only)."""
+    groups = []
+    matcher = None
+    for token in spec.split("+"):
+        if token in MODIFIER_KEYS:
+            groups.append(MODIFIER_KEYS[token])
+        elif token in NAMED_KEYS or (token[:1] == "F" and token[1:].isdigit()):
+            target = getattr(keyboard.Key, NAMED_KEYS.get(token, token.lower()), None)
+            if target is None:
+                return None
+            matcher = lambda key, target=target: key == target
+        elif len(token) == 1:
+            # Ctrl turns key.char into a control character on Windows, so prefer the virtual key
+            matcher = lambda key, c=token.upper(): isinstance(key, keyboard.KeyCode) and (
+                getattr(key, "vk", None) == ord(c) or (key.char or "").upper() == c
+            )
+        else:
+            return None
+    return groups, matcher
+
+
+WIN_MODIFIER_VKS = {"Ctrl": [0x11], "Alt": [0x12], "Shift": [0x10], "Super": [0x5B, 0x5C]}
+WIN_NAMED_VKS = {
+    "Space": 0x20,
+    "Enter": 0x0D,
+    "Tab": 0x09,
+    "Backspace": 0x08,
+    "Delete": 0x2E,
+    "Escape": 0x1B,
+    "Insert": 0x2D,
+    "Home": 0x24,
+    "End": 0x23,
+    "PageUp": 0x21,
+    "PageDown": 0x22,
+    "CapsLock": 0x14,
+    "Pause": 0x13,
+}
+
+
+def win_hotkey_vk_groups(spec: str):
+    """Map a canonical hotkey to virtual-key groups for GetAsyncKeyState (None if unsupported)."""
+    groups = []
+    for token in spec.split("+"):
+        if token in WIN_MODIFIER_VKS:
+            groups.append(WIN_MODIFIER_VKS[token])
+        elif token in WIN_NAMED_VKS:
+            groups.append([WIN_NAMED_VKS[token]])
+        elif token[:1] == "F" and token[1:].isdigit():
+            # VK_F1 is 0x70 and the rest follow in order
+            groups.append([0x6F + int(token[1:])])
+        elif len(token) == 1 and token.isalnum():
+            groups.append([ord(token.upper())])
+        else:
+            return None
+    return groups
+
+
+def is_cancel_hotkey_pressed() -> bool:
+    if CANCEL_HOTKEY is None:
+        return False
+    return hotkey_matches(CANCEL_HOTKEY, cancel_pressed)
+
+
+def track_cancel_key(key, down: bool):
+    global cancel_held
+    if down:
+        cancel_pressed.add(key)
+    else:
+        cancel_pressed.discard(key)
+    now_held = is_cancel_hotkey_pressed()
+    if now_held and not cancel_held:
+        cancel_dictation()
+    cancel_held = now_held
 
 
 def on_press(key):
-    global recording, hotkey_active
+    track_cancel_key(key, True)
     if USE_POLLING_HOTKEY:
         return
     pressed.add(key)
-    if not hotkey_active and is_hotkey_pressed():
-        begin_dictation()
+    if not hotkey_held and is_hotkey_pressed():
+        hotkey_down()
 
 
 def on_release(key):
-    global recording, hotkey_active
+    track_cancel_key(key, False)
     if USE_POLLING_HOTKEY:
         return
     if key in pressed:
         pressed.remove(key)
-    if hotkey_active and not is_hotkey_pressed():
+    if hotkey_held and not is_hotkey_pressed():
+        hotkey_up()
+
+
+def hotkey_down():
+    """Report the press to the host, then start (or toggle) dictation."""
+    global hotkey_held
+    hotkey_held = True
+    sys.stdout.write(json.dumps({"type": "hotkey", "down": True}) + "\n")
+    sys.stdout.flush()
+    if HOTKEY_MODE == "toggle" and hotkey_active:
+        end_dictation()
+    else:
+        begin_dictation()
+
+
+def hotkey_up():
+    """Report the release to the host; push-to-talk stops dictating here."""
+    global hotkey_held
+    hotkey_held = False
+    sys.stdout.write(json.dumps({"type": "hotkey", "down": False}) + "\n")
+    sys.stdout.flush()
+    if HOTKEY_MODE != "toggle":
         end_dictation()
 
 
@@ -413,20 +786,40 @@ def begin_dictation():
     sys.stdout.flush()
 
 
-def end_dictation():
+def end_dictation(route=True):
+    """Stop dictating and return the transcript, if any; see `process_audio` for `route`."""
     global recording, hotkey_active
     with state_lock:
         if not hotkey_active:
-            return
+            return None
         hotkey_active = False
+    text = None
     if recording:
         recording = False
         sys.stdout.write(json.dumps({"type": "dictation_stop"}) + "\n")
         sys.stdout.flush()
-        stop_recording()
+        text = stop_recording(route=route)
     # Signal overlay collapse regardless
     sys.stdout.write(json.dumps({"type": "overlay", "hover": False}) + "\n")
     sys.stdout.flush()
+    return text
+
+
+def cancel_dictation():
+    """Stop dictating and throw the audio away; the held dictation hotkey's release is then a no-op."""
+    global recording, hotkey_active
+    with state_lock:
+        if not hotkey_active:
+            return
+        hotkey_active = False
+    if recording:
+        recording = False
+        sys.stdout.write(json.dumps({"type": "dictation_stop"}) + "\n")
+        sys.stdout.flush()
+        stop_recording(discard=True)
+    sys.stdout.write(json.dumps({"type": "cancelled"}) + "\n")
+    sys.stdout.write(json.dumps({"type": "overlay", "hover": False}) + "\n")
+    sys.stdout.flush()
 
 
 def hotkey_poll_loop():
@@ -440,53 +833,241 @@ def hotkey_poll_loop():
         now = time.monotonic()
         if down:
             up_since = None
-            if not hotkey_active:
+            if not hotkey_held:
                 if down_since is None:
                     down_since = now
                 elif now - down_since >= 0.03:
                     down_since = None
-                    begin_dictation()
+                    hotkey_down()
         else:
             down_since = None
-            if hotkey_active:
+            if hotkey_held:
                 if up_since is None:
                     up_since = now
                 elif now - up_since >= 0.05:
                     up_since = None
-                    end_dictation()
+                    hotkey_up()
         time.sleep(0.01)
 
+# --- HOST COMMANDS (JSON lines on stdin) ---
+def arm_hotkey():
+    """Start listening for the dictation hotkey; safe to call more than once."""
+    global hotkey_armed, poll_thread, hotkey_listener
+    with state_lock:
+        if hotkey_armed:
+            return
+        hotkey_armed = True
+
+    if USE_POLLING_HOTKEY:
+        poll_stop_event.clear()
+        poll_thread = threading.Thread(target=hotkey_poll_loop, daemon=True)
+        poll_thread.start()
+
+    hotkey_listener = keyboard.Listener(on_press=on_press, on_release=on_release)
+    hotkey_listener.start()
+    print("[python] Hotkey armed", flush=True)
+    # The model loaded at import, so armed means listening
+    sys.stdout.write(json.dumps({"type": "ready"}) + "\n")
+    sys.stdout.flush()
+
+
+def send_response(request_id, ok: bool, **fields) -> None:
+    payload = {"type": "response", "requestId": request_id, "ok": ok}
+    payload.update(fields)
+    sys.stdout.write(json.dumps(payload) + "\n")
+    sys.stdout.flush()
+
+
+def retranscribe_last(request_id, overrides: dict) -> None:
+    with last_audio_lock:
+        audio_data = last_audio
+    if audio_data is None:
+        send_response(request_id, False, error="No utterance captured yet")
+        return
+    try:
+        text = apply_overrides(transcribe(audio_data), overrides)
+    except Exception as exc:
+        send_response(request_id, False, error=str(exc))
+        return
+    send_response(request_id, True, text=text)
+
+
+def forget_last():
+    """Drop the audio kept for retranscribe once the host clears its history."""
+    global last_audio
+    with last_audio_lock:
+        last_audio = None
+
+
+def set_input_device(device_id):
+    """Record from `device_id` (None for the system default) starting with the next utterance."""
+    global MIC_DEVICE
+    with lock:
+        MIC_DEVICE = device_id or None
+    print(f"[python] Microphone: {MIC_DEVICE or 'default'}", flush=True)
+    emit_engine_info()
+
+
+def dictate_once(request_id, max_ms) -> None:
+    """Record until a pause after speech or `max_ms`, then reply with the transcript instead of typing it."""
+    if hotkey_active:
+        send_response(request_id, False, error="Already dictating")
+        return
+    begin_dictation()
+    if not recording:
+        end_dictation(route=False)
+        send_response(request_id, False, error="Unable to start recording")
+        return
+    deadline = time.monotonic() + max_ms / 1000.0
+    heard_speech = False
+    quiet_since = None
+    while hotkey_active and time.monotonic() < deadline:
+        time.sleep(0.05)
+        with level_lock:
+            level = latest_level
+        if level >= DICTATE_ONCE_SPEECH_LEVEL:
+            heard_speech = True
+            quiet_since = None
+        elif heard_speech:
+            quiet_since = quiet_since or time.monotonic()
+            if time.monotonic() - quiet_since >= DICTATE_ONCE_SILENCE_S:
+                break
+    if not hotkey_active:
+        # The hotkey or cancel hotkey ended this dictation first
+        send_response(request_id, False, error="Dictation was interrupted")
+        return
+    try:
+        text = end_dictation(route=False)
+    except Exception as exc:
+        send_response(request_id, False, error=str(exc))
+        return
+    send_response(request_id, True, text=text or "")
+
+
+def request_shutdown():
+    """Stop listening and let main() return so the process exits on its own."""
+    poll_stop_event.set()
+    if hotkey_listener is not None:
+        hotkey_listener.stop()
+    print("[python] Shutting down", flush=True)
+    shutdown_event.set()
+
+
+def handle_host_command(message):
+    kind = message.get("type")
+    if kind == "arm":
+        arm_hotkey()
+    elif kind == "shutdown":
+        request_shutdown()
+    elif kind == "set_device":
+        set_input_device(message.get("id"))
+    elif kind == "dictate_once":
+        # Runs for up to maxMs; keep it off the command thread
+        threading.Thread(
+            target=dictate_once,
+            args=(message.get("requestId"), message.get("maxMs") or 0),
+            daemon=True,
+        ).start()
+    elif kind == "forget_last":
+        forget_last()
+    elif kind == "retranscribe":
+        # Decode off the command thread so stdin keeps draining
+        threading.Thread(
+            target=retranscribe_last,
+            args=(message.get("requestId"), message.get("overrides") or {}),
+            daemon=True,
+        ).start()
+    else:
+        print(f"[python] Ignoring unknown host command: {kind}", file=sys.stderr, flush=True)
+
+
+def host_command_loop():
+    if sys.stdin is None:
+        return
+    for raw in sys.stdin:
+        raw = raw.strip()
+        if not raw:
+            continue
+        try:
+            message = json.loads(raw)
+        except ValueError:
+            print(f"[python] Ignoring malformed host command: {raw}", file=sys.stderr, flush=True)
+            continue
+        if isinstance(message, dict):
+            handle_host_command(message)
+    # The host closed our stdin (or died); don't linger as an orphan
+    request_shutdown()
+
+
 # --- MAIN LOOP ---
 def main():
-    global MODEL_DIR, TYPE_INTO_ACTIVE_APP, PASTE_MODE
+    global MODEL_DIR, TYPE_INTO_ACTIVE_APP, OUTPUT_MODE, HOTKEY_MODE, PASTE_MODE, REQUESTED_SAMPLE_RATE, REQUESTED_CHANNELS, MIC_DEVICE, LANGUAGE, MODEL_NAME, CANCEL_HOTKEY, HOTKEY, WIN_HOTKEY_VKS
     # Parse command-line arguments from Tauri
     parser = argparse.ArgumentParser(description="Speech-to-text engine")
-    parser.add_argument("--hotkey", type=str, help="Hotkey combination (ignored for now; hardcoded Ctrl+Shift)")
+    parser.add_argument("--hotkey", type=str, help="Dictation hotkey in canonical form, e.g. Ctrl+Shift or Ctrl+Alt+D")
+    parser.add_argument("--hotkey-mode", type=str, default=HOTKEY_MODE, choices=["pushToTalk", "toggle"], help="Record while held (pushToTalk) or start/stop on each press (toggle)")
     parser.add_argument("--model-dir", type=str, default=MODEL_DIR, help="Path to the ONNX model directory")
     parser.add_argument("--type-into-active-app", type=str, default="true", help="Type into active app (true/false)")
+    parser.add_argument("--output-mode", type=str, default=OUTPUT_MODE, choices=["type", "clipboard", "both", "off"], help="Where transcripts go; the host handles the clipboard")
     parser.add_argument("--paste-mode", type=str, default=PASTE_MODE, help="Paste method: auto, clipboard, typing")
+    parser.add_argument("--sample-rate", type=int, default=None, help="Preferred capture sample rate in Hz")
+    parser.add_argument("--channels", type=int, default=None, help="Preferred capture channel count")
+    parser.add_argument("--no-hotkey", action="store_true", help="Load the model but wait for an arm command before listening")
+    parser.add_argument("--mic-device", type=str, default=None, help="Capture device id from --list-devices")
+    parser.add_argument("--list-devices", action="store_true", help="Print capture devices as JSON and exit")
+    parser.add_argument("--language", type=str, default=None, help="Dictation language code, e.g. en or es")
+    parser.add_argument("--model", type=str, default=None, help="Name of the model; the host resolves it to --model-dir")
+    parser.add_argument("--cancel-hotkey", type=str, default=None, help="Hotkey that discards the current utterance, e.g. Ctrl+Escape")
     args = parser.parse_args()
+
+    if args.list_devices:
+        print(json.dumps(list_input_devices()), flush=True)
+        return
     
     MODEL_DIR = args.model_dir
     TYPE_INTO_ACTIVE_APP = args.type_into_active_app.lower() == "true"
+    OUTPUT_MODE = args.output_mode
+    HOTKEY_MODE = args.hotkey_mode
     PASTE_MODE = args.paste_mode.strip().lower()
     if PASTE_MODE not in {"auto", "clipboard", "typing"}:
         PASTE_MODE = "auto"
+    REQUESTED_SAMPLE_RATE = args.sample_rate
+    REQUESTED_CHANNELS = args.channels
+    MIC_DEVICE = args.mic_device
+    LANGUAGE = args.language
+    MODEL_NAME = args.model
+    if args.hotkey:
+        hotkey = parse_hotkey_spec(args.hotkey)
+        vk_groups = win_hotkey_vk_groups(args.hotkey)
+        if hotkey is None or vk_groups is None:
+            print(f"[python] Ignoring unsupported hotkey {args.hotkey}; using Ctrl+Shift", file=sys.stderr, flush=True)
+        else:
+            HOTKEY, WIN_HOTKEY_VKS = hotkey, vk_groups
+    if args.cancel_hotkey:
+        CANCEL_HOTKEY = parse_hotkey_spec(args.cancel_hotkey)
+        if CANCEL_HOTKEY is None:
+            print(f"[python] Ignoring unsupported cancel hotkey: {args.cancel_hotkey}", file=sys.stderr, flush=True)
     
     print(f"[python] Model dir: {MODEL_DIR}", flush=True)
     print(f"[python] Type into active app: {TYPE_INTO_ACTIVE_APP}", flush=True)
+    print(f"[python] Output mode: {OUTPUT_MODE}", flush=True)
+    print(f"[python] Hotkey mode: {HOTKEY_MODE}", flush=True)
     print(f"[python] Paste mode: {PASTE_MODE}", flush=True)
+    print(f"[python] Microphone: {MIC_DEVICE or 'default'}", flush=True)
+    print(f"[python] Language: {LANGUAGE or 'auto'}", flush=True)
+    print(f"[python] Model: {MODEL_NAME or 'default'}", flush=True)
+    load_recognizer(MODEL_DIR, LANGUAGE)
+    emit_engine_info()
     
-    if USE_POLLING_HOTKEY:
-        global poll_thread
-        poll_stop_event.clear()
-        poll_thread = threading.Thread(target=hotkey_poll_loop, daemon=True)
-        poll_thread.start()
+    threading.Thread(target=host_command_loop, daemon=True).start()
+
+    if args.no_hotkey:
+        print("[python] Pre-warmed; waiting for arm command", flush=True)
+    else:
+        arm_hotkey()
 
-    # Start the hotkey listener and block so the process stays alive.
-    listener = keyboard.Listener(on_press=on_press, on_release=on_release)
-    listener.start()
-    listener.join()  # wait forever until the program is killed
+    # Block so the process stays alive until it is killed.
+    shutdown_event.wait()
 
 
 if __name__ == "__main__":
@@ -0,0 +1,107 @@
+#[cfg(windows)]
+use windows::core::w;
+#[cfg(windows)]
+use windows::Win32::Foundation::{GlobalFree, HANDLE, HGLOBAL};
+#[cfg(windows)]
+use windows::Win32::System::DataExchange::{
+    CloseClipboard, EmptyClipboard, OpenClipboard, RegisterClipboardFormatW, SetClipboardData,
+};
+#[cfg(windows)]
+use windows::Win32::System::Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+#[cfg(windows)]
+use windows::Win32::System::Ole::CF_UNICODETEXT;
+
+#[cfg(windows)]
+const OPEN_ATTEMPTS: u32 = 10;
+#[cfg(windows)]
+const OPEN_RETRY_MS: u64 = 20;
+
+/// Another process may briefly hold the clipboard open; retry before giving up
+#[cfg(windows)]
+fn open_clipboard() -> Result<(), String> {
+    let mut last_err = String::new();
+    for attempt in 0..OPEN_ATTEMPTS {
+        match unsafe { OpenClipboard(None) } {
+            Ok(()) => return Ok(()),
+            Err(err) => last_err = format!("{err:?}"),
+        }
+        if attempt + 1 < OPEN_ATTEMPTS {
+            std::thread::sleep(std::time::Duration::from_millis(OPEN_RETRY_MS));
+        }
+    }
+    Err(format!("OpenClipboard failed: {last_err}"))
+}
+
+#[cfg(windows)]
+unsafe fn alloc_global_bytes(bytes: &[u8]) -> Result<HGLOBAL, String> {
+    let hglobal = GlobalAlloc(GMEM_MOVEABLE, bytes.len()).map_err(|e| format!("{e:?}"))?;
+    let locked = GlobalLock(hglobal);
+    if locked.is_null() {
+        let _ = GlobalFree(Some(hglobal));
+        return Err("GlobalLock failed".to_string());
+    }
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), locked as *mut u8, bytes.len());
+    let _ = GlobalUnlock(hglobal);
+    Ok(hglobal)
+}
+
+#[cfg(windows)]
+unsafe fn set_global_data(format: u32, bytes: &[u8]) -> Result<(), String> {
+    let hglobal = alloc_global_bytes(bytes)?;
+    if let Err(err) = SetClipboardData(format, Some(HANDLE(hglobal.0))) {
+        // Ownership only transfers to the system on success
+        let _ = GlobalFree(Some(hglobal));
+        return Err(format!("SetClipboardData failed: {err:?}"));
+    }
+    Ok(())
+}
+
+/// Windows 10+ clipboard history (Win+V) reads this DWORD to decide whether to keep the entry
+#[cfg(windows)]
+unsafe fn set_history_flag(include: bool) -> Result<(), String> {
+    let format = RegisterClipboardFormatW(w!("CanIncludeInClipboardHistory"));
+    if format == 0 {
+        return Err("RegisterClipboardFormatW failed".to_string());
+    }
+    set_global_data(format, &u32::from(include).to_le_bytes())
+}
+
+/// Replace the clipboard contents with `text` as CF_UNICODETEXT.
+/// `include_in_history` marks the entry for (or excludes it from) clipboard history.
+#[cfg(windows)]
+pub fn set_text(text: &str, include_in_history: bool) -> Result<(), String> {
+    let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let bytes: Vec<u8> = wide.iter().flat_map(|unit| unit.to_le_bytes()).collect();
+
+    open_clipboard()?;
+    let result = unsafe {
+        (|| {
+            EmptyClipboard().map_err(|e| format!("EmptyClipboard failed: {e:?}"))?;
+            set_global_data(CF_UNICODETEXT.0 as u32, &bytes)?;
+            set_history_flag(include_in_history)
+        })()
+    };
+    let _ = unsafe { CloseClipboard() };
+    result
+}
+
+/// Replace the clipboard contents with `text`; clipboard history is a Windows concept, so
+/// `include_in_history` has no effect here
+#[cfg(not(windows))]
+pub fn set_text(text: &str, _include_in_history: bool) -> Result<(), String> {
+    use std::sync::{Mutex, OnceLock};
+    // On X11 the owning process serves the contents, so the handle has to outlive this call
+    static CLIPBOARD: OnceLock<Mutex<Option<arboard::Clipboard>>> = OnceLock::new();
+    let mut guard = CLIPBOARD
+        .get_or_init(|| Mutex::new(None))
+        .lock()
+        .map_err(|_| "Clipboard lock poisoned".to_string())?;
+    if guard.is_none() {
+        *guard =
+            Some(arboard::Clipboard::new().map_err(|e| format!("Clipboard unavailable: {e}"))?);
+    }
+    let clipboard = guard.as_mut().expect("clipboard was just opened");
+    clipboard
+        .set_text(text)
+        .map_err(|e| format!("Clipboard write failed: {e}"))
+}
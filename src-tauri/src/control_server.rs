@@ -0,0 +1,182 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How often the accept loop checks whether it has been replaced or stopped
+const POLL_INTERVAL_MS: u64 = 100;
+/// A client that doesn't finish its request line in this long is dropped
+const READ_TIMEOUT_MS: u64 = 2000;
+
+/// Bumped on every start/stop; an accept loop exits once it no longer matches
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+/// Port the live server listens on, if any
+static PORT: Mutex<Option<u16>> = Mutex::new(None);
+
+/// Requests the control server understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    Start,
+    Stop,
+    Toggle,
+    Status,
+}
+
+/// Map an HTTP request line to a command, or the status code to answer with.
+/// Commands are POST; `/status` also answers GET.
+fn parse_request_line(line: &str) -> Result<ControlCommand, u16> {
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(400);
+    };
+    let path = target
+        .split('?')
+        .next()
+        .unwrap_or(target)
+        .trim_end_matches('/');
+    let command = match path {
+        "/start" => ControlCommand::Start,
+        "/stop" => ControlCommand::Stop,
+        "/toggle" => ControlCommand::Toggle,
+        "/status" => ControlCommand::Status,
+        _ => return Err(404),
+    };
+    match method {
+        "POST" => Ok(command),
+        "GET" if command == ControlCommand::Status => Ok(command),
+        _ => Err(405),
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+/// A complete `Connection: close` response carrying `body` as JSON
+fn response(status: u16, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        reason_phrase(status),
+        body.len()
+    )
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// Read one request from `stream` and answer it with `handler`'s JSON
+fn serve<F>(stream: TcpStream, handler: &F)
+where
+    F: Fn(ControlCommand) -> Result<serde_json::Value, String>,
+{
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(READ_TIMEOUT_MS)));
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // Browsers send Origin on cross-site requests; scripts and curl don't. Refusing it
+    // keeps a web page from driving dictation through the loopback port.
+    let mut from_browser = false;
+    loop {
+        let mut header = String::new();
+        match reader.read_line(&mut header) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if header.trim().is_empty() => break,
+            Ok(_) => {
+                let name = header.split(':').next().unwrap_or_default();
+                from_browser |= name.trim().eq_ignore_ascii_case("origin");
+            }
+        }
+    }
+    let (status, body) = if from_browser {
+        (403, error_body("browser requests are not accepted"))
+    } else {
+        match parse_request_line(&request_line) {
+            Ok(command) => match handler(command) {
+                Ok(value) => (200, value.to_string()),
+                Err(err) => (500, error_body(&err)),
+            },
+            Err(status) => (status, error_body(reason_phrase(status))),
+        }
+    };
+    let mut stream = &stream;
+    let _ = stream.write_all(response(status, &body).as_bytes());
+}
+
+/// Listen on `127.0.0.1:port`, answering each request with `handler`. Replaces a server
+/// on another port and is a no-op when one already runs on this port.
+pub fn start<F>(port: u16, handler: F) -> Result<(), String>
+where
+    F: Fn(ControlCommand) -> Result<serde_json::Value, String> + Send + 'static,
+{
+    let mut live = PORT.lock().map_err(|_| "Control server lock poisoned")?;
+    if *live == Some(port) {
+        return Ok(());
+    }
+    // The old loop stops even if the new port fails to bind, matching the config
+    let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    *live = None;
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+        .map_err(|e| format!("Failed to listen on 127.0.0.1:{port}: {e}"))?;
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to configure the control listener: {e}"))?;
+    *live = Some(port);
+    std::thread::spawn(move || {
+        while GENERATION.load(Ordering::SeqCst) == generation {
+            match listener.accept() {
+                Ok((stream, _)) => serve(stream, &handler),
+                Err(_) => std::thread::sleep(Duration::from_millis(POLL_INTERVAL_MS)),
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Stop the running server, if any
+pub fn stop() {
+    let mut live = PORT.lock().unwrap_or_else(|e| e.into_inner());
+    if live.take().is_some() {
+        GENERATION.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_requests_map_to_commands() {
+        assert_eq!(
+            parse_request_line("POST /start HTTP/1.1\r\n"),
+            Ok(ControlCommand::Start)
+        );
+        assert_eq!(
+            parse_request_line("POST /toggle/ HTTP/1.1"),
+            Ok(ControlCommand::Toggle)
+        );
+        assert_eq!(
+            parse_request_line("GET /status?x=1 HTTP/1.1"),
+            Ok(ControlCommand::Status)
+        );
+        assert_eq!(parse_request_line("GET /stop HTTP/1.1"), Err(405));
+        assert_eq!(parse_request_line("POST /restart HTTP/1.1"), Err(404));
+        assert_eq!(parse_request_line(""), Err(400));
+
+        let response = response(200, r#"{"running":true}"#);
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Length: 16\r\n"));
+        assert!(response.ends_with("\r\n\r\n{\"running\":true}"));
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+
+/// Modifiers in the order they appear in the canonical form
+const MODIFIERS: [(&str, &[&str]); 4] = [
+    ("Ctrl", &["ctrl", "control"]),
+    ("Alt", &["alt", "option"]),
+    ("Shift", &["shift"]),
+    ("Super", &["super", "win", "meta", "cmd", "command"]),
+];
+
+const NAMED_KEYS: [&str; 13] = [
+    "Space",
+    "Enter",
+    "Tab",
+    "Backspace",
+    "Delete",
+    "Escape",
+    "Insert",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "CapsLock",
+    "Pause",
+];
+
+/// A validated hotkey: modifiers in canonical order plus at most one key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedHotkey {
+    modifiers: Vec<&'static str>,
+    key: Option<String>,
+}
+
+impl fmt::Display for NormalizedHotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<&str> = self.modifiers.clone();
+        if let Some(key) = &self.key {
+            parts.push(key);
+        }
+        f.write_str(&parts.join("+"))
+    }
+}
+
+fn modifier(token: &str) -> Option<&'static str> {
+    let lower = token.to_ascii_lowercase();
+    MODIFIERS
+        .iter()
+        .find(|(_, aliases)| aliases.contains(&lower.as_str()))
+        .map(|(name, _)| *name)
+}
+
+fn key(token: &str) -> Option<String> {
+    let lower = token.to_ascii_lowercase();
+    if token.len() == 1 && token.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Some(token.to_ascii_uppercase());
+    }
+    if let Some(n) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+        if (1..=24).contains(&n) {
+            return Some(format!("F{n}"));
+        }
+    }
+    if lower == "esc" {
+        return Some("Escape".to_string());
+    }
+    NAMED_KEYS
+        .iter()
+        .find(|name| name.to_ascii_lowercase() == lower)
+        .map(|name| name.to_string())
+}
+
+/// Parse a `+`-separated hotkey such as `ctrl+shift`, rejecting unknown or repeated tokens
+pub fn parse_hotkey(raw: &str) -> Result<NormalizedHotkey, String> {
+    let mut modifiers = Vec::new();
+    let mut key_token: Option<String> = None;
+    for token in raw.split('+').map(str::trim) {
+        if token.is_empty() {
+            return Err(format!("Hotkey \"{raw}\" has an empty key"));
+        }
+        if let Some(name) = modifier(token) {
+            if modifiers.contains(&name) {
+                return Err(format!("Hotkey \"{raw}\" repeats {name}"));
+            }
+            modifiers.push(name);
+        } else if let Some(name) = key(token) {
+            if let Some(existing) = &key_token {
+                return Err(format!(
+                    "Hotkey \"{raw}\" has more than one key ({existing} and {name})"
+                ));
+            }
+            key_token = Some(name);
+        } else {
+            return Err(format!("Hotkey \"{raw}\" has an unknown key \"{token}\""));
+        }
+    }
+    // A bare key would start dictation on every ordinary press of it
+    let function_key = key_token
+        .as_deref()
+        .and_then(|k| k.strip_prefix('F'))
+        .is_some_and(|n| n.parse::<u8>().is_ok());
+    if modifiers.is_empty() && !function_key {
+        return Err(format!("Hotkey \"{raw}\" needs at least one modifier"));
+    }
+    modifiers.sort_by_key(|name| MODIFIERS.iter().position(|(m, _)| m == name));
+    Ok(NormalizedHotkey {
+        modifiers,
+        key: key_token,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hotkeys_normalize_and_reject_typos() {
+        let parsed = parse_hotkey(" shift + control ").unwrap();
+        assert_eq!(parsed.to_string(), "Ctrl+Shift");
+        assert_eq!(
+            parse_hotkey("cmd+alt+f5").unwrap().to_string(),
+            "Alt+Super+F5"
+        );
+        assert_eq!(parse_hotkey("F13").unwrap().to_string(), "F13");
+        assert!(parse_hotkey("Ctrl+Shft").is_err());
+        assert!(parse_hotkey("Ctrl+Ctrl").is_err());
+        assert!(parse_hotkey("Ctrl+A+B").is_err());
+        assert!(parse_hotkey("A").is_err());
+        assert!(parse_hotkey("Ctrl+").is_err());
+    }
+}
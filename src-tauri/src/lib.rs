@@ -1,42 +1,428 @@
 use serde::{Deserialize, Serialize};
-use std::fs::OpenOptions;
+use std::collections::{HashMap, VecDeque};
 use std::io::{BufRead, BufReader, Write};
 use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
-use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::{Arc, Mutex, OnceLock};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
 use std::time::Duration;
 use tauri::image::Image;
-use tauri::menu::{MenuBuilder, MenuItemBuilder};
-use tauri::tray::{TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::menu::{MenuBuilder, MenuItem, MenuItemBuilder};
+use tauri::tray::TrayIconBuilder;
 use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+use tauri_plugin_opener::OpenerExt;
 
-#[cfg(not(windows))]
-use tauri::{LogicalPosition, WebviewUrl, WebviewWindowBuilder};
+#[cfg(not(any(windows, target_os = "macos")))]
+use tauri::{LogicalPosition, LogicalSize, WebviewUrl, WebviewWindowBuilder};
 
+mod clipboard;
+mod control_server;
+mod hotkey;
+mod log_file;
 mod native_overlay;
+mod process_stats;
 mod system_audio;
+mod text_injection;
 
+#[cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
 #[cfg(windows)]
 use std::os::windows::process::{CommandExt, ExitStatusExt};
 
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// JSON protocol version this app speaks; must match `ENGINE_PROTOCOL` in python/main.py
+const ENGINE_PROTOCOL_VERSION: u32 = 4;
+
+/// Where finished transcripts go
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum OutputMode {
+    /// Into the active app, per `inject_method`
+    #[default]
+    Type,
+    /// Only onto the clipboard, for apps that drop injected keystrokes
+    Clipboard,
+    /// Into the active app, and left on the clipboard afterwards
+    Both,
+    /// Neither typed nor copied; transcripts only show in the app and overlay
+    Off,
+}
+
+impl OutputMode {
+    fn as_arg(self) -> &'static str {
+        match self {
+            OutputMode::Type => "type",
+            OutputMode::Clipboard => "clipboard",
+            OutputMode::Both => "both",
+            OutputMode::Off => "off",
+        }
+    }
+}
+
+/// Reads `outputMode`, or the `typeIntoActiveApp` bool it replaced
+fn deserialize_output_mode<'de, D>(deserializer: D) -> Result<OutputMode, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Saved {
+        Mode(OutputMode),
+        TypeIntoActiveApp(bool),
+    }
+    Ok(match Saved::deserialize(deserializer)? {
+        Saved::Mode(mode) => mode,
+        Saved::TypeIntoActiveApp(true) => OutputMode::Type,
+        // Turning typing off never meant taking over the clipboard
+        Saved::TypeIntoActiveApp(false) => OutputMode::Off,
+    })
+}
+
+/// How transcripts are typed when `output_mode` sends them to the active app
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum InjectMethod {
+    /// The engine pastes/types via synthetic keystrokes
+    #[default]
+    Keystrokes,
+    /// The app sets the focused element's value through UI Automation
+    Accessibility,
+}
+
+/// What to do with a transcript the foreground window refused
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum InjectFallback {
+    /// Copy it to the clipboard and flash the overlay so it can be pasted by hand
+    #[default]
+    Clipboard,
+    /// Only log it; the UI can notify from `stt:inject-failed`
+    Notify,
+    Drop,
+}
+
+/// How the dictation hotkey drives recording. The engine reports every press and release
+/// as `{"type": "hotkey", "down": bool}` regardless of mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum HotkeyMode {
+    /// Record only while the hotkey is held
+    #[default]
+    PushToTalk,
+    /// Each press starts or stops recording
+    Toggle,
+}
+
+impl HotkeyMode {
+    fn as_arg(self) -> &'static str {
+        match self {
+            HotkeyMode::PushToTalk => "pushToTalk",
+            HotkeyMode::Toggle => "toggle",
+        }
+    }
+}
+
+/// Screen edge the overlay bar sits against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum OverlayAnchor {
+    #[default]
+    Top,
+    Bottom,
+}
+
+/// How music is quieted while dictating
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum MuteMode {
+    /// Fade to `duck_ratio` of the current volume
+    #[default]
+    Duck,
+    /// Mute the output device outright
+    Mute,
+}
+
+/// Which volume a duck turns down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+enum DuckScope {
+    /// The output device's master volume
+    #[default]
+    Master,
+    /// Each other app's audio session, leaving our own sounds and system sounds alone
+    Sessions,
+}
+
+/// Recognition tweaks for `stt_retranscribe_last`; unknown keys are rejected
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct RetranscribeOverrides {
+    /// `Some(false)` strips punctuation from the result
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    punctuation: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lowercase: Option<bool>,
+}
+
+/// Workarounds applied while running inside a Remote Desktop session
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RemoteSessionBehavior {
+    #[default]
+    Normal,
+    /// Leave volume alone; RDP audio redirection fades poorly
+    DisableDucking,
+    /// Keep the layered overlay off screen; it redraws badly over RDP
+    DisableOverlay,
+    Both,
+}
+
+impl RemoteSessionBehavior {
+    fn disables_ducking(self) -> bool {
+        matches!(self, Self::DisableDucking | Self::Both)
+    }
+
+    fn disables_overlay(self) -> bool {
+        matches!(self, Self::DisableOverlay | Self::Both)
+    }
+}
+
+/// Loopback HTTP API for scripting dictation from other tools
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ControlServerConfig {
+    enabled: bool,
+    port: u16,
+}
+
+impl Default for ControlServerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 7345,
+        }
+    }
+}
+
+/// An alternative STT backend living in its own directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EngineDef {
+    name: String,
+    script_path: String,
+    #[serde(default)]
+    model_dir: Option<String>,
+    /// Extra CLI args appended after the standard engine args
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EngineList {
+    engines: Vec<EngineDef>,
+    /// `None` means the bundled parakeet engine
+    active: Option<String>,
+}
+
+/// When the overlay bar should be on screen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum OverlayMode {
+    #[default]
+    Always,
+    WhileRunning,
+    /// Only while the engine is recording or asked the bar to expand
+    WhileListening,
+    Never,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 struct SttConfig {
+    /// Layout of the saved file; older files are brought up to date by `migrate_config`
+    #[serde(default)]
+    config_version: u32,
     hotkey: String,
+    hotkey_mode: HotkeyMode,
     run_in_background: bool,
-    type_into_active_app: bool,
+    #[serde(
+        alias = "typeIntoActiveApp",
+        deserialize_with = "deserialize_output_mode"
+    )]
+    output_mode: OutputMode,
+    inject_method: InjectMethod,
+    inject_fallback: InjectFallback,
+    /// Buffer transcripts and only type them when the commit hotkey is pressed
+    commit_mode: bool,
+    commit_hotkey: String,
+    discard_hotkey: String,
+    /// Held during dictation, throws the utterance away instead of transcribing it
+    cancel_hotkey: Option<String>,
+    /// Starts or stops the engine; registered by the app, so it works while the engine is down
+    toggle_engine_hotkey: Option<String>,
+    remote_session_behavior: RemoteSessionBehavior,
+    control_server: ControlServerConfig,
+    /// Capture device id from `stt_list_audio_devices`; `None` uses the system default
+    mic_device_id: Option<String>,
+    /// Capture sample rate hint for the engine; `None` uses the device default
+    sample_rate: Option<u32>,
+    /// Capture channel hint; multi-channel input is downmixed to mono
+    channels: Option<u16>,
+    /// Dictation language code from `stt_list_languages`; `None` lets the model detect it
+    language: Option<String>,
+    /// Model directory under data/ to load instead of the bundled Parakeet model
+    model_name: Option<String>,
+    /// Restart the engine gracefully once its resident memory exceeds this many MiB
+    engine_memory_limit_mb: Option<u64>,
+    /// Respawn the engine with backoff when it exits with a failure status
+    auto_restart: bool,
+    /// Seconds without any engine output before it counts as stalled; 0 disables the check
+    engine_stall_timeout_secs: u64,
+    /// Restart a stalled engine instead of only reporting it
+    restart_on_stall: bool,
+    /// Interpreter to run the engine with; `None` probes embedded/pyw/pythonw/python
+    python_path: Option<String>,
+    /// Extra environment for the engine process, e.g. `CUDA_VISIBLE_DEVICES`; applied last
+    engine_env: HashMap<String, String>,
+    overlay_mode: OverlayMode,
+    /// Master switch for the overlay, toggled from the tray; off hides it whatever the mode
+    overlay_enabled: bool,
+    /// 0xRRGGBB fill behind the overlay bar; `None` follows the system theme
+    overlay_bg_color: Option<u32>,
+    /// 0xRRGGBB color of the level bars; `None` follows the system theme
+    overlay_bar_color: Option<u32>,
+    /// Index into `stt_list_monitors`; `None` or a missing index uses the primary monitor
+    overlay_monitor: Option<usize>,
+    overlay_anchor: OverlayAnchor,
+    /// Gap between the overlay bar and the anchored screen edge
+    overlay_offset_px: i32,
+    /// Start the engine on launch; off leaves it stopped until started from the tray or UI
+    auto_start_engine: bool,
+    /// Load the engine on launch but leave the hotkey disarmed until `stt_arm`
+    prewarm_on_start: bool,
+    /// 0xRRGGBB flash shown when a transcript was typed into the active app
+    overlay_type_flash_color: u32,
+    /// 0xRRGGBB flash shown when a transcript only went to the clipboard
+    overlay_clipboard_flash_color: u32,
+    /// 0xRRGGBB flash shown when the cancel hotkey discarded an utterance
+    overlay_cancel_flash_color: u32,
+    /// Tint the flash red→green by the transcript's confidence when the engine reports one
+    overlay_confidence_flash: bool,
+    /// Skip ducking when system volume is already at or below this scalar
+    duck_min_volume: f32,
+    /// Duck (or mute) other audio while dictating
+    duck_audio: bool,
+    mute_mode: MuteMode,
+    /// Fraction of the current volume music is ducked to while dictating
+    duck_ratio: f32,
+    /// Fade-down time when ducking starts; unsigned so negative values are rejected
+    duck_attack_ms: u64,
+    /// Fade-up time when ducked audio is restored
+    duck_release_ms: u64,
+    duck_scope: DuckScope,
+    /// Keep clipboard-routed transcripts in Windows clipboard history (Win+V)
+    clipboard_history: bool,
+    /// Remember recent transcripts for `stt_get_transcripts`; off stores none at all
+    keep_history: bool,
+    /// 0xRRGGBB pulse shown while the engine is stalled
+    overlay_unresponsive_color: u32,
+    /// Half-period of the stall pulse
+    overlay_pulse_interval_ms: u64,
+    /// Level bars in the expanded overlay; clamped to `native_overlay::MAX_BAR_COUNT`
+    overlay_bar_count: usize,
+    /// 0 for square corners; half the bar height or more makes a pill
+    overlay_corner_radius: i32,
+    /// How much wider and taller the overlay grows on hover; values below 1 are treated as 1
+    overlay_hover_scale_x: f32,
+    overlay_hover_scale_y: f32,
+    /// Delay before the hovered overlay collapses, so grazing it doesn't flicker; see `overlay_dwell_ms`
+    overlay_dwell_ms: u64,
+    /// Keep the overlay out of screen captures and recordings; needs Windows 10 2004+
+    exclude_from_capture: bool,
+    /// Persist the main window's position/size and restore it on launch
+    remember_window_geometry: bool,
+    /// Lower bound for the overlay width while it shows text
+    overlay_min_width: i32,
+    /// Upper bound for the overlay width while it shows text; longer text is ellipsized
+    overlay_max_width: i32,
+    /// Recent transcripts listed in the expanded overlay; 0 disables the queue
+    overlay_transcript_lines: usize,
+    /// How long each queued transcript stays before fading out
+    overlay_transcript_display_ms: u64,
+    engines: Vec<EngineDef>,
+    /// Name of the entry in `engines` to launch; `None` uses the bundled engine
+    active_engine: Option<String>,
 }
 
 impl Default for SttConfig {
     fn default() -> Self {
         Self {
+            config_version: CONFIG_VERSION,
             hotkey: "Ctrl+Shift".to_string(),
+            hotkey_mode: HotkeyMode::PushToTalk,
             run_in_background: true,
-            type_into_active_app: true,
+            output_mode: OutputMode::Type,
+            inject_method: InjectMethod::Keystrokes,
+            inject_fallback: InjectFallback::Clipboard,
+            commit_mode: false,
+            commit_hotkey: "Ctrl+Alt+Enter".to_string(),
+            discard_hotkey: "Ctrl+Alt+Backspace".to_string(),
+            cancel_hotkey: None,
+            toggle_engine_hotkey: None,
+            remote_session_behavior: RemoteSessionBehavior::Normal,
+            control_server: ControlServerConfig::default(),
+            engine_memory_limit_mb: None,
+            auto_restart: true,
+            engine_stall_timeout_secs: 30,
+            restart_on_stall: false,
+            python_path: None,
+            engine_env: HashMap::new(),
+            mic_device_id: None,
+            sample_rate: None,
+            channels: None,
+            language: None,
+            model_name: None,
+            overlay_mode: OverlayMode::Always,
+            overlay_enabled: true,
+            overlay_bg_color: None,
+            overlay_bar_color: None,
+            overlay_monitor: None,
+            overlay_anchor: OverlayAnchor::Top,
+            overlay_offset_px: OVERLAY_VERTICAL_MARGIN_PX,
+            auto_start_engine: true,
+            prewarm_on_start: false,
+            overlay_type_flash_color: 0x22C55E,
+            overlay_clipboard_flash_color: 0x3B82F6,
+            overlay_cancel_flash_color: 0xEF4444,
+            overlay_confidence_flash: true,
+            duck_min_volume: 0.0,
+            duck_audio: true,
+            mute_mode: MuteMode::Duck,
+            duck_ratio: 0.5,
+            duck_attack_ms: 150,
+            duck_release_ms: 150,
+            duck_scope: DuckScope::Master,
+            clipboard_history: false,
+            keep_history: true,
+            overlay_unresponsive_color: 0xF59E0B,
+            overlay_pulse_interval_ms: 500,
+            overlay_bar_count: native_overlay::DEFAULT_BAR_COUNT,
+            overlay_corner_radius: native_overlay::DEFAULT_CORNER_RADIUS,
+            overlay_hover_scale_x: OVERLAY_HOVER_SCALE_X,
+            overlay_hover_scale_y: OVERLAY_HOVER_SCALE_Y,
+            overlay_dwell_ms: 30,
+            exclude_from_capture: false,
+            remember_window_geometry: true,
+            overlay_min_width: 120,
+            overlay_max_width: 480,
+            overlay_transcript_lines: 3,
+            overlay_transcript_display_ms: 6000,
+            engines: Vec::new(),
+            active_engine: None,
         }
     }
 }
@@ -44,13 +430,175 @@ impl Default for SttConfig {
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct SttStatus {
+    /// The engine process is alive; it may still be loading
     running: bool,
+    /// The engine reported `ready`: model loaded and hotkey armed
+    ready: bool,
+    /// Unix milliseconds when the running engine was spawned
+    started_at: Option<u64>,
+    /// Exit code of the previous engine; `None` if it was killed by a signal or never exited
+    last_exit_code: Option<i32>,
+    /// Automatic restarts (crash backoff and memory limit) since launch
+    restart_count: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CompatReport {
+    app_protocol: u32,
+    engine_protocol: Option<u32>,
+    compatible: bool,
+    advice: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AudioDuckedEvent {
+    ducked: bool,
+}
+
+/// Capture format the engine actually opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AudioFormat {
+    sample_rate: u32,
+    channels: u16,
+}
+
+/// What the engine reports it is actually using, from its `engine_info` message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EngineInfo {
+    /// Name of the capture device, resolved from `mic_device_id` or the system default
+    mic: String,
+    sample_rate: u32,
+    model: String,
+    /// Compute device the model runs on, e.g. `cpu`
+    device: String,
+}
+
+/// Diagnostics bundled when the engine exits with a failure, sent as `stt:crash`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CrashReport {
+    /// -1 when the process was killed by a signal rather than exiting
+    exit_code: i32,
+    /// The last log lines before the exit, oldest first, as `[stream] line`
+    tail_logs: Vec<String>,
+    /// Config at the time of the crash, with `engine_env` values redacted
+    config: SttConfig,
+    engine_info: Option<EngineInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EnginePaths {
+    script_path: String,
+    script_exists: bool,
+    model_dir: String,
+    model_dir_exists: bool,
+    python_dir: String,
+    python_dir_exists: bool,
+}
+
+/// Whether the model the engine would load is on disk, so the UI can offer a download
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelStatus {
+    model_dir: String,
+    present: bool,
+    /// Expected files absent from `model_dir`; only known for the bundled model
+    missing_files: Vec<String>,
+}
+
+/// Whether an interpreter was found and which engine imports it lacks
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DoctorReport {
+    python_found: bool,
+    python_version: Option<String>,
+    missing_modules: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AudioDevice {
+    id: String,
+    name: String,
 }
 
+/// A display as reported by the OS, in physical pixels
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
+struct MonitorInfo {
+    /// Position in `available_monitors`, as stored in `overlay_monitor`
+    index: usize,
+    name: Option<String>,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    scale_factor: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CommitBufferEvent {
+    text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InjectFailedEvent {
+    reason: String,
+    text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
 struct TranscriptEvent {
     text: String,
+    /// False for interim text that a later event with the same `segment_id` replaces
+    is_final: bool,
+    segment_id: Option<String>,
+    /// From the end of the utterance's audio to the transcript reaching the app
+    latency_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ModelProgressEvent {
+    stage: String,
+    /// 0..1, absent when the engine can't tell how far along it is
+    pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LevelEvent {
+    level: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptEntry {
+    text: String,
+    /// Unix milliseconds when the transcript arrived
+    timestamp: u64,
+    /// See `TranscriptEvent::latency_ms`
+    latency_ms: Option<u64>,
+}
+
+/// Speech-end to transcript latency over the stored transcript history
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LatencyStats {
+    /// Transcripts that carried a capture timestamp
+    count: usize,
+    min_ms: u64,
+    avg_ms: u64,
+    max_ms: u64,
+    p95_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -58,23 +606,342 @@ struct TranscriptEvent {
 struct LogEvent {
     stream: String,
     line: String,
+    /// `error`, `warn` or `info`
+    level: String,
+    /// Unix milliseconds when the line was emitted
+    ts: u64,
+}
+
+impl LogEvent {
+    fn new(stream: &str, line: String) -> Self {
+        Self {
+            level: infer_log_level(stream, &line).to_string(),
+            stream: stream.to_string(),
+            line,
+            ts: now_millis(),
+        }
+    }
+}
+
+/// Level from a word leading the line, possibly after one `[source]` tag, e.g.
+/// `[error] ...`, `ERROR: ...`, `ValueError: ...` or `[python] Warning: ...`;
+/// otherwise stderr is a warning unless it is clearly chatter
+fn infer_log_level(stream: &str, line: &str) -> &'static str {
+    let mut rest = line.trim_start();
+    for _ in 0..2 {
+        let word = rest.trim_start_matches('[').to_ascii_lowercase();
+        let first = word.split_whitespace().next().unwrap_or_default();
+        if word.starts_with("error")
+            || word.starts_with("traceback")
+            || word.starts_with("fatal")
+            || first.ends_with("error:")
+            || first.ends_with("exception:")
+        {
+            return "error";
+        }
+        if word.starts_with("warn") {
+            return "warn";
+        }
+        if word.starts_with("info") || word.starts_with("debug") {
+            return "info";
+        }
+        match rest
+            .strip_prefix('[')
+            .and_then(|tagged| tagged.split_once(']'))
+        {
+            Some((_, after)) => rest = after.trim_start(),
+            None => break,
+        }
+    }
+    if stream == "stderr" && !is_stderr_chatter(line) {
+        "warn"
+    } else {
+        "info"
+    }
+}
+
+/// Stderr output that is plainly not a problem: blank lines and progress bars from
+/// model downloads (`45%|████     | 12/27 [00:03<00:04, 3.1it/s]`)
+fn is_stderr_chatter(line: &str) -> bool {
+    let line = line.trim();
+    line.is_empty() || line.contains("%|") || line.ends_with("it/s]") || line.ends_with("B/s]")
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadyEvent {
+    /// Time from spawn to `ready`
+    startup_ms: u64,
+}
+
+/// `kind` is one of `script_not_found`, `model_not_found`, `spawn_failed`, `engine_crashed`
+/// or `ready_timeout`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorEvent {
+    kind: String,
+    message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RawLineEvent {
+    stream: String,
+    line: String,
+}
+
+/// One command tried while launching the engine and the OS error it produced
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpawnAttempt {
+    command: String,
+    args: Vec<String>,
+    error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SpawnDiagnostics {
+    attempts: Vec<SpawnAttempt>,
+}
+
+impl SpawnDiagnostics {
+    fn record(
+        &mut self,
+        command: impl AsRef<std::ffi::OsStr>,
+        args: &[std::ffi::OsString],
+        error: &std::io::Error,
+    ) {
+        self.attempts.push(SpawnAttempt {
+            command: command.as_ref().to_string_lossy().into_owned(),
+            args: args
+                .iter()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect(),
+            error: error.to_string(),
+        });
+    }
+}
+
+/// Window or monitor rectangle in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WindowGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+/// One part of a `StateSnapshot`; a failed accessor becomes `{ "error": ... }`
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+enum SnapshotSection<T> {
+    Ok(T),
+    Err { error: String },
+}
+
+impl<T> From<Result<T, String>> for SnapshotSection<T> {
+    fn from(result: Result<T, String>) -> Self {
+        match result {
+            Ok(value) => Self::Ok(value),
+            Err(error) => Self::Err { error },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EngineSnapshot {
+    running: bool,
+    compat: CompatReport,
+    dictation_active: bool,
+    /// Unix millis of the engine's last output; 0 if not running
+    last_activity_ms: u64,
+    stalled: bool,
+    last_spawn: SpawnDiagnostics,
+    /// Capabilities from the engine's `engine_info` line
+    info: Option<EngineInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AudioSnapshot {
+    /// Format the engine last opened the microphone with
+    format: Option<AudioFormat>,
+    output_device: bool,
+    output_volume: SnapshotSection<Option<system_audio::OutputVolume>>,
+    /// Audio is ducked and waiting to be restored
+    awaiting_restore: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OverlaySnapshot {
+    backend: String,
+    visible: bool,
+    hover_requested: bool,
+    suppressed: bool,
+}
+
+/// Everything useful for a bug report, gathered in one call
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StateSnapshot {
+    taken_at_ms: u64,
+    config: SnapshotSection<SttConfig>,
+    status: SnapshotSection<SttStatus>,
+    /// Latency over the kept transcripts
+    stats: SnapshotSection<Option<LatencyStats>>,
+    recent_logs: SnapshotSection<Vec<LogEvent>>,
+    /// Newest first
+    transcripts: SnapshotSection<Vec<TranscriptEntry>>,
+    engine: SnapshotSection<EngineSnapshot>,
+    audio: SnapshotSection<AudioSnapshot>,
+    /// Read from flags that can't fail, so never an error marker
+    overlay: OverlaySnapshot,
 }
 
 struct InnerState {
     config: SttConfig,
     child: Option<Child>,
+    stdin: Option<ChildStdin>,
+    engine_protocol: Option<u32>,
+    /// Failed commands from the most recent launch, including fallbacks that preceded a success
+    last_spawn: SpawnDiagnostics,
+    next_request_id: u64,
+    /// Reported by the engine each time it opens the microphone
+    audio_format: Option<AudioFormat>,
+    /// Latest `engine_info` from the running engine
+    engine_info: Option<EngineInfo>,
+    /// Transcripts waiting for the commit hotkey in commit mode
+    commit_buffer: String,
+    /// Oldest first, capped at `TRANSCRIPT_HISTORY_LIMIT`; the newest entry is what
+    /// `stt_type_text` types when given no text
+    transcript_history: VecDeque<TranscriptEntry>,
+    /// Consecutive crash restarts; cleared after a healthy stretch of uptime
+    restart_attempts: AtomicU32,
+    /// Unix milliseconds when the running engine was spawned
+    started_at: Option<u64>,
+    /// Config the running engine was spawned with, so an edit that is reverted inside
+    /// the restart debounce doesn't restart it
+    launch_config: Option<SttConfig>,
+    /// Set by the engine's `ready` line; cleared on every spawn and exit
+    ready: bool,
+    last_exit_code: Option<i32>,
+    /// Automatic restarts since launch; unlike `restart_attempts` never cleared
+    restart_count: u32,
+    /// Callers waiting on a `response` line with the matching `requestId`
+    pending_requests: HashMap<u64, std::sync::mpsc::Sender<serde_json::Value>>,
+    /// `config` has changes not yet written to disk
+    config_dirty: bool,
+    /// Bumped on every change; a debounced save only writes if it is still current
+    config_save_generation: u64,
+}
+
+impl InnerState {
+    /// Newest final transcript; kept only while `keep_history` is on
+    fn last_transcript(&self) -> Option<String> {
+        self.transcript_history
+            .back()
+            .map(|entry| entry.text.clone())
+    }
 }
 
 #[derive(Clone)]
 struct AppState(Arc<Mutex<InnerState>>);
 
 impl AppState {
-    fn new() -> Self {
+    fn new(config: SttConfig) -> Self {
         Self(Arc::new(Mutex::new(InnerState {
-            config: SttConfig::default(),
+            config,
             child: None,
+            stdin: None,
+            engine_protocol: None,
+            last_spawn: SpawnDiagnostics::default(),
+            next_request_id: 0,
+            audio_format: None,
+            engine_info: None,
+            commit_buffer: String::new(),
+            transcript_history: VecDeque::new(),
+            restart_attempts: AtomicU32::new(0),
+            started_at: None,
+            launch_config: None,
+            ready: false,
+            last_exit_code: None,
+            restart_count: 0,
+            pending_requests: HashMap::new(),
+            config_dirty: false,
+            config_save_generation: 0,
         })))
     }
+
+    /// Write one JSON command line to the running engine's stdin
+    fn write_engine_command(&self, cmd: &serde_json::Value) -> Result<(), String> {
+        let mut guard = self.0.lock().map_err(|_| "State lock poisoned")?;
+        let stdin = guard.stdin.as_mut().ok_or("Engine is not running")?;
+        writeln!(stdin, "{cmd}").map_err(|e| format!("Failed to write to engine: {e}"))?;
+        stdin
+            .flush()
+            .map_err(|e| format!("Failed to write to engine: {e}"))
+    }
+
+    /// Send a command tagged with a fresh `requestId` and block until the engine replies.
+    /// Returns the reply's fields on `"ok": true`, otherwise its `error`.
+    fn request_engine(
+        &self,
+        mut cmd: serde_json::Value,
+        timeout: Duration,
+    ) -> Result<serde_json::Value, String> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let request_id = {
+            let mut guard = self.0.lock().map_err(|_| "State lock poisoned")?;
+            guard.next_request_id += 1;
+            let id = guard.next_request_id;
+            guard.pending_requests.insert(id, tx);
+            id
+        };
+        cmd["requestId"] = request_id.into();
+        let reply = self
+            .write_engine_command(&cmd)
+            .and_then(|()| match rx.recv_timeout(timeout) {
+                Ok(reply) => Ok(reply),
+                Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                    Err("Engine did not respond in time".to_string())
+                }
+                Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+                    Err("Engine exited before responding".to_string())
+                }
+            });
+        if let Ok(mut guard) = self.0.lock() {
+            guard.pending_requests.remove(&request_id);
+        }
+        let reply = reply?;
+        if reply.get("ok").and_then(|v| v.as_bool()) == Some(true) {
+            Ok(reply)
+        } else {
+            Err(reply
+                .get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Engine reported an error")
+                .to_string())
+        }
+    }
+
+    /// Hand a `response` line to whoever is waiting on its `requestId`
+    fn complete_request(&self, reply: serde_json::Value) {
+        let Some(request_id) = reply.get("requestId").and_then(|v| v.as_u64()) else {
+            return;
+        };
+        let sender = self
+            .0
+            .lock()
+            .ok()
+            .and_then(|mut g| g.pending_requests.remove(&request_id));
+        if let Some(sender) = sender {
+            let _ = sender.send(reply);
+        }
+    }
 }
 
 const OVERLAY_WIDTH_PX: i32 = 90;
@@ -82,8 +949,55 @@ const OVERLAY_HEIGHT_PX: i32 = 5;
 const OVERLAY_HORIZONTAL_OFFSET_PX: i32 = 0;
 const OVERLAY_VERTICAL_MARGIN_PX: i32 = 16;
 
+/// Default hover growth; see `overlay_hover_scale_x`
 const OVERLAY_HOVER_SCALE_X: f32 = 1.15;
 const OVERLAY_HOVER_SCALE_Y: f32 = 5.0;
+const OVERLAY_FLASH_MS: u64 = 180;
+/// Frame interval of the fake level driven by `overlay_preview`
+const OVERLAY_PREVIEW_FRAME_MS: u64 = 33;
+/// One rise and fall of the preview level
+const OVERLAY_PREVIEW_PERIOD_MS: u64 = 700;
+const CONFIDENCE_LOW_COLOR: u32 = 0xEF4444;
+const CONFIDENCE_HIGH_COLOR: u32 = 0x22C55E;
+/// Re-decoding a long utterance can take a few seconds on CPU
+const ENGINE_REQUEST_TIMEOUT_MS: u64 = 15_000;
+/// Longest recording `stt_dictate_once` accepts
+const DICTATE_ONCE_MAX_MS: u64 = 60_000;
+/// How long a `shutdown` command gets to exit the engine before it is killed
+const ENGINE_SHUTDOWN_GRACE_MS: u64 = 2000;
+const ENGINE_RESTART_BASE_MS: u64 = 1000;
+const ENGINE_RESTART_MAX_MS: u64 = 30_000;
+/// Automatic restarts in a row before the engine is left stopped
+const ENGINE_RESTART_MAX_ATTEMPTS: u32 = 10;
+/// Uptime after which a crash counts as the first again
+const ENGINE_HEALTHY_UPTIME_MS: u64 = 60_000;
+const TRANSCRIPT_HISTORY_LIMIT: usize = 200;
+/// `stt:log` events kept for views opened after the lines were emitted
+const RECENT_LOG_LIMIT: usize = 500;
+/// Log lines attached to a crash report
+const CRASH_TAIL_LINES: usize = 50;
+const WINDOW_STATE_FILE: &str = "window-state.json";
+const CONFIG_FILE: &str = "config.json";
+/// Bumped whenever a saved setting changes meaning; see `migrate_config`
+const CONFIG_VERSION: u32 = 1;
+const WINDOW_STATE_SAVE_DEBOUNCE_MS: u64 = 400;
+/// Quiet period after `stt_apply_config` before an engine restart, so a burst of edits restarts once
+const CONFIG_RESTART_DEBOUNCE_MS: u64 = 500;
+/// Config changes within this window are written to disk once
+const CONFIG_SAVE_DEBOUNCE_MS: u64 = 500;
+/// Top-level imports of python/main.py, matching python/requirements.txt
+const ENGINE_REQUIRED_MODULES: [&str; 5] =
+    ["numpy", "sherpa_onnx", "sounddevice", "pynput", "pyautogui"];
+/// Run as `python -c DOCTOR_SCRIPT <modules>`; prints the version and the modules not found
+const DOCTOR_SCRIPT: &str = "import importlib.util, json, sys; \
+print(json.dumps({'version': sys.version.split()[0], \
+'missing': [m for m in sys.argv[1:] if importlib.util.find_spec(m) is None]}))";
+/// How long an armed engine has to report `ready` before `stt:error` kind `ready_timeout`
+const ENGINE_READY_TIMEOUT_MS: u64 = 60_000;
+/// Longest hover collapse dwell accepted from config
+const OVERLAY_MAX_DWELL_MS: u64 = 500;
+/// Minimum overlap of the title strip with a monitor for a saved position to count as reachable
+const WINDOW_MIN_VISIBLE_PX: i32 = 64;
 
 // Track overlay visibility and debounce sequence for hover collapse dwell
 static OVERLAY_VISIBLE: OnceLock<AtomicBool> = OnceLock::new();
@@ -91,6 +1005,31 @@ static HOVER_DWELL_SEQ: OnceLock<AtomicU64> = OnceLock::new();
 static SOUND_EFFECTS_ENABLED: OnceLock<AtomicBool> = OnceLock::new();
 static DICTATION_ACTIVE: OnceLock<AtomicBool> = OnceLock::new();
 static DICTATION_LAST_START_MS: OnceLock<AtomicU64> = OnceLock::new();
+static OVERLAY_HOVER_REQUESTED: OnceLock<AtomicBool> = OnceLock::new();
+static RAW_TAP_ENABLED: OnceLock<AtomicBool> = OnceLock::new();
+static OVERLAY_RECONCILE_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+// Held from a spawn or stop's child check through the store or take that follows,
+// so racing callers can never leave two engines running
+static ENGINE_LIFECYCLE_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+// Temporary suppression; the sequence cancels restore timers from earlier calls
+static OVERLAY_SUPPRESSED: OnceLock<AtomicBool> = OnceLock::new();
+static OVERLAY_SUPPRESS_SEQ: OnceLock<AtomicU64> = OnceLock::new();
+static WINDOW_GEOMETRY_SEQ: OnceLock<AtomicU64> = OnceLock::new();
+static CONFIG_RESTART_SEQ: OnceLock<AtomicU64> = OnceLock::new();
+// Bumped on every overlay_preview call; the animation thread stops once it changes
+static OVERLAY_PREVIEW_SEQ: OnceLock<AtomicU64> = OnceLock::new();
+// Bumped per spawn, arm and stop so a stale ready timer never fires
+static ENGINE_READY_SEQ: OnceLock<AtomicU64> = OnceLock::new();
+// Bumped by output only real work produces; set at spawn so an engine that never speaks
+// still trips it
+static ENGINE_LAST_ACTIVITY_MS: OnceLock<AtomicU64> = OnceLock::new();
+static ENGINE_STALLED: OnceLock<AtomicBool> = OnceLock::new();
+// Tray entry whose label follows `overlay_enabled`
+static OVERLAY_TRAY_ITEM: OnceLock<MenuItem<tauri::Wry>> = OnceLock::new();
+// Tray entry that is only enabled while a transcript is kept to copy
+static COPY_TRANSCRIPT_TRAY_ITEM: OnceLock<MenuItem<tauri::Wry>> = OnceLock::new();
+// Outside `InnerState` so logging never waits on, or deadlocks against, the state lock
+static RECENT_LOGS: OnceLock<Mutex<VecDeque<LogEvent>>> = OnceLock::new();
 
 fn overlay_visible_flag() -> &'static AtomicBool {
     OVERLAY_VISIBLE.get_or_init(|| AtomicBool::new(false))
@@ -112,6 +1051,58 @@ fn dictation_last_start_ms() -> &'static AtomicU64 {
     DICTATION_LAST_START_MS.get_or_init(|| AtomicU64::new(0))
 }
 
+fn overlay_hover_requested_flag() -> &'static AtomicBool {
+    OVERLAY_HOVER_REQUESTED.get_or_init(|| AtomicBool::new(false))
+}
+
+fn raw_tap_enabled_flag() -> &'static AtomicBool {
+    RAW_TAP_ENABLED.get_or_init(|| AtomicBool::new(false))
+}
+
+fn overlay_reconcile_lock() -> &'static Mutex<()> {
+    OVERLAY_RECONCILE_LOCK.get_or_init(|| Mutex::new(()))
+}
+
+fn engine_lifecycle_lock() -> &'static Mutex<()> {
+    ENGINE_LIFECYCLE_LOCK.get_or_init(|| Mutex::new(()))
+}
+
+fn recent_logs_storage() -> &'static Mutex<VecDeque<LogEvent>> {
+    RECENT_LOGS.get_or_init(|| Mutex::new(VecDeque::new()))
+}
+
+fn overlay_suppressed_flag() -> &'static AtomicBool {
+    OVERLAY_SUPPRESSED.get_or_init(|| AtomicBool::new(false))
+}
+
+fn overlay_suppress_seq() -> &'static AtomicU64 {
+    OVERLAY_SUPPRESS_SEQ.get_or_init(|| AtomicU64::new(0))
+}
+
+fn window_geometry_seq() -> &'static AtomicU64 {
+    WINDOW_GEOMETRY_SEQ.get_or_init(|| AtomicU64::new(0))
+}
+
+fn config_restart_seq() -> &'static AtomicU64 {
+    CONFIG_RESTART_SEQ.get_or_init(|| AtomicU64::new(0))
+}
+
+fn overlay_preview_seq() -> &'static AtomicU64 {
+    OVERLAY_PREVIEW_SEQ.get_or_init(|| AtomicU64::new(0))
+}
+
+fn engine_ready_seq() -> &'static AtomicU64 {
+    ENGINE_READY_SEQ.get_or_init(|| AtomicU64::new(0))
+}
+
+fn engine_last_activity_ms() -> &'static AtomicU64 {
+    ENGINE_LAST_ACTIVITY_MS.get_or_init(|| AtomicU64::new(0))
+}
+
+fn engine_stalled_flag() -> &'static AtomicBool {
+    ENGINE_STALLED.get_or_init(|| AtomicBool::new(false))
+}
+
 fn now_millis() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     SystemTime::now()
@@ -133,12 +1124,77 @@ fn emit_dictation_start(app: &AppHandle) {
         dictation_last_start_ms().store(now, Ordering::SeqCst);
         let _ = app.emit("stt:dictation-start", ());
     }
+    reconcile_overlay(app);
+}
+
+/// Forget stall state when the engine goes away so a restart starts clean
+fn reset_engine_activity() {
+    engine_last_activity_ms().store(0, Ordering::SeqCst);
+    engine_stalled_flag().store(false, Ordering::SeqCst);
+    native_overlay::stop_pulse();
+}
+
+/// Record output from the engine; a stalled engine that speaks again stops pulsing
+fn engine_activity() {
+    engine_last_activity_ms().store(now_millis(), Ordering::SeqCst);
+    if engine_stalled_flag().swap(false, Ordering::SeqCst) {
+        native_overlay::stop_pulse();
+    }
+}
+
+/// Silence only counts while the engine owes output: loading the model or dictating.
+/// An idle engine waiting for the hotkey is quiet by design
+fn engine_stalled(
+    awaiting_output: bool,
+    last_activity_ms: u64,
+    now_ms: u64,
+    timeout_secs: u64,
+) -> bool {
+    awaiting_output
+        && timeout_secs > 0
+        && last_activity_ms > 0
+        && now_ms.saturating_sub(last_activity_ms) >= timeout_secs.saturating_mul(1000)
+}
+
+/// Called from the engine monitor loop; reports a silent engine once per stall and
+/// returns true when it should be restarted
+fn check_engine_stall(app: &AppHandle) -> bool {
+    let config = current_config(app);
+    let last = engine_last_activity_ms().load(Ordering::SeqCst);
+    // `engine_ready` arrives once the model has loaded
+    let loading = app
+        .state::<AppState>()
+        .0
+        .lock()
+        .is_ok_and(|guard| guard.engine_protocol.is_none());
+    let awaiting_output = loading || dictation_active_flag().load(Ordering::SeqCst);
+    if !engine_stalled(
+        awaiting_output,
+        last,
+        now_millis(),
+        config.engine_stall_timeout_secs,
+    ) || engine_stalled_flag().swap(true, Ordering::SeqCst)
+    {
+        return false;
+    }
+    let message = format!(
+        "engine produced no output for {} s",
+        config.engine_stall_timeout_secs
+    );
+    emit_log(app, "engine", &message);
+    emit_error(app, "engine_stalled", &message);
+    let _ = native_overlay::pulse(
+        config.overlay_unresponsive_color,
+        config.overlay_pulse_interval_ms,
+    );
+    config.restart_on_stall
 }
 
 fn emit_dictation_stop(app: &AppHandle) {
     if dictation_active_flag().swap(false, Ordering::SeqCst) {
         let _ = app.emit("stt:dictation-stop", ());
     }
+    reconcile_overlay(app);
 }
 
 #[cfg(test)]
@@ -150,552 +1206,4018 @@ mod tests {
         let config = SttConfig::default();
         assert_eq!(config.hotkey, "Ctrl+Shift");
         assert!(config.run_in_background);
-        assert!(config.type_into_active_app);
+        assert_eq!(config.output_mode, OutputMode::Type);
+        assert_eq!(config.inject_method, InjectMethod::Keystrokes);
+        assert_eq!(config.overlay_mode, OverlayMode::Always);
+        assert!(config.overlay_enabled);
+        assert_eq!(config.overlay_bg_color, None);
+        assert_eq!(config.overlay_bar_color, None);
+        assert_eq!(
+            overlay_colors(&config, false),
+            native_overlay::OverlayColors::default()
+        );
+        assert_eq!(config.inject_fallback, InjectFallback::Clipboard);
+        assert!(!config.commit_mode);
+        assert!(!config.control_server.enabled);
+        assert_eq!(config.toggle_engine_hotkey, None);
+        assert!(config.auto_start_engine);
+        assert!(engine_types_into_active_app(&config));
     }
 
     #[test]
-    fn now_millis_nonzero() {
-        assert!(now_millis() > 0);
+    fn saved_config_round_trips() {
+        let config = SttConfig {
+            hotkey: "Ctrl+Alt".to_string(),
+            run_in_background: false,
+            ..SttConfig::default()
+        };
+        let saved = serde_json::to_string_pretty(&config).unwrap();
+        let loaded: SttConfig = serde_json::from_str(&saved).unwrap();
+        assert_eq!(loaded.hotkey, "Ctrl+Alt");
+        assert!(!loaded.run_in_background);
     }
-}
 
-#[cfg_attr(not(windows), allow(unused_variables))]
-fn configure_overlay(app: &AppHandle) -> Result<(), String> {
-    #[cfg(windows)]
+    #[test]
+    fn audio_devices_parse_from_last_json_line() {
+        let stdout = "[python] Engine starting…\n[{\"id\": \"Mic, MME\", \"name\": \"Mic\"}]\n";
+        let devices = parse_audio_devices(stdout).unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].id, "Mic, MME");
+        assert!(parse_audio_devices("[python] Engine starting…\n").is_err());
+    }
+
+    #[test]
+    fn python_path_must_exist() {
+        assert!(validate_python_path(None).is_ok());
+        let err = validate_python_path(Some("/no/such/python")).unwrap_err();
+        assert!(err.contains("/no/such/python"));
+    }
+
+    #[test]
+    fn model_status_lists_missing_files() {
+        let dir = std::env::temp_dir().join(format!("jargon-model-test-{}", now_millis()));
+        let status = model_status(&dir, false);
+        assert!(!status.present);
+        assert!(status.missing_files.is_empty());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("tokens.txt"), "").unwrap();
+        let status = model_status(&dir, false);
+        assert!(!status.present);
+        assert_eq!(status.missing_files.len(), MODEL_REQUIRED_FILES.len() - 1);
+        assert!(model_status(&dir, true).present);
+
+        for file in MODEL_REQUIRED_FILES {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+        assert!(model_status(&dir, false).present);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn language_must_be_supported_by_the_bundled_engine() {
+        assert!(validate_language(None, false).is_ok());
+        assert!(validate_language(Some("es"), false).is_ok());
+        assert!(validate_language(Some("tlh"), false).is_err());
+        assert!(validate_language(Some("tlh"), true).is_ok());
+    }
+
+    #[test]
+    fn model_names_cannot_leave_the_data_directory() {
+        assert!(validate_model_name(None).is_ok());
+        assert!(validate_model_name(Some("parakeet-tdt-0.6b-v3")).is_ok());
+        for bad in ["", "..", "../models", "a/b", "a\\b"] {
+            assert!(validate_model_name(Some(bad)).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn restart_backoff_doubles_up_to_the_cap() {
+        assert_eq!(restart_delay_ms(0), 1000);
+        assert_eq!(restart_delay_ms(1), 2000);
+        assert_eq!(restart_delay_ms(2), 4000);
+        assert_eq!(restart_delay_ms(5), 30_000);
+        assert_eq!(restart_delay_ms(40), 30_000);
+    }
+
+    #[test]
+    fn restart_attempts_run_out_and_then_start_over() {
+        let state = AppState::new(SttConfig::default());
+        let mut guard = state.0.lock().unwrap();
+        for expected in 0..ENGINE_RESTART_MAX_ATTEMPTS {
+            assert_eq!(next_restart_attempt(&mut guard), Some(expected));
+        }
+        assert_eq!(next_restart_attempt(&mut guard), None);
+        assert_eq!(guard.restart_count, ENGINE_RESTART_MAX_ATTEMPTS);
+        assert_eq!(next_restart_attempt(&mut guard), Some(0));
+    }
+
+    #[test]
+    fn transcript_history_is_capped_and_newest_first() {
+        let mut history = VecDeque::new();
+        for i in 0..TRANSCRIPT_HISTORY_LIMIT + 5 {
+            let entry = TranscriptEntry {
+                text: i.to_string(),
+                timestamp: i as u64,
+                latency_ms: None,
+            };
+            push_transcript_history(&mut history, entry);
+        }
+        assert_eq!(history.len(), TRANSCRIPT_HISTORY_LIMIT);
+        assert_eq!(history.front().unwrap().text, "5");
+        let recent = recent_transcripts(&history, Some(2));
+        let texts: Vec<_> = recent.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, ["204", "203"]);
+        assert_eq!(
+            recent_transcripts(&history, None).len(),
+            TRANSCRIPT_HISTORY_LIMIT
+        );
+    }
+
+    #[test]
+    fn latency_stats_cover_transcripts_with_a_capture_time() {
+        let mut history = VecDeque::new();
+        assert_eq!(latency_stats(&history), None);
+        for latency_ms in (1..=20).map(|i| Some(i * 10)).chain([None]) {
+            let entry = TranscriptEntry {
+                text: String::new(),
+                timestamp: 0,
+                latency_ms,
+            };
+            push_transcript_history(&mut history, entry);
+        }
+        let stats = latency_stats(&history).unwrap();
+        assert_eq!(stats.count, 20);
+        assert_eq!((stats.min_ms, stats.max_ms), (10, 200));
+        assert_eq!(stats.avg_ms, 105);
+        assert_eq!(stats.p95_ms, 190);
+    }
+
+    #[test]
+    fn recent_logs_keep_the_newest_in_order() {
+        let mut logs = VecDeque::new();
+        for i in 0..RECENT_LOG_LIMIT + 3 {
+            push_recent_log(&mut logs, LogEvent::new("stdout", i.to_string()));
+        }
+        assert_eq!(logs.len(), RECENT_LOG_LIMIT);
+        let tail: Vec<_> = recent_logs(&logs, Some(2))
+            .into_iter()
+            .map(|event| event.line)
+            .collect();
+        assert_eq!(tail, ["501", "502"]);
+        assert_eq!(recent_logs(&logs, None)[0].line, "3");
+        assert_eq!(recent_logs(&logs, Some(0)).len(), 0);
+    }
+
+    #[test]
+    fn crash_reports_carry_the_log_tail_without_env_values() {
+        let mut logs = VecDeque::new();
+        for i in 0..CRASH_TAIL_LINES + 5 {
+            push_recent_log(&mut logs, LogEvent::new("stderr", i.to_string()));
+        }
+        let config = SttConfig {
+            engine_env: HashMap::from([("HF_TOKEN".to_string(), "hf_secret".to_string())]),
+            ..SttConfig::default()
+        };
+        let report = crash_report(None, &logs, config, None);
+        assert_eq!(report.exit_code, -1);
+        assert_eq!(report.tail_logs.len(), CRASH_TAIL_LINES);
+        assert_eq!(report.tail_logs[0], "[stderr] 5");
+        assert_eq!(report.config.engine_env["HF_TOKEN"], "<redacted>");
+        let json = serde_json::to_value(&report).unwrap();
+        assert!(json["tailLogs"].is_array());
+        assert!(json.get("engineInfo").is_some());
+    }
+
+    #[test]
+    fn commit_mode_keeps_the_engine_from_typing() {
+        let config = SttConfig {
+            commit_mode: true,
+            ..SttConfig::default()
+        };
+        assert!(!engine_types_into_active_app(&config));
+        assert!(config.commit_hotkey.parse::<Shortcut>().is_ok());
+        assert!(config.discard_hotkey.parse::<Shortcut>().is_ok());
+    }
+
+    #[test]
+    fn duck_timings_reject_negative_values() {
+        let parsed: SttConfig =
+            serde_json::from_str(r#"{"duckAttackMs": 20, "duckReleaseMs": 600}"#).unwrap();
+        assert_eq!(duck_config(&parsed).attack_ms, 20);
+        assert_eq!(duck_config(&parsed).release_ms, 600);
+        assert!(serde_json::from_str::<SttConfig>(r#"{"duckAttackMs": -1}"#).is_err());
+    }
+
+    #[test]
+    fn duck_ratio_and_fades_are_clamped() {
+        assert_eq!(duck_config(&SttConfig::default()).ratio, 0.5);
+        let parsed: SttConfig = serde_json::from_str(
+            r#"{"duckRatio": 1.5, "duckAttackMs": 10000, "duckReleaseMs": 2000}"#,
+        )
+        .unwrap();
+        let duck = duck_config(&parsed);
+        assert_eq!(duck.ratio, 1.0);
+        assert_eq!(duck.attack_ms, 2000);
+        assert_eq!(duck.release_ms, 2000);
+        let parsed: SttConfig = serde_json::from_str(r#"{"duckRatio": -0.2}"#).unwrap();
+        assert_eq!(duck_config(&parsed).ratio, 0.0);
+    }
+
+    #[test]
+    fn duck_audio_gates_new_ducks() {
+        assert!(duck_config(&SttConfig::default()).enabled);
+        let parsed: SttConfig = serde_json::from_str(r#"{"duckAudio": false}"#).unwrap();
+        assert!(!duck_config(&parsed).enabled);
+    }
+
+    #[test]
+    fn mute_mode_selects_muting() {
+        assert!(!duck_config(&SttConfig::default()).mute);
+        let parsed: SttConfig = serde_json::from_str(r#"{"muteMode": "mute"}"#).unwrap();
+        assert!(duck_config(&parsed).mute);
+    }
+
+    #[test]
+    fn remote_session_behavior_flags() {
+        assert!(!RemoteSessionBehavior::Normal.disables_ducking());
+        assert!(!RemoteSessionBehavior::Normal.disables_overlay());
+        assert!(RemoteSessionBehavior::DisableDucking.disables_ducking());
+        assert!(!RemoteSessionBehavior::DisableDucking.disables_overlay());
+        assert!(RemoteSessionBehavior::DisableOverlay.disables_overlay());
+        assert!(RemoteSessionBehavior::Both.disables_ducking());
+        assert!(RemoteSessionBehavior::Both.disables_overlay());
+    }
+
+    #[test]
+    fn overlay_visibility_follows_mode() {
+        assert!(overlay_should_be_visible(
+            OverlayMode::Always,
+            false,
+            false,
+            false
+        ));
+        assert!(!overlay_should_be_visible(
+            OverlayMode::Never,
+            true,
+            true,
+            true
+        ));
+        assert!(overlay_should_be_visible(
+            OverlayMode::WhileRunning,
+            true,
+            false,
+            false
+        ));
+        assert!(!overlay_should_be_visible(
+            OverlayMode::WhileRunning,
+            false,
+            true,
+            true
+        ));
+        assert!(!overlay_should_be_visible(
+            OverlayMode::WhileListening,
+            true,
+            false,
+            false
+        ));
+        assert!(overlay_should_be_visible(
+            OverlayMode::WhileListening,
+            true,
+            true,
+            false
+        ));
+        assert!(overlay_should_be_visible(
+            OverlayMode::WhileListening,
+            true,
+            false,
+            true
+        ));
+        assert!(!overlay_should_be_visible(
+            OverlayMode::WhileListening,
+            false,
+            true,
+            true
+        ));
+    }
+
+    #[test]
+    fn engine_types_only_with_keystroke_injection() {
+        let mut config = SttConfig::default();
+        assert!(engine_types_into_active_app(&config));
+        config.inject_method = InjectMethod::Accessibility;
+        assert!(!engine_types_into_active_app(&config));
+        config.inject_method = InjectMethod::Keystrokes;
+        config.output_mode = OutputMode::Clipboard;
+        assert!(!engine_types_into_active_app(&config));
+        config.output_mode = OutputMode::Both;
+        assert!(engine_types_into_active_app(&config));
+        config.output_mode = OutputMode::Off;
+        assert!(!engine_types_into_active_app(&config));
+    }
+
+    #[test]
+    fn transcripts_reach_the_clipboard_only_when_chosen() {
+        let mut config = SttConfig::default();
+        assert_eq!(transcript_route(&config), TranscriptRoute::Typed);
+        config.output_mode = OutputMode::Clipboard;
+        assert_eq!(transcript_route(&config), TranscriptRoute::Clipboard);
+        config.output_mode = OutputMode::Both;
+        assert_eq!(transcript_route(&config), TranscriptRoute::Both);
+        config.output_mode = OutputMode::Off;
+        assert_eq!(transcript_route(&config), TranscriptRoute::Shown);
+    }
+
+    #[test]
+    fn output_mode_reads_the_legacy_type_switch() {
+        let parse = |json: &str| serde_json::from_str::<SttConfig>(json).unwrap().output_mode;
+        assert_eq!(parse(r#"{"typeIntoActiveApp": false}"#), OutputMode::Off);
+        assert_eq!(parse(r#"{"typeIntoActiveApp": true}"#), OutputMode::Type);
+        assert_eq!(parse(r#"{"outputMode": "both"}"#), OutputMode::Both);
+        assert_eq!(parse("{}"), OutputMode::Type);
+    }
+
+    #[test]
+    fn active_engine_def_matches_by_name() {
+        let mut config = SttConfig::default();
+        config.engines.push(EngineDef {
+            name: "whisper".to_string(),
+            script_path: "C:/engines/whisper/run_whisper.py".to_string(),
+            model_dir: None,
+            args: vec!["--beam-size".to_string(), "5".to_string()],
+        });
+        assert!(active_engine_def(&config).is_none());
+
+        assert!(engine_requires_model_dir(&config));
+        config.active_engine = Some("whisper".to_string());
+        assert!(!engine_requires_model_dir(&config));
+        let def = active_engine_def(&config).expect("whisper engine");
+        assert_eq!(
+            script_module_name(std::path::Path::new(&def.script_path)),
+            "run_whisper"
+        );
+
+        let args = engine_args(&config, std::path::Path::new("models"), true);
+        assert_eq!(args.last().map(|a| a.as_os_str()), Some("5".as_ref()));
+
+        config.active_engine = Some("missing".to_string());
+        assert!(active_engine_def(&config).is_none());
+    }
+
+    #[test]
+    fn engine_args_carry_the_normalized_hotkey_and_mode() {
+        let config = SttConfig {
+            hotkey: "shift+ctrl".to_string(),
+            hotkey_mode: HotkeyMode::Toggle,
+            ..SttConfig::default()
+        };
+        let args = engine_args(&config, std::path::Path::new("models"), true);
+        let args: Vec<_> = args.iter().map(|a| a.to_string_lossy()).collect();
+        assert_eq!(
+            args[..4],
+            ["--hotkey", "Ctrl+Shift", "--hotkey-mode", "toggle"]
+        );
+        assert_eq!(SttConfig::default().hotkey_mode, HotkeyMode::PushToTalk);
+
+        let config = SttConfig {
+            cancel_hotkey: Some("esc+ctrl".to_string()),
+            ..config
+        };
+        let args = engine_args(&config, std::path::Path::new("models"), true);
+        let args: Vec<_> = args.iter().map(|a| a.to_string_lossy()).collect();
+        let at = args.iter().position(|a| a == "--cancel-hotkey").unwrap();
+        assert_eq!(args[at + 1], "Ctrl+Escape");
+    }
+
+    #[test]
+    fn engine_python_path_handles_spaces_and_unicode() {
+        let python_dir = std::path::Path::new("Program Files")
+            .join("Jàrgon Ünïcode")
+            .join("python");
+        let existing = std::env::join_paths([std::path::Path::new("site packages")]).unwrap();
+
+        let joined = engine_python_path(&python_dir, Some(existing));
+        let parts: Vec<PathBuf> = std::env::split_paths(&joined).collect();
+        assert_eq!(
+            parts,
+            vec![python_dir.clone(), PathBuf::from("site packages")]
+        );
+
+        let alone = engine_python_path(&python_dir, None);
+        assert_eq!(
+            std::env::split_paths(&alone).collect::<Vec<_>>(),
+            vec![python_dir]
+        );
+    }
+
+    #[test]
+    fn compat_report_flags_version_skew() {
+        let current = compat_report(ENGINE_PROTOCOL_VERSION, Some(ENGINE_PROTOCOL_VERSION));
+        assert!(current.compatible);
+
+        let old_engine = compat_report(2, Some(1));
+        assert!(!old_engine.compatible);
+        assert!(old_engine.advice.contains("Python engine"));
+
+        let old_app = compat_report(1, Some(2));
+        assert!(!old_app.compatible);
+        assert!(old_app.advice.contains("update the app"));
+
+        assert!(!compat_report(1, None).compatible);
+    }
+
+    #[test]
+    fn unset_overlay_colors_follow_the_theme() {
+        let dark = native_overlay::OverlayColors::for_theme(true);
+        assert_ne!(dark, native_overlay::OverlayColors::default());
+        assert_eq!(overlay_colors(&SttConfig::default(), true), dark);
+
+        let config = SttConfig {
+            overlay_bg_color: Some(0xFF00_0000),
+            ..SttConfig::default()
+        };
+        let colors = overlay_colors(&config, true);
+        assert_eq!(colors.background, 0x000000);
+        assert_eq!(colors.bar, dark.bar);
+    }
+
+    #[test]
+    fn window_geometry_recentres_when_off_screen() {
+        let primary = WindowGeometry {
+            x: 0,
+            y: 0,
+            width: 1920,
+            height: 1080,
+        };
+        let secondary = WindowGeometry {
+            x: 1920,
+            y: 0,
+            width: 1280,
+            height: 1024,
+        };
+        let on_secondary = WindowGeometry {
+            x: 2000,
+            y: 100,
+            width: 1000,
+            height: 780,
+        };
+        assert_eq!(
+            clamp_window_geometry(on_secondary, &[primary, secondary]),
+            on_secondary
+        );
+
+        // Secondary monitor unplugged since the last run
+        assert_eq!(
+            clamp_window_geometry(on_secondary, &[primary]),
+            WindowGeometry {
+                x: 460,
+                y: 150,
+                width: 1000,
+                height: 780,
+            }
+        );
+
+        // Title strip above the top edge is unreachable even though the body is visible
+        let above = WindowGeometry {
+            x: 100,
+            y: -700,
+            width: 1000,
+            height: 780,
+        };
+        assert_eq!(clamp_window_geometry(above, &[primary]).y, 150);
+        assert_eq!(clamp_window_geometry(above, &[]), above);
+    }
+
+    #[test]
+    fn snapshot_section_marks_errors_inline() {
+        let ok: SnapshotSection<u32> = Ok(3).into();
+        assert_eq!(serde_json::to_value(&ok).unwrap(), serde_json::json!(3));
+
+        let err: SnapshotSection<u32> = Err("State lock poisoned".to_string()).into();
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            serde_json::json!({ "error": "State lock poisoned" })
+        );
+    }
+
+    #[test]
+    fn snapshot_covers_every_section() {
+        let state = AppState::new(SttConfig::default());
+        state
+            .0
+            .lock()
+            .unwrap()
+            .transcript_history
+            .push_back(TranscriptEntry {
+                text: "hello".to_string(),
+                timestamp: 1,
+                latency_ms: Some(120),
+            });
+        let snapshot = serde_json::to_value(state_snapshot(&state)).unwrap();
+        for section in [
+            "config",
+            "status",
+            "stats",
+            "recentLogs",
+            "transcripts",
+            "engine",
+            "audio",
+            "overlay",
+        ] {
+            assert!(snapshot[section].get("error").is_none(), "{section}");
+        }
+        assert_eq!(snapshot["transcripts"][0]["text"], "hello");
+        assert_eq!(snapshot["stats"]["maxMs"], 120);
+        assert_eq!(snapshot["status"]["running"], false);
+    }
+
+    #[test]
+    fn retranscribe_overrides_reject_unknown_keys() {
+        let overrides: RetranscribeOverrides =
+            serde_json::from_str(r#"{"punctuation": false}"#).unwrap();
+        assert_eq!(
+            serde_json::to_value(&overrides).unwrap(),
+            serde_json::json!({ "punctuation": false })
+        );
+        assert!(serde_json::from_str::<RetranscribeOverrides>(r#"{"punctation": false}"#).is_err());
+    }
+
+    #[test]
+    fn confidence_color_blends_red_to_green() {
+        assert_eq!(confidence_color(0.0), CONFIDENCE_LOW_COLOR);
+        assert_eq!(confidence_color(1.0), CONFIDENCE_HIGH_COLOR);
+        assert_eq!(confidence_color(1.7), CONFIDENCE_HIGH_COLOR);
+        assert_eq!(confidence_color(f32::NAN), CONFIDENCE_LOW_COLOR);
+        // Midpoint of 0xEF4444 and 0x22C55E
+        assert_eq!(confidence_color(0.5), 0x898551);
+    }
+
+    #[test]
+    fn engine_info_parses_the_engine_message() {
+        let info: EngineInfo = serde_json::from_value(serde_json::json!({
+            "type": "engine_info",
+            "mic": "USB Mic",
+            "sampleRate": 48000,
+            "model": "parakeet_model",
+            "device": "cpu",
+        }))
+        .unwrap();
+        assert_eq!(info.mic, "USB Mic");
+        assert_eq!(info.sample_rate, 48_000);
+        assert_eq!(info.device, "cpu");
+    }
+
+    #[test]
+    fn audio_format_hints_are_range_checked() {
+        assert!(validate_audio_format(None, None).is_ok());
+        assert!(validate_audio_format(Some(48_000), Some(2)).is_ok());
+        assert!(validate_audio_format(Some(96_000), None).is_err());
+        assert!(validate_audio_format(Some(4_000), None).is_err());
+        assert!(validate_audio_format(None, Some(0)).is_err());
+        assert!(validate_audio_format(None, Some(6)).is_err());
+    }
+
+    #[test]
+    fn overlay_geometry_follows_the_anchor() {
+        let monitor = (0, 0, 1080, 1920);
+        let mut config = SttConfig::default();
+        let x = (1080 - OVERLAY_WIDTH_PX) / 2 - OVERLAY_HORIZONTAL_OFFSET_PX;
+        assert_eq!(
+            overlay_geometry(&config, monitor, 1.0),
+            (
+                x,
+                OVERLAY_VERTICAL_MARGIN_PX,
+                OVERLAY_WIDTH_PX,
+                OVERLAY_HEIGHT_PX
+            )
+        );
+
+        config.overlay_anchor = OverlayAnchor::Bottom;
+        config.overlay_offset_px = 40;
+        assert_eq!(
+            overlay_geometry(&config, monitor, 1.0),
+            (
+                x,
+                1920 - OVERLAY_HEIGHT_PX - 40,
+                OVERLAY_WIDTH_PX,
+                OVERLAY_HEIGHT_PX
+            )
+        );
+    }
+
+    #[test]
+    fn overlay_geometry_scales_with_dpi() {
+        // A 2560x1440 panel at 150%, placed right of a 1920-wide primary
+        let monitor = (1920, 0, 2560, 1440);
+        let config = SttConfig::default();
+        // 90x5 logical → 135x8 physical (7.5 rounds up), 16 px margin → 24
+        assert_eq!(
+            overlay_geometry(&config, monitor, 1.5),
+            (1920 + (2560 - 135) / 2, 24, 135, 8)
+        );
+    }
+
+    #[test]
+    fn overlay_monitor_index_falls_back_when_missing() {
+        let monitors = vec!["left", "primary", "right"];
+        assert_eq!(pick_monitor(monitors.clone(), Some(2)), Some("right"));
+        assert_eq!(pick_monitor(monitors.clone(), Some(3)), None);
+        assert_eq!(pick_monitor(monitors, None), None);
+    }
+
+    #[test]
+    fn overlay_monitor_choice_degrades_to_any_monitor_or_none() {
+        let monitors = vec!["left", "right"];
+        assert_eq!(
+            choose_monitor(monitors.clone(), Some(1), Some("left")),
+            Some("right")
+        );
+        assert_eq!(
+            choose_monitor(monitors.clone(), Some(5), Some("right")),
+            Some("right")
+        );
+        assert_eq!(choose_monitor(monitors, Some(5), None), Some("left"));
+        // Headless: nothing to place the overlay on, so configuration is skipped
+        assert_eq!(choose_monitor(Vec::<&str>::new(), Some(0), None), None);
+        assert_eq!(choose_monitor(Vec::<&str>::new(), None, None), None);
+    }
+
+    #[test]
+    fn engine_status_reports_last_exit_and_restarts() {
+        let state = AppState::new(SttConfig::default());
+        let mut guard = state.0.lock().unwrap();
+        let status = engine_status(&guard);
+        assert!(!status.running);
+        assert!(!status.ready);
+        assert_eq!(status.started_at, None);
+        assert_eq!(status.restart_count, 0);
+
+        guard.last_exit_code = Some(3);
+        guard.restart_count = 2;
+        let status = engine_status(&guard);
+        assert_eq!(status.last_exit_code, Some(3));
+        assert_eq!(status.restart_count, 2);
+    }
+
+    #[test]
+    fn engine_stalls_after_the_configured_silence() {
+        assert!(!engine_stalled(true, 1_000, 30_999, 30));
+        assert!(engine_stalled(true, 1_000, 31_000, 30));
+        // Idle and waiting for the hotkey
+        assert!(!engine_stalled(false, 1_000, 31_000, 30));
+        // Not spawned yet, or the check is disabled
+        assert!(!engine_stalled(true, 0, 31_000, 30));
+        assert!(!engine_stalled(true, 1_000, 1_000_000, 0));
+        assert_eq!(SttConfig::default().engine_stall_timeout_secs, 30);
+    }
+
+    #[test]
+    fn only_real_work_counts_as_engine_activity() {
+        assert!(handle_engine_line("loading model").is_work());
+        assert!(handle_engine_line(r#"{"type":"transcript","text":"hi"}"#).is_work());
+        assert!(handle_engine_line(r#"{"type":"overlay_level","level":0.5}"#).is_work());
+        assert!(!handle_engine_line(r#"{"type":"ready"}"#).is_work());
+        assert!(!handle_engine_line(r#"{"type":"engine_ready","protocol":4}"#).is_work());
+        assert!(!handle_engine_line(r#"{"type":"heartbeat"}"#).is_work());
+    }
+
+    #[test]
+    fn model_progress_parses_with_and_without_pct() {
+        let line = r#"{"type":"model_progress","stage":"loading","pct":0.4}"#;
+        let value: serde_json::Value = serde_json::from_str(line).unwrap();
+        let progress: ModelProgressEvent = serde_json::from_value(value).unwrap();
+        assert_eq!(progress.stage, "loading");
+        assert_eq!(progress.pct, Some(0.4));
+
+        let value = serde_json::json!({ "type": "model_progress", "stage": "loaded" });
+        let progress: ModelProgressEvent = serde_json::from_value(value).unwrap();
+        assert_eq!(progress.pct, None);
+    }
+
+    #[test]
+    fn engine_commands_carry_their_name_as_type() {
+        let cmd = engine_command("set_language", serde_json::json!({ "language": "de" })).unwrap();
+        assert_eq!(
+            cmd,
+            serde_json::json!({ "type": "set_language", "language": "de" })
+        );
+        let cmd = engine_command("arm", serde_json::Value::Null).unwrap();
+        assert_eq!(cmd, serde_json::json!({ "type": "arm" }));
+        assert!(engine_command("arm", serde_json::json!([1])).is_err());
+        assert!(engine_command("", serde_json::Value::Null).is_err());
+    }
+
+    #[test]
+    fn doctor_output_lists_missing_modules() {
+        let stdout = "some warning\n{\"version\": \"3.11.4\", \"missing\": [\"pynput\"]}\n";
+        let report = parse_doctor_output(stdout).unwrap();
+        assert!(report.python_found);
+        assert_eq!(report.python_version.as_deref(), Some("3.11.4"));
+        assert_eq!(report.missing_modules, vec!["pynput".to_string()]);
+        assert_eq!(
+            parse_doctor_output("Traceback (most recent call last):"),
+            None
+        );
+    }
+
+    #[test]
+    fn python_invocations_cover_each_platform_branch() {
+        let script = std::path::Path::new("engine/main.py");
+        let extra: Vec<std::ffi::OsString> = vec!["--probe".into()];
+        let invocations = |configured, embedded, windows| {
+            python_interpreters(configured, embedded, windows)
+                .iter()
+                .map(|interpreter| {
+                    let mut line = vec![interpreter.program.clone()];
+                    line.extend(engine_invocation_args(interpreter, script, &extra));
+                    line.iter()
+                        .map(|arg| arg.to_string_lossy().replace('\\', "/"))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let configured = vec!["/opt/py -u engine/main.py --probe".to_string()];
+        assert_eq!(invocations(Some("/opt/py"), None, true), configured);
+        assert_eq!(invocations(Some("/opt/py"), None, false), configured);
+
+        let home = std::path::Path::new("runtime");
+        assert_eq!(
+            invocations(None, Some(home), true),
+            vec![
+                "runtime/pythonw.exe -u engine/main.py --probe",
+                "pyw -3 -u -m main --probe",
+                "pythonw -u -m main --probe",
+                "python -u -m main --probe",
+            ]
+        );
+        assert_eq!(invocations(None, None, true).len(), 3);
+        assert_eq!(
+            invocations(None, Some(home), false),
+            vec!["python -u engine/main.py --probe"]
+        );
+        let embedded = python_interpreters(None, Some(home), true);
+        assert_eq!(embedded[0].embedded_home.as_deref(), Some(home));
+        assert!(embedded[1..].iter().all(|i| i.embedded_home.is_none()));
+    }
+
+    #[test]
+    fn engine_env_is_validated_and_applied() {
+        let env = HashMap::from([("HF_HOME".to_string(), "/models/hf".to_string())]);
+        assert!(validate_engine_env(&env).is_ok());
+        for key in ["", " ", "A=B"] {
+            let bad = HashMap::from([(key.to_string(), "1".to_string())]);
+            assert!(validate_engine_env(&bad).is_err(), "{key:?}");
+        }
+
+        let interpreter = &python_interpreters(None, None, false)[0];
+        let command = build_python_command(interpreter, &[], std::path::Path::new("."), &env);
+        assert!(command
+            .get_envs()
+            .any(|(key, value)| key == "HF_HOME"
+                && value == Some(std::ffi::OsStr::new("/models/hf"))));
+    }
+
+    #[test]
+    fn transcripts_are_final_unless_marked_interim() {
+        let legacy = handle_engine_line(r#"{"type": "transcript", "text": "hi"}"#);
+        let EngineMessage::Transcript {
+            is_final,
+            segment_id,
+            ..
+        } = legacy
+        else {
+            panic!("expected a transcript, got {legacy:?}");
+        };
+        assert!(is_final);
+        assert_eq!(segment_id, None);
+
+        let interim = handle_engine_line(
+            r#"{"type": "transcript", "text": "hel", "final": false, "segmentId": "seg-1"}"#,
+        );
+        let EngineMessage::Transcript {
+            is_final,
+            segment_id,
+            ..
+        } = interim
+        else {
+            panic!("expected a transcript, got {interim:?}");
+        };
+        assert!(!is_final);
+        assert_eq!(segment_id.as_deref(), Some("seg-1"));
+        assert_eq!(
+            handle_engine_line(r#"{"type": "transcript"}"#),
+            EngineMessage::Unhandled
+        );
+    }
+
+    #[test]
+    fn engine_messages_round_trip() {
+        let messages = [
+            EngineMessage::Overlay { hover: false },
+            EngineMessage::ModelProgress(ModelProgressEvent {
+                stage: "download".to_string(),
+                pct: Some(0.5),
+            }),
+            EngineMessage::Cancelled,
+            EngineMessage::Level { level: 0.75 },
+            EngineMessage::InjectFailed {
+                reason: Some("focus".to_string()),
+                text: "hi".to_string(),
+            },
+            EngineMessage::AudioFormat(AudioFormat {
+                sample_rate: 48000,
+                channels: 2,
+            }),
+            EngineMessage::Response(serde_json::json!({ "requestId": 3, "ok": true })),
+            EngineMessage::EngineReady { protocol: 3 },
+            EngineMessage::Transcript {
+                text: "hel".to_string(),
+                is_final: false,
+                segment_id: Some("seg-1".to_string()),
+                confidence: Some(0.5),
+                capture_ts: Some(1_700_000_000_000),
+            },
+            EngineMessage::Unhandled,
+        ];
+        for message in messages {
+            let line = serde_json::to_string(&message).unwrap();
+            assert_eq!(handle_engine_line(&line), message, "{line}");
+        }
+        let line = serde_json::to_string(&EngineMessage::Level { level: 0.1 }).unwrap();
+        assert!(line.contains(r#""type":"overlay_level""#), "{line}");
+    }
+
+    #[test]
+    fn engine_lines_parse_into_messages() {
+        assert_eq!(
+            handle_engine_line(r#"{"type":"overlay","hover":true}"#),
+            EngineMessage::Overlay { hover: true }
+        );
+        assert_eq!(
+            handle_engine_line(r#"{"type":"overlay_level","level":0.25}"#),
+            EngineMessage::Level { level: 0.25 }
+        );
+        assert_eq!(
+            handle_engine_line(r#"{"type":"transcript","text":"hello","confidence":0.5}"#),
+            EngineMessage::Transcript {
+                text: "hello".to_string(),
+                is_final: true,
+                segment_id: None,
+                confidence: Some(0.5),
+                capture_ts: None,
+            }
+        );
+        assert_eq!(
+            handle_engine_line(r#"{"type":"engine_ready"}"#),
+            EngineMessage::EngineReady { protocol: 0 }
+        );
+        // Known types missing their fields and unknown types aren't log output either
+        assert_eq!(
+            handle_engine_line(r#"{"type":"overlay"}"#),
+            EngineMessage::Unhandled
+        );
+        assert_eq!(
+            handle_engine_line(r#"{"type":"future_thing"}"#),
+            EngineMessage::Unhandled
+        );
+        for raw in [
+            r#"{"type":"overlay","#,
+            r#"{"kind":"overlay"}"#,
+            "42",
+            "[python] Model loaded",
+        ] {
+            assert_eq!(
+                handle_engine_line(raw),
+                EngineMessage::Log {
+                    raw: raw.to_string()
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn preview_level_swings_below_the_peak() {
+        assert!((preview_level(0.8, 0) - 0.08).abs() < 1e-6);
+        assert!((preview_level(0.8, OVERLAY_PREVIEW_PERIOD_MS / 2) - 0.8).abs() < 1e-3);
+        for elapsed_ms in (0..2 * OVERLAY_PREVIEW_PERIOD_MS).step_by(33) {
+            let level = preview_level(3.0, elapsed_ms);
+            assert!((0.0..=1.0).contains(&level));
+        }
+    }
+
+    #[test]
+    fn overlay_dwell_is_capped() {
+        assert_eq!(overlay_dwell_ms(&SttConfig::default()), 30);
+        let parsed: SttConfig = serde_json::from_str(r#"{"overlayDwellMs": 5000}"#).unwrap();
+        assert_eq!(overlay_dwell_ms(&parsed), OVERLAY_MAX_DWELL_MS);
+        assert!(serde_json::from_str::<SttConfig>(r#"{"overlayDwellMs": -1}"#).is_err());
+    }
+
+    #[test]
+    fn duck_scope_defaults_to_master() {
+        assert!(!duck_config(&SttConfig::default()).sessions);
+        let parsed: SttConfig = serde_json::from_str(r#"{"duckScope": "sessions"}"#).unwrap();
+        assert!(duck_config(&parsed).sessions);
+    }
+
+    #[test]
+    fn only_launch_settings_require_a_restart() {
+        let old = SttConfig::default();
+        let cosmetic = SttConfig {
+            overlay_bar_color: Some(0x22C55E),
+            overlay_anchor: OverlayAnchor::Bottom,
+            duck_ratio: 0.2,
+            ..old.clone()
+        };
+        assert!(!config_requires_restart(&old, &cosmetic));
+
+        for changed in [
+            SttConfig {
+                hotkey: "Ctrl+Alt".to_string(),
+                ..old.clone()
+            },
+            SttConfig {
+                mic_device_id: Some("USB Mic, MME".to_string()),
+                ..old.clone()
+            },
+            SttConfig {
+                output_mode: OutputMode::Clipboard,
+                ..old.clone()
+            },
+            SttConfig {
+                python_path: Some("python3".to_string()),
+                ..old.clone()
+            },
+            SttConfig {
+                language: Some("es".to_string()),
+                ..old.clone()
+            },
+            SttConfig {
+                model_name: Some("parakeet-tdt-0.6b-v3".to_string()),
+                ..old.clone()
+            },
+        ] {
+            assert!(config_requires_restart(&old, &changed));
+        }
+    }
+
+    #[test]
+    fn reverting_a_launch_setting_needs_no_restart() {
+        let running = SttConfig::default();
+        let edited = SttConfig {
+            language: Some("fr".to_string()),
+            ..running.clone()
+        };
+        assert!(!launch_config_changed(None, &edited));
+        assert!(launch_config_changed(Some(&running), &edited));
+        assert!(!launch_config_changed(Some(&running), &running));
+    }
+
+    #[test]
+    fn config_file_reads_report_bad_files_and_drop_bad_hints() {
+        let dir = std::env::temp_dir().join(format!("jargon-config-test-{}", now_millis()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(&path, r#"{"hotkey": "Ctrl+Alt", "sampleRate": 44100}"#).unwrap();
+        let config = read_config_file(&path).unwrap();
+        assert_eq!(config.hotkey, "Ctrl+Alt");
+        assert_eq!(config.sample_rate, Some(44_100));
+        std::fs::write(
+            &path,
+            r#"{"hotkey": "Ctrl+Alt", "sampleRate": 96000, "channels": 2}"#,
+        )
+        .unwrap();
+        let config = read_config_file(&path).unwrap();
+        assert_eq!(config.hotkey, "Ctrl+Alt");
+        assert_eq!(config.sample_rate, None);
+        assert_eq!(config.channels, Some(2));
+        std::fs::write(&path, "{not json").unwrap();
+        assert!(read_config_file(&path).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn old_default_overlay_colors_migrate_to_the_theme() {
+        let dir = std::env::temp_dir().join(format!("jargon-migrate-test-{}", now_millis()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(CONFIG_FILE);
+        std::fs::write(
+            &path,
+            r#"{"overlayBgColor": 0, "overlayBarColor": 16777215}"#,
+        )
+        .unwrap();
+        let config = read_config_file(&path).unwrap();
+        assert_eq!(config.overlay_bg_color, None);
+        assert_eq!(config.overlay_bar_color, None);
+        assert_eq!(config.config_version, CONFIG_VERSION);
+
+        // Only the old defaults are dropped, and a current file keeps black and white
+        std::fs::write(
+            &path,
+            r#"{"overlayBgColor": 0, "overlayBarColor": 2278750}"#,
+        )
+        .unwrap();
+        let config = read_config_file(&path).unwrap();
+        assert_eq!(config.overlay_bg_color, None);
+        assert_eq!(config.overlay_bar_color, Some(0x22C55E));
+        std::fs::write(
+            &path,
+            r#"{"configVersion": 1, "overlayBgColor": 0, "overlayBarColor": 16777215}"#,
+        )
+        .unwrap();
+        let config = read_config_file(&path).unwrap();
+        assert_eq!(config.overlay_bg_color, Some(0));
+        assert_eq!(config.overlay_bar_color, Some(0xFFFFFF));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn rapid_config_changes_are_written_once() {
+        let state = AppState::new(SttConfig::default());
+        let mut guard = state.0.lock().unwrap();
+        let generations: Vec<u64> = (0..5)
+            .map(|i| {
+                guard.config.duck_ratio = i as f32 / 10.0;
+                mark_config_dirty(&mut guard)
+            })
+            .collect();
+        let writes: Vec<SttConfig> = generations
+            .iter()
+            .filter_map(|&generation| take_dirty_config(&mut guard, Some(generation)))
+            .collect();
+        assert_eq!(writes.len(), 1);
+        assert_eq!(writes[0].duck_ratio, 0.4);
+        assert!(take_dirty_config(&mut guard, None).is_none());
+
+        mark_config_dirty(&mut guard);
+        assert!(take_dirty_config(&mut guard, None).is_some());
+    }
+
+    #[test]
+    fn log_levels_come_from_the_line_or_the_stream() {
+        assert_eq!(infer_log_level("stdout", "[error] model failed"), "error");
+        assert_eq!(infer_log_level("stdout", "ERROR: no device"), "error");
+        assert_eq!(infer_log_level("stdout", "WARN low memory"), "warn");
+        assert_eq!(
+            infer_log_level("stdout", "[python] Warning: failed to paste"),
+            "warn"
+        );
+        assert_eq!(
+            infer_log_level("stderr", "Traceback (most recent call last):"),
+            "error"
+        );
+        assert_eq!(
+            infer_log_level("stderr", "[python] Paste method: clipboard"),
+            "warn"
+        );
+        assert_eq!(
+            infer_log_level("engine", "[python] Model dir: /models"),
+            "info"
+        );
+        assert_eq!(
+            infer_log_level("stderr", "RuntimeError: CUDA out of memory"),
+            "error"
+        );
+        assert_eq!(infer_log_level("stderr", "INFO: loading model"), "info");
+        assert_eq!(
+            infer_log_level(
+                "stderr",
+                "model.bin:  45%|████      | 12.0M/27.0M [00:03<00:04, 3.1MB/s]"
+            ),
+            "info"
+        );
+        assert_eq!(infer_log_level("stderr", ""), "info");
+        let event = serde_json::to_value(LogEvent::new("config", "saved".to_string())).unwrap();
+        assert_eq!(event["level"], "info");
+        assert!(event["ts"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    fn now_millis_nonzero() {
+        assert!(now_millis() > 0);
+    }
+}
+
+/// Monitor origin and size in the units the overlay positions with: physical pixels
+/// for Win32, logical points for AppKit and the WebView overlay elsewhere
+#[cfg(windows)]
+fn overlay_monitor_bounds(monitor: &tauri::Monitor) -> (i32, i32, i32, i32) {
+    let position = monitor.position();
+    let size = monitor.size();
+    (
+        position.x,
+        position.y,
+        size.width as i32,
+        size.height as i32,
+    )
+}
+
+/// Factor from the logical `OVERLAY_*_PX` sizes to the units of `overlay_monitor_bounds`
+#[cfg(windows)]
+fn overlay_monitor_scale(monitor: &tauri::Monitor) -> f64 {
+    monitor.scale_factor()
+}
+
+#[cfg(not(windows))]
+fn overlay_monitor_scale(_monitor: &tauri::Monitor) -> f64 {
+    1.0
+}
+
+#[cfg(not(windows))]
+fn overlay_monitor_bounds(monitor: &tauri::Monitor) -> (i32, i32, i32, i32) {
+    let scale = monitor.scale_factor();
+    let position = monitor.position().to_logical::<f64>(scale);
+    let size = monitor.size().to_logical::<f64>(scale);
+    (
+        position.x as i32,
+        position.y as i32,
+        size.width as i32,
+        size.height as i32,
+    )
+}
+
+/// Entry `index` of `monitors`, or `None` when unset or out of range
+fn pick_monitor<T>(monitors: Vec<T>, index: Option<usize>) -> Option<T> {
+    monitors.into_iter().nth(index?)
+}
+
+/// Hover collapse dwell, capped at `OVERLAY_MAX_DWELL_MS`
+fn overlay_dwell_ms(config: &SttConfig) -> u64 {
+    config.overlay_dwell_ms.min(OVERLAY_MAX_DWELL_MS)
+}
+
+/// Entry `index` of `monitors`, else `primary`, else the first monitor listed; `None`
+/// only when no monitor is known at all (headless, or an RDP session mid-reconnect)
+fn choose_monitor<T: Clone>(
+    monitors: Vec<T>,
+    index: Option<usize>,
+    primary: Option<T>,
+) -> Option<T> {
+    let first = monitors.first().cloned();
+    pick_monitor(monitors, index).or(primary).or(first)
+}
+
+/// The monitor chosen in `overlay_monitor`, falling back to the primary one
+fn overlay_target_monitor(app: &AppHandle, config: &SttConfig) -> Option<tauri::Monitor> {
+    choose_monitor(
+        app.available_monitors().unwrap_or_default(),
+        config.overlay_monitor,
+        app.primary_monitor().ok().flatten(),
+    )
+}
+
+/// Collapsed bar as `(x, y, width, height)` in the monitor's units: centered
+/// horizontally and `overlay_offset_px` away from the anchored edge, with the
+/// logical sizes multiplied by `scale`
+fn overlay_geometry(
+    config: &SttConfig,
+    (left, top, width, height): (i32, i32, i32, i32),
+    scale: f64,
+) -> (i32, i32, i32, i32) {
+    let scaled = |px: i32| (px as f64 * scale).round() as i32;
+    let bar_width = scaled(OVERLAY_WIDTH_PX).max(1);
+    let bar_height = scaled(OVERLAY_HEIGHT_PX).max(1);
+    let offset = scaled(config.overlay_offset_px);
+    let x = (left + (width - bar_width) / 2 - scaled(OVERLAY_HORIZONTAL_OFFSET_PX)).max(left);
+    let y = match config.overlay_anchor {
+        OverlayAnchor::Top => top + offset,
+        OverlayAnchor::Bottom => top + height - bar_height - offset,
+    };
+    (x, y, bar_width, bar_height)
+}
+
+/// `overlay_geometry` on the configured monitor, or `None` when there is no monitor to
+/// place it on; shared by the native overlays and the WebView fallback so both honor
+/// anchor, monitor and offset the same way
+fn compute_overlay_geometry(app: &AppHandle, config: &SttConfig) -> Option<(i32, i32, i32, i32)> {
+    let monitor = overlay_target_monitor(app, config)?;
+    Some(overlay_geometry(
+        config,
+        overlay_monitor_bounds(&monitor),
+        overlay_monitor_scale(&monitor),
+    ))
+}
+
+fn configure_overlay(app: &AppHandle) -> Result<(), String> {
+    #[cfg(any(windows, target_os = "macos"))]
+    {
+        let config = current_config(app);
+        let Some((x, y, width, height)) = compute_overlay_geometry(app, &config) else {
+            log_to_file("[warn] no monitor found; leaving the overlay unconfigured");
+            return Ok(());
+        };
+
+        native_overlay::set_text_bounds(config.overlay_min_width, config.overlay_max_width);
+        native_overlay::set_leave_dwell(overlay_dwell_ms(&config));
+        native_overlay::set_bar_count(config.overlay_bar_count);
+        native_overlay::set_corner_radius(config.overlay_corner_radius);
+        if let Err(err) = native_overlay::set_exclude_from_capture(config.exclude_from_capture) {
+            log_to_file(&format!(
+                "[warn] overlay capture exclusion unavailable: {err}"
+            ));
+        }
+        native_overlay::set_transcript_queue(
+            config.overlay_transcript_lines,
+            config.overlay_transcript_display_ms,
+        );
+
+        native_overlay::configure(
+            width,
+            height,
+            x,
+            y,
+            config.overlay_hover_scale_x,
+            config.overlay_hover_scale_y,
+            overlay_colors(&config, native_overlay::system_prefers_dark()),
+        )
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        // Recomputed on every show, so monitor and DPI changes are picked up
+        if let Some(window) = app.get_webview_window("overlay") {
+            let Some((x, y, width, height)) = compute_overlay_geometry(app, &current_config(app))
+            else {
+                log_to_file("[warn] no monitor found; leaving the overlay unconfigured");
+                return Ok(());
+            };
+            window
+                .set_size(LogicalSize::new(width as f64, height as f64))
+                .map_err(|e| e.to_string())?;
+            window
+                .set_position(LogicalPosition::new(x as f64, y as f64))
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Clicking the overlay starts the engine when it is stopped and stops it otherwise
+fn handle_overlay_event(app: &AppHandle, event: native_overlay::OverlayEvent) {
+    match event {
+        native_overlay::OverlayEvent::Clicked => {
+            if let Err(err) = toggle_engine(app, &app.state::<AppState>()) {
+                emit_log(app, "engine", &format!("overlay toggle failed: {err}"));
+            }
+        }
+        native_overlay::OverlayEvent::ThemeChanged => {
+            let config = current_config(app);
+            if config.overlay_bg_color.is_none() || config.overlay_bar_color.is_none() {
+                let _ = configure_overlay(app);
+            }
+        }
+    }
+}
+
+/// Configured overlay colors, with unset ones taken from the light or dark theme defaults
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+fn overlay_colors(config: &SttConfig, dark: bool) -> native_overlay::OverlayColors {
+    let theme = native_overlay::OverlayColors::for_theme(dark);
+    native_overlay::OverlayColors {
+        background: config.overlay_bg_color.unwrap_or(theme.background) & 0x00FF_FFFF,
+        bar: config.overlay_bar_color.unwrap_or(theme.bar) & 0x00FF_FFFF,
+    }
+}
+
+#[cfg_attr(any(windows, target_os = "macos"), allow(unused_variables))]
+fn set_overlay_visibility(app: &AppHandle, visible: bool) -> Result<(), String> {
+    // Avoid redundant show/hide operations
+    let was = overlay_visible_flag().swap(visible, Ordering::SeqCst);
+    if was == visible {
+        return Ok(());
+    }
+
+    #[cfg(any(windows, target_os = "macos"))]
+    {
+        if visible {
+            configure_overlay(app)?;
+            native_overlay::show()
+        } else {
+            native_overlay::hide()
+        }
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        if let Some(window) = app.get_webview_window("overlay") {
+            if visible {
+                configure_overlay(app)?;
+                let _: tauri::Result<()> = window.show();
+                let _: tauri::Result<()> = window.set_focus();
+            } else {
+                let _: tauri::Result<()> = window.hide();
+            }
+        }
+        Ok(())
+    }
+}
+
+fn overlay_should_be_visible(
+    mode: OverlayMode,
+    running: bool,
+    listening: bool,
+    hover: bool,
+) -> bool {
+    match mode {
+        OverlayMode::Always => true,
+        OverlayMode::WhileRunning => running,
+        OverlayMode::WhileListening => running && (listening || hover),
+        OverlayMode::Never => false,
+    }
+}
+
+/// Recompute overlay visibility from the current engine/listening/hover/config state.
+/// Every event that could affect the bar calls this instead of showing/hiding directly,
+/// so out-of-order events still converge on the latest state.
+fn reconcile_overlay(app: &AppHandle) {
+    let _serial = overlay_reconcile_lock()
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let (mode, enabled, running, remote_behavior) = app
+        .state::<AppState>()
+        .0
+        .lock()
+        .map(|g| {
+            (
+                g.config.overlay_mode,
+                g.config.overlay_enabled,
+                g.child.is_some(),
+                g.config.remote_session_behavior,
+            )
+        })
+        .unwrap_or((
+            OverlayMode::Always,
+            true,
+            false,
+            RemoteSessionBehavior::Normal,
+        ));
+    let listening = dictation_active_flag().load(Ordering::SeqCst);
+    let hover = overlay_hover_requested_flag().load(Ordering::SeqCst);
+    let suppressed = !enabled
+        || overlay_suppressed_flag().load(Ordering::SeqCst)
+        || (remote_behavior.disables_overlay() && is_remote_session());
+    let _ = set_overlay_visibility(
+        app,
+        !suppressed && overlay_should_be_visible(mode, running, listening, hover),
+    );
+}
+
+fn overlay_tray_label(enabled: bool) -> &'static str {
+    if enabled {
+        "Hide Overlay"
+    } else {
+        "Show Overlay"
+    }
+}
+
+/// Turn the overlay on or off without touching the engine, and save the choice
+fn set_overlay_enabled(app: &AppHandle, state: &AppState, enabled: bool) -> Result<(), String> {
+    let generation = {
+        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        guard.config.overlay_enabled = enabled;
+        mark_config_dirty(&mut guard)
+    };
+    if let Some(item) = OVERLAY_TRAY_ITEM.get() {
+        let _ = item.set_text(overlay_tray_label(enabled));
+    }
+    reconcile_overlay(app);
+    schedule_config_save(app, state, generation);
+    Ok(())
+}
+
+fn dev_workspace_root() -> PathBuf {
+    // CARGO_MANIFEST_DIR points to src-tauri; go up one level to workspace root
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("src-tauri should have a parent directory")
+        .to_path_buf()
+}
+
+fn active_engine_def(config: &SttConfig) -> Option<&EngineDef> {
+    let name = config.active_engine.as_deref()?;
+    config.engines.iter().find(|def| def.name == name)
+}
+
+/// Whether the engine needs its model directory to exist: the bundled engine always
+/// does, a custom one only when its definition names one
+fn engine_requires_model_dir(config: &SttConfig) -> bool {
+    active_engine_def(config).is_none_or(|def| def.model_dir.is_some())
+}
+
+/// `-m <module>` name for a script, so non-`main.py` engines launch too
+fn script_module_name(script_path: &std::path::Path) -> std::ffi::OsString {
+    script_path
+        .file_stem()
+        .map(|stem| stem.to_owned())
+        .unwrap_or_else(|| "main".into())
+}
+
+fn resolve_script_path(app: &AppHandle, config: &SttConfig) -> PathBuf {
+    if let Some(def) = active_engine_def(config) {
+        return PathBuf::from(&def.script_path);
+    }
+
+    // In dev mode, always use workspace root; in production, use Resource directory
+    let resource_path = app
+        .path()
+        .resolve("python/main.py", tauri::path::BaseDirectory::Resource);
+
+    match resource_path {
+        Ok(path) if path.exists() => path,
+        _ => dev_workspace_root().join("python").join("main.py"),
+    }
+}
+
+fn config_path(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join(CONFIG_FILE))
+}
+
+fn read_config_file(path: &std::path::Path) -> Result<SttConfig, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut config = serde_json::from_str::<SttConfig>(&raw).map_err(|e| e.to_string())?;
+    migrate_config(&mut config);
+    // An out-of-range hint only costs that hint, not every other saved setting
+    if let Err(err) = validate_audio_format(config.sample_rate, None) {
+        log_to_file(&format!("[config] {err}; using the device default"));
+        config.sample_rate = None;
+    }
+    if let Err(err) = validate_audio_format(None, config.channels) {
+        log_to_file(&format!("[config] {err}; using the device default"));
+        config.channels = None;
+    }
+    Ok(config)
+}
+
+/// Rewrite settings saved by an older version to what they mean now
+fn migrate_config(config: &mut SttConfig) {
+    if config.config_version < 1 {
+        // Black and white used to be the defaults; now an unset color follows the theme
+        if config.overlay_bg_color == Some(0x000000) {
+            config.overlay_bg_color = None;
+        }
+        if config.overlay_bar_color == Some(0xFFFFFF) {
+            config.overlay_bar_color = None;
+        }
+    }
+    config.config_version = CONFIG_VERSION;
+}
+
+/// Saved settings, or the defaults when there are none or they can't be read
+fn load_config(app: &AppHandle) -> SttConfig {
+    let Some(path) = config_path(app).filter(|p| p.exists()) else {
+        return SttConfig::default();
+    };
+    read_config_file(&path).unwrap_or_else(|err| {
+        emit_log(
+            app,
+            "config",
+            &format!("ignoring {}: {err}", path.display()),
+        );
+        SttConfig::default()
+    })
+}
+
+/// Flag the in-memory config as unsaved; returns the generation a debounced save must match
+fn mark_config_dirty(inner: &mut InnerState) -> u64 {
+    inner.config_dirty = true;
+    inner.config_save_generation += 1;
+    inner.config_save_generation
+}
+
+/// Clear the dirty flag and return the config to write, if there is one; `generation`
+/// limits a debounced save to the latest change, `None` flushes unconditionally
+fn take_dirty_config(inner: &mut InnerState, generation: Option<u64>) -> Option<SttConfig> {
+    if !inner.config_dirty || generation.is_some_and(|g| g != inner.config_save_generation) {
+        return None;
+    }
+    inner.config_dirty = false;
+    Some(inner.config.clone())
+}
+
+/// Write the config after `CONFIG_SAVE_DEBOUNCE_MS` unless another change supersedes it
+fn schedule_config_save(app: &AppHandle, state: &AppState, generation: u64) {
+    let app = app.clone();
+    let state = state.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(CONFIG_SAVE_DEBOUNCE_MS));
+        let config = match state.0.lock() {
+            Ok(mut guard) => take_dirty_config(&mut guard, Some(generation)),
+            Err(_) => None,
+        };
+        if let Some(config) = config {
+            if let Err(err) = save_config(&app, &config) {
+                emit_log(&app, "config", &format!("Failed to save config: {err}"));
+            }
+        }
+    });
+}
+
+/// Write any pending config change now, e.g. before the engine stops or the app exits
+fn flush_config(app: &AppHandle, state: &AppState) -> Result<(), String> {
+    let config = {
+        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        take_dirty_config(&mut guard, None)
+    };
+    match config {
+        Some(config) => {
+            save_config(app, &config).map_err(|e| format!("Failed to save config: {e}"))
+        }
+        None => Ok(()),
+    }
+}
+
+fn save_config(app: &AppHandle, config: &SttConfig) -> Result<(), String> {
+    let path = config_path(app).ok_or("No app config directory")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    // Write a sibling file and rename it into place, so a crash mid-write leaves the
+    // previous config intact instead of a truncated one
+    let tmp = path.with_extension("json.tmp");
+    let file = std::fs::File::create(&tmp).map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(&file, config).map_err(|e| e.to_string())?;
+    file.sync_all().map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+fn window_state_path(app: &AppHandle) -> Option<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .ok()
+        .map(|dir| dir.join(WINDOW_STATE_FILE))
+}
+
+fn load_window_geometry(app: &AppHandle) -> Option<WindowGeometry> {
+    let raw = std::fs::read_to_string(window_state_path(app)?).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+fn save_window_geometry(app: &AppHandle, geometry: WindowGeometry) -> Result<(), String> {
+    let path = window_state_path(app).ok_or("No app config directory")?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&geometry).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| e.to_string())
+}
+
+/// Keep a restored window reachable. If its title strip doesn't overlap any monitor,
+/// recentre it on `monitors[0]` (the primary), shrinking it to fit if needed.
+fn clamp_window_geometry(saved: WindowGeometry, monitors: &[WindowGeometry]) -> WindowGeometry {
+    let strip_right = saved.x + saved.width as i32;
+    let strip_bottom = saved.y + WINDOW_MIN_VISIBLE_PX;
+    let reachable = monitors.iter().any(|m| {
+        let overlap_x = strip_right.min(m.x + m.width as i32) - saved.x.max(m.x);
+        let overlap_y = strip_bottom.min(m.y + m.height as i32) - saved.y.max(m.y);
+        overlap_x >= WINDOW_MIN_VISIBLE_PX && overlap_y > 0
+    });
+    match monitors.first() {
+        Some(primary) if !reachable => {
+            let width = saved.width.min(primary.width);
+            let height = saved.height.min(primary.height);
+            WindowGeometry {
+                x: primary.x + (primary.width - width) as i32 / 2,
+                y: primary.y + (primary.height - height) as i32 / 2,
+                width,
+                height,
+            }
+        }
+        _ => saved,
+    }
+}
+
+/// Minimized/maximized placements aren't worth restoring; keep the last normal one
+fn window_geometry(window: &tauri::WebviewWindow) -> Option<WindowGeometry> {
+    if window.is_minimized().unwrap_or(false) || window.is_maximized().unwrap_or(false) {
+        return None;
+    }
+    let position = window.outer_position().ok()?;
+    let size = window.inner_size().ok()?;
+    Some(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+/// Coalesce a drag's worth of move/resize events into one write
+fn schedule_window_geometry_save(window: &tauri::WebviewWindow) {
+    if !current_config(window.app_handle()).remember_window_geometry {
+        return;
+    }
+    let seq = window_geometry_seq().fetch_add(1, Ordering::SeqCst) + 1;
+    let window = window.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(WINDOW_STATE_SAVE_DEBOUNCE_MS));
+        if window_geometry_seq().load(Ordering::SeqCst) != seq {
+            return;
+        }
+        if let Some(geometry) = window_geometry(&window) {
+            if let Err(err) = save_window_geometry(window.app_handle(), geometry) {
+                log_to_file(&format!("[warn] failed to save window state: {err}"));
+            }
+        }
+    });
+}
+
+fn restore_window_geometry(window: &tauri::WebviewWindow) {
+    if !current_config(window.app_handle()).remember_window_geometry {
+        return;
+    }
+    let Some(saved) = load_window_geometry(window.app_handle()) else {
+        return;
+    };
+    let monitor_rect = |m: &tauri::Monitor| WindowGeometry {
+        x: m.position().x,
+        y: m.position().y,
+        width: m.size().width,
+        height: m.size().height,
+    };
+    let mut monitors: Vec<WindowGeometry> = window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(monitor_rect)
+        .collect();
+    if let Ok(Some(primary)) = window.primary_monitor() {
+        let primary = monitor_rect(&primary);
+        monitors.retain(|m| *m != primary);
+        monitors.insert(0, primary);
+    }
+    let geometry = clamp_window_geometry(saved, &monitors);
+    let _ = window.set_size(tauri::PhysicalSize::new(geometry.width, geometry.height));
+    let _ = window.set_position(tauri::PhysicalPosition::new(geometry.x, geometry.y));
+}
+
+/// Working directory the engine runs in: the script's folder
+fn engine_python_dir(script_path: &std::path::Path) -> PathBuf {
+    script_path
+        .parent()
+        .map(|p| p.to_path_buf())
+        .unwrap_or_else(|| dev_workspace_root().join("python"))
+}
+
+fn resolve_model_dir(app: &AppHandle, config: &SttConfig) -> PathBuf {
+    if let Some(dir) = active_engine_def(config).and_then(|def| def.model_dir.as_ref()) {
+        return PathBuf::from(dir);
+    }
+
+    // A named model sits next to the bundled one under data/
+    let name = config.model_name.as_deref().unwrap_or("parakeet_model");
+    let resource_path = app
+        .path()
+        .resolve(format!("data/{name}"), tauri::path::BaseDirectory::Resource);
+
+    match resource_path {
+        Ok(path) if path.exists() => path,
+        _ => dev_workspace_root().join("data").join(name),
+    }
+}
+
+fn resolve_embedded_python_dir(app: &AppHandle) -> Option<PathBuf> {
+    let resource_path = app
+        .path()
+        .resolve("python_embedded", tauri::path::BaseDirectory::Resource)
+        .ok();
+
+    if let Some(path) = resource_path {
+        if path.exists() {
+            return Some(path);
+        }
+    }
+
+    let dev_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("python_embedded");
+    if dev_path.exists() {
+        return Some(dev_path);
+    }
+
+    None
+}
+
+fn compat_report(app_protocol: u32, engine_protocol: Option<u32>) -> CompatReport {
+    let (compatible, advice) = match engine_protocol {
+        None => (
+            false,
+            "The engine has not reported a protocol version yet; start it and try again.".to_string(),
+        ),
+        Some(engine) if engine == app_protocol => {
+            (true, "App and engine protocols match.".to_string())
+        }
+        Some(engine) if engine < app_protocol => (
+            false,
+            format!(
+                "The Python engine is older than the app (protocol {engine} < {app_protocol}); update the python/ files."
+            ),
+        ),
+        Some(engine) => (
+            false,
+            format!(
+                "The app is older than the Python engine (protocol {engine} > {app_protocol}); update the app."
+            ),
+        ),
+    };
+    CompatReport {
+        app_protocol,
+        engine_protocol,
+        compatible,
+        advice,
+    }
+}
+
+fn engine_status(guard: &InnerState) -> SttStatus {
+    SttStatus {
+        running: guard.child.is_some(),
+        ready: guard.child.is_some() && guard.ready,
+        started_at: guard.started_at,
+        last_exit_code: guard.last_exit_code,
+        restart_count: guard.restart_count,
+    }
+}
+
+fn emit_status(app: &AppHandle) -> Result<SttStatus, String> {
+    let status = {
+        let state = app.state::<AppState>();
+        let guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        engine_status(&guard)
+    };
+    let _ = app.emit("stt:status", status.clone());
+    Ok(status)
+}
+
+/// Failures nobody awaits (auto-start, the monitor thread) so the UI can still surface them
+fn emit_error(app: &AppHandle, kind: &str, message: &str) {
+    let _ = app.emit(
+        "stt:error",
+        ErrorEvent {
+            kind: kind.to_string(),
+            message: message.to_string(),
+        },
+    );
+}
+
+/// Classify a failed engine start for `stt:error`
+fn start_error_kind(app: &AppHandle) -> &'static str {
+    let config = current_config(app);
+    if !resolve_script_path(app, &config).exists() {
+        "script_not_found"
+    } else if engine_requires_model_dir(&config) && !resolve_model_dir(app, &config).is_dir() {
+        "model_not_found"
+    } else {
+        "spawn_failed"
+    }
+}
+
+/// Persist a line to the rotating log in the app log directory
+fn log_line(app: &AppHandle, stream: &str, line: &str) {
+    if log_file::dir().is_none() {
+        if let Ok(dir) = app.path().app_log_dir() {
+            log_file::init(dir);
+        }
+    }
+    log_file::append(&format!("[{stream}] {line}"));
+}
+
+fn emit_log(app: &AppHandle, stream: &str, line: &str) {
+    log_line(app, stream, line);
+    publish_log(app, LogEvent::new(stream, line.to_string()));
+}
+
+fn emit_transcript(app: &AppHandle, event: &TranscriptEvent) {
+    if event.is_final {
+        let kept = app
+            .state::<AppState>()
+            .0
+            .lock()
+            .map(|guard| guard.last_transcript().is_some())
+            .unwrap_or(false);
+        set_copy_transcript_enabled(kept);
+    }
+    let _ = app.emit("stt:transcript", event);
+}
+
+fn set_copy_transcript_enabled(enabled: bool) {
+    if let Some(item) = COPY_TRANSCRIPT_TRAY_ITEM.get() {
+        let _ = item.set_enabled(enabled);
+    }
+}
+
+/// Keep `event` for `stt_get_recent_logs` and send it to open log views
+fn publish_log(app: &AppHandle, event: LogEvent) {
+    if let Ok(mut logs) = recent_logs_storage().lock() {
+        push_recent_log(&mut logs, event.clone());
+    }
+    let _ = app.emit("stt:log", event);
+}
+
+fn push_recent_log(logs: &mut VecDeque<LogEvent>, event: LogEvent) {
+    if logs.len() >= RECENT_LOG_LIMIT {
+        logs.pop_front();
+    }
+    logs.push_back(event);
+}
+
+/// The last `limit` log events, oldest first so a log view can append them in order
+fn recent_logs(logs: &VecDeque<LogEvent>, limit: Option<usize>) -> Vec<LogEvent> {
+    let skip = logs.len().saturating_sub(limit.unwrap_or(usize::MAX));
+    logs.iter().skip(skip).cloned().collect()
+}
+
+fn crash_report(
+    exit_code: Option<i32>,
+    logs: &VecDeque<LogEvent>,
+    mut config: SttConfig,
+    engine_info: Option<EngineInfo>,
+) -> CrashReport {
+    // Engine env vars are where tokens end up, and reports get pasted into issues
+    for value in config.engine_env.values_mut() {
+        *value = "<redacted>".to_string();
+    }
+    CrashReport {
+        exit_code: exit_code.unwrap_or(-1),
+        tail_logs: recent_logs(logs, Some(CRASH_TAIL_LINES))
+            .into_iter()
+            .map(|event| format!("[{}] {}", event.stream, event.line))
+            .collect(),
+        config,
+        engine_info,
+    }
+}
+
+/// Emit `stt:crash` for an engine that exited with `exit_code` and keep a copy as
+/// `crash-<ts>.json` next to the log file
+fn report_engine_crash(app: &AppHandle, exit_code: Option<i32>) {
+    let (config, engine_info) = match app.state::<AppState>().0.lock() {
+        Ok(guard) => (guard.config.clone(), guard.engine_info.clone()),
+        Err(_) => return,
+    };
+    let report = match recent_logs_storage().lock() {
+        Ok(logs) => crash_report(exit_code, &logs, config, engine_info),
+        Err(_) => return,
+    };
+    if let Some(dir) = log_file::dir() {
+        let path = dir.join(format!("crash-{}.json", now_millis()));
+        let written = serde_json::to_string_pretty(&report)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(&path, json).map_err(|e| e.to_string()));
+        if let Err(err) = written {
+            log_to_file(&format!(
+                "[warn] failed to write crash report {}: {err}",
+                path.display()
+            ));
+        }
+    }
+    let _ = app.emit("stt:crash", &report);
+}
+
+fn push_transcript_history(history: &mut VecDeque<TranscriptEntry>, entry: TranscriptEntry) {
+    if history.len() >= TRANSCRIPT_HISTORY_LIMIT {
+        history.pop_front();
+    }
+    history.push_back(entry);
+}
+
+/// Up to `limit` entries, newest first
+fn recent_transcripts(
+    history: &VecDeque<TranscriptEntry>,
+    limit: Option<usize>,
+) -> Vec<TranscriptEntry> {
+    history
+        .iter()
+        .rev()
+        .take(limit.unwrap_or(usize::MAX))
+        .cloned()
+        .collect()
+}
+
+/// `None` until a stored transcript carries a latency
+fn latency_stats(history: &VecDeque<TranscriptEntry>) -> Option<LatencyStats> {
+    let mut latencies: Vec<u64> = history.iter().filter_map(|e| e.latency_ms).collect();
+    if latencies.is_empty() {
+        return None;
+    }
+    latencies.sort_unstable();
+    let count = latencies.len();
+    // Nearest-rank percentile
+    let p95_index = (count * 95).div_ceil(100) - 1;
+    Some(LatencyStats {
+        count,
+        min_ms: latencies[0],
+        avg_ms: latencies.iter().sum::<u64>() / count as u64,
+        max_ms: latencies[count - 1],
+        p95_ms: latencies[p95_index],
+    })
+}
+
+fn current_config(app: &AppHandle) -> SttConfig {
+    app.state::<AppState>()
+        .0
+        .lock()
+        .map(|g| g.config.clone())
+        .unwrap_or_default()
+}
+
+/// The engine only types when keystroke injection is selected; otherwise the app injects
+fn engine_types_into_active_app(config: &SttConfig) -> bool {
+    matches!(config.output_mode, OutputMode::Type | OutputMode::Both)
+        && config.inject_method == InjectMethod::Keystrokes
+        && !config.commit_mode
+}
+
+/// PYTHONPATH with the engine dir first so `-m main` resolves even when the cwd
+/// contains spaces or non-ASCII characters that trip up module lookup
+fn engine_python_path(
+    python_dir: &std::path::Path,
+    existing: Option<std::ffi::OsString>,
+) -> std::ffi::OsString {
+    let mut paths = vec![python_dir.to_path_buf()];
+    if let Some(existing) = existing {
+        paths.extend(std::env::split_paths(&existing));
+    }
+    std::env::join_paths(paths).unwrap_or_else(|_| python_dir.as_os_str().to_owned())
+}
+
+/// Engine CLI args shared by every spawn strategy
+fn engine_args(
+    config: &SttConfig,
+    model_dir: &std::path::Path,
+    armed: bool,
+) -> Vec<std::ffi::OsString> {
+    let mut args: Vec<std::ffi::OsString> = vec![
+        "--hotkey".into(),
+        hotkey::parse_hotkey(&config.hotkey)
+            .map(|hotkey| hotkey.to_string())
+            .unwrap_or_else(|_| config.hotkey.clone())
+            .into(),
+        "--hotkey-mode".into(),
+        config.hotkey_mode.as_arg().into(),
+        "--model-dir".into(),
+        model_dir.as_os_str().to_owned(),
+        "--type-into-active-app".into(),
+        if engine_types_into_active_app(config) {
+            "true".into()
+        } else {
+            "false".into()
+        },
+        "--output-mode".into(),
+        config.output_mode.as_arg().into(),
+    ];
+    if !armed {
+        args.push("--no-hotkey".into());
+    }
+    if let Some(cancel) = &config.cancel_hotkey {
+        args.push("--cancel-hotkey".into());
+        args.push(
+            hotkey::parse_hotkey(cancel)
+                .map(|hotkey| hotkey.to_string())
+                .unwrap_or_else(|_| cancel.clone())
+                .into(),
+        );
+    }
+    if let Some(device) = &config.mic_device_id {
+        args.push("--mic-device".into());
+        args.push(device.into());
+    }
+    if let Some(sample_rate) = config.sample_rate {
+        args.push("--sample-rate".into());
+        args.push(sample_rate.to_string().into());
+    }
+    if let Some(channels) = config.channels {
+        args.push("--channels".into());
+        args.push(channels.to_string().into());
+    }
+    if let Some(language) = &config.language {
+        args.push("--language".into());
+        args.push(language.into());
+    }
+    if let Some(model) = &config.model_name {
+        args.push("--model".into());
+        args.push(model.into());
+    }
+    if let Some(def) = active_engine_def(config) {
+        args.extend(def.args.iter().map(Into::into));
+    }
+    args
+}
+
+/// True when `new` changes how the engine is launched: its arguments, interpreter,
+/// script or model. Overlay, ducking and other host-side settings apply live.
+fn config_requires_restart(old: &SttConfig, new: &SttConfig) -> bool {
+    let launch = |config: &SttConfig| {
+        let def = active_engine_def(config);
+        (
+            engine_args(config, std::path::Path::new(""), true),
+            config.python_path.clone(),
+            config.engine_env.clone(),
+            def.map(|def| def.script_path.clone()),
+            def.and_then(|def| def.model_dir.clone()),
+        )
+    };
+    launch(old) != launch(new)
+}
+
+/// Files python/main.py loads from the bundled Parakeet model directory
+const MODEL_REQUIRED_FILES: [&str; 4] = [
+    "encoder.int8.onnx",
+    "decoder.int8.onnx",
+    "joiner.int8.onnx",
+    "tokens.txt",
+];
+
+/// Presence of `model_dir`; custom engines' and named models' file layouts are unknown,
+/// so only the bundled model is checked file by file
+fn model_status(model_dir: &std::path::Path, custom_layout: bool) -> ModelStatus {
+    let missing_files = if !model_dir.is_dir() || custom_layout {
+        Vec::new()
+    } else {
+        MODEL_REQUIRED_FILES
+            .iter()
+            .filter(|file| !model_dir.join(file).is_file())
+            .map(|file| file.to_string())
+            .collect()
+    };
+    ModelStatus {
+        model_dir: model_dir.display().to_string(),
+        present: model_dir.is_dir() && missing_files.is_empty(),
+        missing_files,
+    }
+}
+
+/// Languages the bundled Parakeet model transcribes
+const SUPPORTED_LANGUAGES: &[&str] = &[
+    "bg", "cs", "da", "de", "el", "en", "es", "et", "fi", "fr", "hr", "hu", "it", "lt", "lv", "mt",
+    "nl", "pl", "pt", "ro", "ru", "sk", "sl", "sv", "uk",
+];
+
+/// Model names pick a directory under data/, so they must not name a path
+fn validate_model_name(name: Option<&str>) -> Result<(), String> {
+    match name {
+        Some(name)
+            if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) =>
+        {
+            Err(format!("Invalid model name \"{name}\""))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Custom engines may support other languages, so only the bundled one is checked
+fn validate_language(language: Option<&str>, custom_engine: bool) -> Result<(), String> {
+    match language {
+        Some(code) if !custom_engine && !SUPPORTED_LANGUAGES.contains(&code) => {
+            Err(format!("Unsupported language {code}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+const SAMPLE_RATE_RANGE_HZ: std::ops::RangeInclusive<u32> = 8_000..=48_000;
+const CHANNELS_RANGE: std::ops::RangeInclusive<u16> = 1..=2;
+
+fn validate_python_path(python_path: Option<&str>) -> Result<(), String> {
+    match python_path {
+        Some(path) if !std::path::Path::new(path).is_file() => {
+            Err(format!("Python executable not found at {path}"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Variable names must be non-empty and free of `=`; neither side may hold a NUL
+fn validate_engine_env(engine_env: &HashMap<String, String>) -> Result<(), String> {
+    for (key, value) in engine_env {
+        if key.trim().is_empty() {
+            return Err("Engine environment variable names can't be empty".to_string());
+        }
+        if key.contains(['=', '\0']) || value.contains('\0') {
+            return Err(format!("Invalid engine environment variable {key}"));
+        }
+    }
+    Ok(())
+}
+
+fn validate_audio_format(sample_rate: Option<u32>, channels: Option<u16>) -> Result<(), String> {
+    if let Some(rate) = sample_rate.filter(|rate| !SAMPLE_RATE_RANGE_HZ.contains(rate)) {
+        return Err(format!(
+            "Sample rate {rate} Hz is outside {}-{} Hz",
+            SAMPLE_RATE_RANGE_HZ.start(),
+            SAMPLE_RATE_RANGE_HZ.end()
+        ));
+    }
+    if let Some(channels) = channels.filter(|c| !CHANNELS_RANGE.contains(c)) {
+        return Err(format!(
+            "Channel count {channels} is outside {}-{}",
+            CHANNELS_RANGE.start(),
+            CHANNELS_RANGE.end()
+        ));
+    }
+    Ok(())
+}
+
+/// Duck or restore system audio and tell the frontend once the fade has finished
+fn set_ducking(app: &AppHandle, duck: bool) -> Result<(), String> {
+    system_audio::set_music_muted(duck, &duck_config(&current_config(app)))?;
+    let _ = app.emit("stt:audio-ducked", AudioDuckedEvent { ducked: duck });
+    Ok(())
+}
+
+type DuckRequest = (bool, Option<std::sync::mpsc::Sender<Result<(), String>>>);
+
+/// Fades run on one worker so they never stall the engine reader and always
+/// apply in the order they were requested
+fn ducking_worker(app: &AppHandle) -> &'static std::sync::mpsc::Sender<DuckRequest> {
+    static WORKER: OnceLock<std::sync::mpsc::Sender<DuckRequest>> = OnceLock::new();
+    WORKER.get_or_init(|| {
+        let (tx, rx) = std::sync::mpsc::channel::<DuckRequest>();
+        let app = app.clone();
+        std::thread::spawn(move || {
+            for (duck, reply) in rx {
+                let result = set_ducking(&app, duck);
+                match reply {
+                    Some(reply) => {
+                        let _ = reply.send(result);
+                    }
+                    None => {
+                        if let Err(err) = result {
+                            let action = if duck {
+                                "duck audio"
+                            } else {
+                                "restore audio mute state"
+                            };
+                            emit_log(&app, "audio", &format!("failed to {action}: {err}"));
+                        }
+                    }
+                }
+            }
+        });
+        tx
+    })
+}
+
+/// Duck or restore in the background; failures are logged
+fn queue_ducking(app: &AppHandle, duck: bool) {
+    let _ = ducking_worker(app).send((duck, None));
+}
+
+/// Duck or restore after any queued fades, waiting for the result
+fn set_ducking_in_order(app: &AppHandle, duck: bool) -> Result<(), String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    ducking_worker(app)
+        .send((duck, Some(tx)))
+        .map_err(|_| "Audio worker stopped".to_string())?;
+    rx.recv().map_err(|_| "Audio worker stopped".to_string())?
+}
+
+fn duck_config(config: &SttConfig) -> system_audio::DuckConfig {
+    system_audio::DuckConfig {
+        min_volume: config.duck_min_volume.clamp(0.0, 1.0),
+        ratio: config.duck_ratio.clamp(0.0, 1.0),
+        mute: config.mute_mode == MuteMode::Mute,
+        attack_ms: config.duck_attack_ms.min(system_audio::MAX_FADE_MS),
+        release_ms: config.duck_release_ms.min(system_audio::MAX_FADE_MS),
+        enabled: config.duck_audio
+            && !(is_remote_session() && config.remote_session_behavior.disables_ducking()),
+        sessions: config.duck_scope == DuckScope::Sessions,
+    }
+}
+
+/// Checked on every use since a session can be connected to or from RDP at any time
+#[cfg(windows)]
+fn is_remote_session() -> bool {
+    use windows::Win32::UI::WindowsAndMessaging::{GetSystemMetrics, SM_REMOTESESSION};
+    unsafe { GetSystemMetrics(SM_REMOTESESSION) != 0 }
+}
+
+#[cfg(not(windows))]
+fn is_remote_session() -> bool {
+    false
+}
+
+/// Where a finished transcript ends up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TranscriptRoute {
+    Typed,
+    Clipboard,
+    Both,
+    /// Left where it already shows; nothing to deliver or flash
+    Shown,
+}
+
+fn transcript_route(config: &SttConfig) -> TranscriptRoute {
+    match config.output_mode {
+        OutputMode::Type => TranscriptRoute::Typed,
+        OutputMode::Clipboard => TranscriptRoute::Clipboard,
+        OutputMode::Both => TranscriptRoute::Both,
+        OutputMode::Off => TranscriptRoute::Shown,
+    }
+}
+
+/// Type a transcript the engine left to the app; keystroke injection already happened
+fn type_transcript(config: &SttConfig, text: &str) -> Result<(), String> {
+    if config.inject_method == InjectMethod::Accessibility {
+        text_injection::insert_into_focused_element(&format!("{text} "))
+            .map_err(|err| format!("accessibility injection failed: {err}"))
+    } else {
+        // The engine already typed it
+        Ok(())
+    }
+}
+
+fn copy_transcript(config: &SttConfig, text: &str) -> Result<(), String> {
+    clipboard::set_text(text, config.clipboard_history)
+        .map_err(|err| format!("clipboard copy failed: {err}"))
+}
+
+/// Linear blend between the low and high confidence colors, per channel
+fn confidence_color(confidence: f32) -> u32 {
+    let t = if confidence.is_finite() {
+        confidence.clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let channel = |shift: u32| {
+        let low = ((CONFIDENCE_LOW_COLOR >> shift) & 0xFF) as f32;
+        let high = ((CONFIDENCE_HIGH_COLOR >> shift) & 0xFF) as f32;
+        ((low + (high - low) * t).round() as u32) << shift
+    };
+    channel(16) | channel(8) | channel(0)
+}
+
+/// Deliver a transcript per config and flash the overlay with the route's color
+fn route_transcript(app: &AppHandle, text: &str, confidence: Option<f32>) {
+    let config = current_config(app);
+    if config.commit_mode {
+        buffer_transcript(app, text);
+        return;
+    }
+    let route = transcript_route(&config);
+    if route == TranscriptRoute::Shown {
+        return;
+    }
+    let app = app.clone();
+    let text = text.to_string();
+    std::thread::spawn(move || {
+        let (delivered, flash_color) = match route {
+            TranscriptRoute::Typed => (
+                type_transcript(&config, &text),
+                config.overlay_type_flash_color,
+            ),
+            TranscriptRoute::Clipboard => (
+                copy_transcript(&config, &text),
+                config.overlay_clipboard_flash_color,
+            ),
+            TranscriptRoute::Both => {
+                // Copy even when typing failed, so the text can still be pasted by hand
+                let typed = type_transcript(&config, &text);
+                let copied = copy_transcript(&config, &text);
+                (typed.and(copied), config.overlay_type_flash_color)
+            }
+            TranscriptRoute::Shown => return,
+        };
+        let flash_color = match confidence {
+            Some(confidence) if config.overlay_confidence_flash => confidence_color(confidence),
+            _ => flash_color,
+        };
+        match delivered {
+            Ok(()) => {
+                let _ = native_overlay::flash(flash_color, OVERLAY_FLASH_MS);
+            }
+            Err(err) => emit_log(&app, "inject", &err),
+        }
+    });
+}
+
+/// Replace the pending commit text and show it on the overlay and scratchpad
+fn set_commit_buffer(app: &AppHandle, text: String) {
+    if let Ok(mut guard) = app.state::<AppState>().0.lock() {
+        guard.commit_buffer = text.clone();
+    }
+    let _ = native_overlay::set_commit_text(Some(text.clone()).filter(|t| !t.is_empty()));
+    let _ = app.emit("stt:commit-buffer", CommitBufferEvent { text });
+}
+
+fn buffer_transcript(app: &AppHandle, text: &str) {
+    let mut buffer = app
+        .state::<AppState>()
+        .0
+        .lock()
+        .map(|g| g.commit_buffer.clone())
+        .unwrap_or_default();
+    if !buffer.is_empty() {
+        buffer.push(' ');
+    }
+    buffer.push_str(text.trim());
+    set_commit_buffer(app, buffer);
+}
+
+/// Type the buffered text into the focused app, falling back like a refused injection
+fn commit_buffer(app: &AppHandle) {
+    let text = app
+        .state::<AppState>()
+        .0
+        .lock()
+        .map(|mut g| std::mem::take(&mut g.commit_buffer))
+        .unwrap_or_default();
+    set_commit_buffer(app, String::new());
+    if text.is_empty() {
+        return;
+    }
+    let config = current_config(app);
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let text = format!("{text} ");
+        let delivered = if config.inject_method == InjectMethod::Accessibility {
+            text_injection::insert_into_focused_element(&text)
+        } else {
+            text_injection::send_unicode_text(&text)
+        };
+        match delivered {
+            Ok(()) => {
+                let _ = native_overlay::flash(config.overlay_type_flash_color, OVERLAY_FLASH_MS);
+            }
+            Err(err) => handle_inject_failed(&app, &err, &text),
+        }
+    });
+}
+
+fn handle_shortcut(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    // Act on release so the hotkey's modifiers are up before text is typed
+    if event.state != ShortcutState::Released {
+        return;
+    }
+    let config = current_config(app);
+    let toggle = config.toggle_engine_hotkey.as_deref();
+    if toggle
+        .and_then(|hotkey| hotkey.parse::<Shortcut>().ok())
+        .as_ref()
+        == Some(shortcut)
+    {
+        // Starting waits on the spawn; keep that off the event loop
+        let app = app.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = toggle_engine(&app, &app.state::<AppState>()) {
+                emit_log(&app, "engine", &format!("hotkey toggle failed: {err}"));
+            }
+        });
+        return;
+    }
+    if !config.commit_mode {
+        return;
+    }
+    if config.commit_hotkey.parse::<Shortcut>().ok().as_ref() == Some(shortcut) {
+        commit_buffer(app);
+    } else if config.discard_hotkey.parse::<Shortcut>().ok().as_ref() == Some(shortcut) {
+        set_commit_buffer(app, String::new());
+    }
+}
+
+/// Answer a control server request; every command replies with the resulting status
+fn handle_control_command(
+    app: &AppHandle,
+    command: control_server::ControlCommand,
+) -> Result<SttStatus, String> {
+    let state = app.state::<AppState>();
+    match command {
+        control_server::ControlCommand::Start => {
+            if let Err(err) = start_engine_inner(app, &state) {
+                emit_error(app, start_error_kind(app), &err);
+                return Err(err);
+            }
+        }
+        control_server::ControlCommand::Stop => {
+            flush_config(app, &state)?;
+            stop_engine_inner(app, &state)?;
+        }
+        control_server::ControlCommand::Toggle => return toggle_engine(app, &state),
+        control_server::ControlCommand::Status => {}
+    }
+    emit_status(app)
+}
+
+/// Run the control server on the configured port, or stop it when it is turned off
+fn sync_control_server(app: &AppHandle) {
+    let config = current_config(app).control_server;
+    if !config.enabled {
+        control_server::stop();
+        return;
+    }
+    let handle = app.clone();
+    let started = control_server::start(config.port, move |command| {
+        let status = handle_control_command(&handle, command)?;
+        serde_json::to_value(status).map_err(|e| e.to_string())
+    });
+    if let Err(err) = started {
+        emit_error(app, "control_server_unavailable", &err);
+    }
+}
+
+/// Hold the engine toggle hotkey when one is set, and the commit/discard hotkeys only
+/// while commit mode is on
+fn sync_shortcuts(app: &AppHandle) {
+    let shortcuts = app.global_shortcut();
+    let _ = shortcuts.unregister_all();
+    let config = current_config(app);
+    if let Some(hotkey) = &config.toggle_engine_hotkey {
+        // Usually another app already holds the combination
+        if let Err(err) = shortcuts.register(hotkey.as_str()) {
+            emit_error(
+                app,
+                "hotkey_unavailable",
+                &format!("failed to register {hotkey}: {err}"),
+            );
+        }
+    }
+    if !config.commit_mode {
+        return;
+    }
+    for hotkey in [&config.commit_hotkey, &config.discard_hotkey] {
+        if let Err(err) = shortcuts.register(hotkey.as_str()) {
+            emit_log(
+                app,
+                "hotkey",
+                &format!("failed to register {hotkey}: {err}"),
+            );
+        }
+    }
+}
+
+/// The engine couldn't type into the foreground window (e.g. an elevated app)
+fn handle_inject_failed(app: &AppHandle, reason: &str, text: &str) {
+    let _ = app.emit(
+        "stt:inject-failed",
+        InjectFailedEvent {
+            reason: reason.to_string(),
+            text: text.to_string(),
+        },
+    );
+    let config = current_config(app);
+    match config.inject_fallback {
+        InjectFallback::Clipboard => match clipboard::set_text(text, config.clipboard_history) {
+            Ok(()) => {
+                let _ =
+                    native_overlay::flash(config.overlay_clipboard_flash_color, OVERLAY_FLASH_MS);
+            }
+            Err(err) => emit_log(app, "inject", &format!("clipboard fallback failed: {err}")),
+        },
+        InjectFallback::Notify => {
+            emit_log(
+                app,
+                "inject",
+                &format!("transcript not delivered: {reason}"),
+            );
+        }
+        InjectFallback::Drop => {}
+    }
+}
+
+fn log_to_file(message: &str) {
+    log_file::append(message);
+}
+
+fn spawn_reader_thread<R: std::io::Read + Send + 'static>(
+    app: AppHandle,
+    stream_name: &'static str,
+    reader: R,
+) {
+    std::thread::spawn(move || {
+        let buf = BufReader::new(reader);
+        for line in buf.lines().map_while(Result::ok) {
+            log_line(&app, &format!("python:{stream_name}"), &line);
+            if raw_tap_enabled_flag().load(Ordering::Relaxed) {
+                let _ = app.emit(
+                    "stt:raw",
+                    RawLineEvent {
+                        stream: stream_name.to_string(),
+                        line: line.clone(),
+                    },
+                );
+            }
+            let message = handle_engine_line(&line);
+            if message.is_work() {
+                engine_activity();
+            }
+            match message {
+                // Already in the log file from the top of the loop
+                EngineMessage::Log { raw } => publish_log(&app, LogEvent::new(stream_name, raw)),
+                // Control messages the app doesn't handle still aren't log output
+                EngineMessage::Unhandled if stream_name == "stdout" => {}
+                EngineMessage::Unhandled => publish_log(&app, LogEvent::new(stream_name, line)),
+                message => act_on_engine_message(&app, message),
+            }
+        }
+    });
+}
+
+/// One line of engine output, parsed without acting on it; `type` picks the variant
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum EngineMessage {
+    Overlay {
+        hover: bool,
+    },
+    ModelProgress(ModelProgressEvent),
+    Hotkey {
+        down: bool,
+    },
+    DictationStart,
+    DictationStop,
+    Cancelled,
+    Recording {
+        active: bool,
+    },
+    #[serde(rename = "overlay_level")]
+    Level {
+        level: f64,
+    },
+    InjectFailed {
+        reason: Option<String>,
+        #[serde(default)]
+        text: String,
+    },
+    AudioFormat(AudioFormat),
+    EngineInfo(EngineInfo),
+    /// Reply to a `request_engine` command, passed on whole
+    Response(serde_json::Value),
+    Ready,
+    /// Handshake; engines predating the `protocol` field speak protocol 0
+    EngineReady {
+        #[serde(default)]
+        protocol: u32,
+    },
+    Transcript {
+        text: String,
+        /// Engines that predate interim results only send final text
+        #[serde(rename = "final", default = "default_final")]
+        is_final: bool,
+        #[serde(rename = "segmentId")]
+        segment_id: Option<String>,
+        confidence: Option<f32>,
+        /// Unix milliseconds when the utterance's audio ended
+        #[serde(rename = "captureTs")]
+        capture_ts: Option<u64>,
+    },
+    /// Anything that isn't a typed JSON object: plain output, malformed JSON
+    #[serde(skip)]
+    Log {
+        raw: String,
+    },
+    /// Typed JSON the app doesn't know, or a known type missing the fields it needs
+    #[serde(other)]
+    Unhandled,
+}
+
+fn default_final() -> bool {
+    true
+}
+
+impl EngineMessage {
+    /// Output that only a working engine produces. Handshakes and hotkey echoes come from
+    /// side threads that keep running when the main loop hangs, so they don't count
+    fn is_work(&self) -> bool {
+        !matches!(
+            self,
+            Self::Overlay { .. }
+                | Self::Hotkey { .. }
+                | Self::Ready
+                | Self::EngineReady { .. }
+                | Self::EngineInfo(_)
+                | Self::AudioFormat(_)
+                | Self::Unhandled
+        )
+    }
+}
+
+fn handle_engine_line(line: &str) -> EngineMessage {
+    if let Ok(message) = serde_json::from_str::<EngineMessage>(line) {
+        return message;
+    }
+    // A known type with bad fields is still a control message, not output
+    let typed = serde_json::from_str::<serde_json::Value>(line)
+        .is_ok_and(|value| value.get("type").is_some_and(|v| v.is_string()));
+    if typed {
+        EngineMessage::Unhandled
+    } else {
+        EngineMessage::Log {
+            raw: line.to_string(),
+        }
+    }
+}
+
+fn act_on_engine_message(app: &AppHandle, message: EngineMessage) {
+    match message {
+        EngineMessage::Overlay { hover: true } => {
+            hover_dwell_seq().fetch_add(1, Ordering::SeqCst);
+            overlay_hover_requested_flag().store(true, Ordering::SeqCst);
+            reconcile_overlay(app);
+            let _ = crate::native_overlay::set_hover(true);
+        }
+        EngineMessage::Overlay { hover: false } => {
+            // Dwell before collapsing; cancel if another event arrives
+            let seq = hover_dwell_seq().fetch_add(1, Ordering::SeqCst) + 1;
+            let dwell_ms = overlay_dwell_ms(&current_config(app));
+            let app_for_dwell = app.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(std::time::Duration::from_millis(dwell_ms));
+                if hover_dwell_seq().load(Ordering::SeqCst) == seq {
+                    overlay_hover_requested_flag().store(false, Ordering::SeqCst);
+                    let _ = crate::native_overlay::set_hover(false);
+                    reconcile_overlay(&app_for_dwell);
+                }
+            });
+        }
+        EngineMessage::ModelProgress(progress) => {
+            let _ = app.emit("stt:model_progress", progress);
+        }
+        EngineMessage::Hotkey { down } => {
+            // Toggle mode leaves the overlay to the engine's `overlay` messages
+            if current_config(app).hotkey_mode == HotkeyMode::PushToTalk {
+                let _ = crate::native_overlay::set_hover(down);
+            }
+        }
+        EngineMessage::DictationStart => {
+            // Emit event first so the frontend can play the sound effect
+            emit_dictation_start(app);
+            queue_ducking(app, true);
+        }
+        EngineMessage::DictationStop => {
+            queue_ducking(app, false);
+            emit_dictation_stop(app);
+        }
+        EngineMessage::Cancelled => {
+            let _ = app.emit("stt:cancelled", ());
+            let _ = native_overlay::flash(
+                current_config(app).overlay_cancel_flash_color,
+                OVERLAY_FLASH_MS,
+            );
+        }
+        EngineMessage::Recording { active } => queue_ducking(app, active),
+        EngineMessage::Level { level } => {
+            let _ = crate::native_overlay::set_level(level as f32);
+            let _ = app.emit("stt:level", LevelEvent { level });
+        }
+        EngineMessage::InjectFailed { reason, text } => {
+            handle_inject_failed(app, reason.as_deref().unwrap_or("unknown"), &text)
+        }
+        EngineMessage::AudioFormat(format) => {
+            if let Ok(mut guard) = app.state::<AppState>().0.lock() {
+                guard.audio_format = Some(format);
+            }
+            let _ = app.emit("stt:audio-format", format);
+        }
+        EngineMessage::EngineInfo(info) => {
+            if let Ok(mut guard) = app.state::<AppState>().0.lock() {
+                guard.engine_info = Some(info.clone());
+            }
+            let _ = app.emit("stt:engine-info", info);
+        }
+        EngineMessage::Response(reply) => app.state::<AppState>().complete_request(reply),
+        EngineMessage::Ready => {
+            let started_at = app.state::<AppState>().0.lock().ok().and_then(|mut guard| {
+                guard.ready = true;
+                guard.started_at
+            });
+            let startup_ms = started_at.map_or(0, |at| now_millis().saturating_sub(at));
+            let _ = app.emit("stt:ready", ReadyEvent { startup_ms });
+            let _ = emit_status(app);
+        }
+        EngineMessage::EngineReady { protocol } => {
+            if let Ok(mut guard) = app.state::<AppState>().0.lock() {
+                guard.engine_protocol = Some(protocol);
+            }
+            if protocol != ENGINE_PROTOCOL_VERSION {
+                emit_log(
+                    app,
+                    "engine",
+                    &compat_report(ENGINE_PROTOCOL_VERSION, Some(protocol)).advice,
+                );
+            }
+        }
+        EngineMessage::Transcript {
+            text,
+            is_final,
+            segment_id,
+            confidence,
+            capture_ts,
+        } => {
+            let event = TranscriptEvent {
+                text,
+                is_final,
+                segment_id,
+                latency_ms: capture_ts.map(|ts| now_millis().saturating_sub(ts)),
+            };
+            if !event.is_final {
+                // Interim text is only for display; typing waits for the final
+                emit_transcript(app, &event);
+                return;
+            }
+            let text = event.text.as_str();
+            if let Ok(mut guard) = app.state::<AppState>().0.lock() {
+                if guard.config.keep_history {
+                    let entry = TranscriptEntry {
+                        text: text.to_string(),
+                        timestamp: now_millis(),
+                        latency_ms: event.latency_ms,
+                    };
+                    push_transcript_history(&mut guard.transcript_history, entry);
+                }
+            }
+            emit_transcript(app, &event);
+            let _ = native_overlay::push_transcript(text);
+            route_transcript(app, text, confidence);
+        }
+        EngineMessage::Unhandled | EngineMessage::Log { .. } => {}
+    }
+}
+
+/// A Python launcher to try, with how the engine script is passed to it
+#[derive(Debug, Clone, PartialEq)]
+struct PythonInterpreter {
+    program: std::ffi::OsString,
+    /// Launcher arguments ahead of the interpreter's own, e.g. `-3` for `pyw`
+    launcher_args: Vec<std::ffi::OsString>,
+    /// Root of the bundled runtime; sets `PYTHONHOME` and leads `PATH` instead of `PYTHONPATH`
+    embedded_home: Option<PathBuf>,
+    /// Run the script as `-m <module>` from the cwd rather than by path
+    by_module: bool,
+}
+
+impl PythonInterpreter {
+    fn new(program: impl Into<std::ffi::OsString>, by_module: bool) -> Self {
+        Self {
+            program: program.into(),
+            launcher_args: Vec::new(),
+            embedded_home: None,
+            by_module,
+        }
+    }
+}
+
+/// Interpreters to try, most preferred first: the configured `python_path` alone, else the
+/// embedded runtime, `pyw -3`, `pythonw` and `python` on Windows, or `python` elsewhere
+fn python_interpreters(
+    configured: Option<&str>,
+    embedded_home: Option<&std::path::Path>,
+    windows: bool,
+) -> Vec<PythonInterpreter> {
+    if let Some(python) = configured {
+        return vec![PythonInterpreter::new(python, false)];
+    }
+    if !windows {
+        return vec![PythonInterpreter::new("python", false)];
+    }
+    let mut interpreters = Vec::new();
+    if let Some(home) = embedded_home {
+        // By path, since the embedded runtime's ._pth file ignores PYTHONPATH
+        interpreters.push(PythonInterpreter {
+            embedded_home: Some(home.to_path_buf()),
+            ..PythonInterpreter::new(home.join("pythonw.exe"), false)
+        });
+    }
+    interpreters.push(PythonInterpreter {
+        launcher_args: vec!["-3".into()],
+        ..PythonInterpreter::new("pyw", true)
+    });
+    interpreters.push(PythonInterpreter::new("pythonw", true));
+    interpreters.push(PythonInterpreter::new("python", true));
+    interpreters
+}
+
+/// Full argument list to run the engine script unbuffered with `script_args`
+fn engine_invocation_args(
+    interpreter: &PythonInterpreter,
+    script_path: &std::path::Path,
+    script_args: &[std::ffi::OsString],
+) -> Vec<std::ffi::OsString> {
+    let mut args = interpreter.launcher_args.clone();
+    args.push("-u".into());
+    if interpreter.by_module {
+        args.push("-m".into());
+        args.push(script_module_name(script_path));
+    } else {
+        args.push(script_path.into());
+    }
+    args.extend(script_args.iter().cloned());
+    args
+}
+
+/// A ready-to-spawn command running `interpreter` with `args` (launcher args included)
+/// in `cwd`, with piped stdio and the engine's module search path
+fn build_python_command(
+    interpreter: &PythonInterpreter,
+    args: &[std::ffi::OsString],
+    cwd: &std::path::Path,
+    engine_env: &HashMap<String, String>,
+) -> Command {
+    let mut command = Command::new(&interpreter.program);
+    command
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .current_dir(cwd);
+    if let Some(home) = &interpreter.embedded_home {
+        command
+            .env("PYTHONHOME", home)
+            .env("PYTHONNOUSERSITE", "1")
+            .env("PATH", engine_python_path(home, std::env::var_os("PATH")));
+    } else {
+        command.env(
+            "PYTHONPATH",
+            engine_python_path(cwd, std::env::var_os("PYTHONPATH")),
+        );
+    }
+    command.envs(engine_env);
+    #[cfg(windows)]
+    command.creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+/// `python_interpreters` for the current config and platform
+fn configured_python_interpreters(app: &AppHandle, config: &SttConfig) -> Vec<PythonInterpreter> {
+    let embedded_home = resolve_embedded_python_dir(app).filter(|dir| {
+        let exists = cfg!(windows) && dir.join("pythonw.exe").exists();
+        if cfg!(windows) && !exists {
+            log_to_file("[warn] embedded pythonw.exe not found; falling back");
+        }
+        exists
+    });
+    python_interpreters(
+        config.python_path.as_deref(),
+        embedded_home.as_deref(),
+        cfg!(windows),
+    )
+}
+
+/// Run the engine script once with `probe_args` and return its stdout, trying the
+/// same interpreters as `spawn_engine` in the same order
+fn run_engine_probe(app: &AppHandle, probe_args: &[&str]) -> Result<String, String> {
+    let config = current_config(app);
+    let script_path = resolve_script_path(app, &config);
+    if !script_path.exists() {
+        return Err(format!(
+            "Python script not found at {}",
+            script_path.display()
+        ));
+    }
+    let python_dir = engine_python_dir(&script_path);
+
+    let mut errors = Vec::new();
+    let probe_args: Vec<std::ffi::OsString> = probe_args.iter().map(Into::into).collect();
+    for interpreter in configured_python_interpreters(app, &config) {
+        let args = engine_invocation_args(&interpreter, &script_path, &probe_args);
+        let mut command =
+            build_python_command(&interpreter, &args, &python_dir, &config.engine_env);
+        command.stdin(Stdio::null());
+        let program = command.get_program().to_string_lossy().into_owned();
+        match command.output() {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+            }
+            Ok(output) => errors.push(format!(
+                "{program} exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+            Err(err) => errors.push(format!("{program}: {err}")),
+        }
+    }
+    Err(format!("Failed to run the engine: {}", errors.join("; ")))
+}
+
+/// Parse the JSON line printed by `DOCTOR_SCRIPT`
+fn parse_doctor_output(stdout: &str) -> Option<DoctorReport> {
+    let value: serde_json::Value = stdout
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str(line).ok())?;
+    Some(DoctorReport {
+        python_found: true,
+        python_version: value.get("version")?.as_str().map(str::to_string),
+        missing_modules: value
+            .get("missing")?
+            .as_array()?
+            .iter()
+            .filter_map(|m| m.as_str().map(str::to_string))
+            .collect(),
+    })
+}
+
+/// Check the first interpreter that runs for the engine's imports, without loading them
+fn run_doctor(app: &AppHandle) -> DoctorReport {
+    let config = current_config(app);
+    let script_path = resolve_script_path(app, &config);
+    let python_dir = engine_python_dir(&script_path);
+    let mut python_found = false;
+    // The engine directory may be the very thing that's missing
+    let cwd = if python_dir.is_dir() {
+        python_dir
+    } else {
+        std::env::temp_dir()
+    };
+    for interpreter in configured_python_interpreters(app, &config) {
+        let mut args = interpreter.launcher_args.clone();
+        args.push("-c".into());
+        args.push(DOCTOR_SCRIPT.into());
+        args.extend(ENGINE_REQUIRED_MODULES.iter().map(Into::into));
+        let mut command = build_python_command(&interpreter, &args, &cwd, &config.engine_env);
+        command.stdin(Stdio::null());
+        let Ok(output) = command.output() else {
+            continue;
+        };
+        python_found = true;
+        if let Some(report) = parse_doctor_output(&String::from_utf8_lossy(&output.stdout)) {
+            return report;
+        }
+    }
+    // Nothing reported, so none of the imports can be vouched for
+    DoctorReport {
+        python_found,
+        python_version: None,
+        missing_modules: ENGINE_REQUIRED_MODULES
+            .iter()
+            .map(|m| m.to_string())
+            .collect(),
+    }
+}
+
+/// The engine prints log lines before the device list, so take the last line that parses
+fn parse_audio_devices(stdout: &str) -> Result<Vec<AudioDevice>, String> {
+    stdout
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str(line).ok())
+        .ok_or_else(|| "Engine did not report a device list".to_string())
+}
+
+fn start_engine_inner(app: &AppHandle, state: &AppState) -> Result<(), String> {
+    spawn_engine(app, state, true)
+}
+
+/// Stop the engine if it is running, otherwise start it. The running check and the
+/// start/stop it picks share one lifecycle lock, so two quick toggles always cancel out
+fn toggle_engine(app: &AppHandle, state: &AppState) -> Result<SttStatus, String> {
+    let lifecycle = engine_lifecycle_lock()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    let running = state
+        .0
+        .lock()
+        .map_err(|_| "State lock poisoned")?
+        .child
+        .is_some();
+    if running {
+        stop_engine_locked(app, state, &lifecycle)?;
+    } else if let Err(err) = spawn_engine_locked(app, state, true, &lifecycle) {
+        emit_error(app, start_error_kind(app), &err);
+        return Err(err);
+    }
+    let guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+    Ok(engine_status(&guard))
+}
+
+fn store_spawn_diagnostics(state: &AppState, diagnostics: SpawnDiagnostics) {
+    if let Ok(mut guard) = state.0.lock() {
+        guard.last_spawn = diagnostics;
+    }
+}
+
+/// Spawn the engine; when `armed` is false it loads the model but ignores the hotkey
+/// until an `{"type":"arm"}` command arrives on stdin
+fn spawn_engine(app: &AppHandle, state: &AppState, armed: bool) -> Result<(), String> {
+    let lifecycle = engine_lifecycle_lock()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    spawn_engine_locked(app, state, armed, &lifecycle)
+}
+
+/// The body of `spawn_engine`; the guard proves the caller holds the lifecycle lock
+fn spawn_engine_locked(
+    app: &AppHandle,
+    state: &AppState,
+    armed: bool,
+    _lifecycle: &MutexGuard<'_, ()>,
+) -> Result<(), String> {
+    let config = {
+        let guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        guard.child.is_none().then(|| guard.config.clone())
+    };
+    let Some(config) = config else {
+        let _ = emit_status(app);
+        return Ok(());
+    };
+
+    let script_path = resolve_script_path(app, &config);
+    log_to_file(&format!(
+        "[setup] resolved Python script path: {}",
+        script_path.display()
+    ));
+    eprintln!(
+        "[setup] resolved Python script path: {}",
+        script_path.display()
+    );
+    if !script_path.exists() {
+        let msg = format!("Python script not found at {}", script_path.display());
+        log_to_file(&format!("[error] {msg}"));
+        return Err(msg);
+    }
+
+    let model_dir = resolve_model_dir(app, &config);
+    if engine_requires_model_dir(&config) && !model_dir.is_dir() {
+        let msg = format!("Model directory not found at {}", model_dir.display());
+        log_to_file(&format!("[error] {msg}"));
+        return Err(msg);
+    }
+    let python_dir = engine_python_dir(&script_path);
+    log_to_file(&format!("[setup] python cwd: {}", python_dir.display()));
+    log_to_file(&format!("[setup] model dir: {}", model_dir.display()));
+
+    let script_args = engine_args(&config, &model_dir, armed);
+    let mut diagnostics = SpawnDiagnostics::default();
+    let mut errors = Vec::new();
+    let mut spawned = None;
+    for interpreter in configured_python_interpreters(app, &config) {
+        let args = engine_invocation_args(&interpreter, &script_path, &script_args);
+        let program = interpreter.program.to_string_lossy().into_owned();
+        eprintln!("[engine] spawn cwd: {}", python_dir.display());
+        eprintln!("[engine] spawn cmd: {program} {:?}", args);
+        match build_python_command(&interpreter, &args, &python_dir, &config.engine_env).spawn() {
+            Ok(ch) => {
+                log_to_file(&format!("[engine] started with {program} {:?}", args));
+                spawned = Some(ch);
+                break;
+            }
+            Err(err) => {
+                log_to_file(&format!("[error] {program} spawn failed: {err}"));
+                diagnostics.record(&interpreter.program, &args, &err);
+                errors.push(format!("{program} error: {err}"));
+            }
+        }
+    }
+    let Some(mut child) = spawned else {
+        store_spawn_diagnostics(state, diagnostics);
+        let msg = match config.python_path.as_deref() {
+            Some(python) => format!("Failed to start Python at {python}: {}", errors.join("; ")),
+            None => format!("Failed to start Python: {}", errors.join("; ")),
+        };
+        log_to_file(&format!("[error] {msg}"));
+        return Err(msg);
+    };
+    store_spawn_diagnostics(state, diagnostics);
+
+    if let Some(stdout) = child.stdout.take() {
+        spawn_reader_thread(app.clone(), "stdout", stdout);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        spawn_reader_thread(app.clone(), "stderr", stderr);
+    }
+
+    let stdin = child.stdin.take();
     {
-        let (x, y) = match app.primary_monitor() {
-            Ok(Some(monitor)) => {
-                let size = monitor.size();
-                let position = monitor.position();
-                let width = size.width as i32;
-                let mut computed_x =
-                    position.x + (width - OVERLAY_WIDTH_PX) / 2 - OVERLAY_HORIZONTAL_OFFSET_PX;
-                if computed_x < position.x {
-                    computed_x = position.x;
+        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        guard.child = Some(child);
+        guard.stdin = stdin;
+        guard.engine_protocol = None;
+        guard.audio_format = None;
+        guard.engine_info = None;
+        guard.started_at = Some(now_millis());
+        guard.launch_config = Some(config.clone());
+        guard.ready = false;
+    }
+    engine_activity();
+
+    let _ = emit_status(app);
+    reconcile_overlay(app);
+    if armed {
+        schedule_ready_timeout(app, state);
+    } else {
+        engine_ready_seq().fetch_add(1, Ordering::SeqCst);
+    }
+
+    let started_at = std::time::Instant::now();
+    let app_for_monitor = app.clone();
+    let state_for_monitor = state.clone();
+    std::thread::spawn(move || loop {
+        let (exit_status, over_limit_bytes) = {
+            let mut guard = match state_for_monitor.0.lock() {
+                Ok(g) => g,
+                Err(_) => return,
+            };
+            let limit_mb = guard.config.engine_memory_limit_mb;
+            let Some(child) = guard.child.as_mut() else {
+                return;
+            };
+
+            let status = match child.try_wait() {
+                Ok(Some(status)) => Some(status),
+                Ok(None) => None,
+                Err(_) => Some(std::process::ExitStatus::from_raw(1)),
+            };
+            let over_limit_bytes = match limit_mb {
+                Some(limit_mb) if status.is_none() => process_stats::resident_bytes(child)
+                    .filter(|bytes| *bytes > limit_mb.saturating_mul(1024 * 1024)),
+                _ => None,
+            };
+            (status, over_limit_bytes)
+        };
+
+        if let Some(status) = exit_status {
+            let restart_attempt = {
+                let mut guard = match state_for_monitor.0.lock() {
+                    Ok(g) => g,
+                    Err(_) => return,
+                };
+                guard.child = None;
+                guard.stdin = None;
+                guard.started_at = None;
+                guard.ready = false;
+                guard.last_exit_code = status.code();
+                // Dropping the senders wakes any waiting requests with an error
+                guard.pending_requests.clear();
+                if started_at.elapsed() >= Duration::from_millis(ENGINE_HEALTHY_UPTIME_MS) {
+                    guard.restart_attempts.store(0, Ordering::SeqCst);
+                }
+                if guard.config.auto_restart && !status.success() {
+                    next_restart_attempt(&mut guard)
+                } else {
+                    None
+                }
+            };
+            reset_engine_activity();
+            overlay_hover_requested_flag().store(false, Ordering::SeqCst);
+            let _ = emit_status(&app_for_monitor);
+            reconcile_overlay(&app_for_monitor);
+            emit_log(
+                &app_for_monitor,
+                "engine",
+                &format!("python exited: {status}"),
+            );
+            if !status.success() {
+                emit_error(
+                    &app_for_monitor,
+                    "engine_crashed",
+                    &format!("python exited: {status}"),
+                );
+                report_engine_crash(&app_for_monitor, status.code());
+            }
+            if let Err(err) = set_ducking_in_order(&app_for_monitor, false) {
+                emit_log(
+                    &app_for_monitor,
+                    "audio",
+                    &format!("failed to restore audio mute state: {err}"),
+                );
+            }
+            if let Some(attempt) = restart_attempt {
+                let delay_ms = restart_delay_ms(attempt);
+                emit_log(
+                    &app_for_monitor,
+                    "engine",
+                    &format!(
+                        "restarting engine in {delay_ms} ms (attempt {})",
+                        attempt + 1
+                    ),
+                );
+                std::thread::sleep(Duration::from_millis(delay_ms));
+                if !current_config(&app_for_monitor).auto_restart {
+                    return;
                 }
-                let computed_y = position.y + OVERLAY_VERTICAL_MARGIN_PX;
-                (computed_x, computed_y)
+                start_engine_with_retry(&app_for_monitor, &state_for_monitor);
+            } else if !status.success() && current_config(&app_for_monitor).auto_restart {
+                emit_log(
+                    &app_for_monitor,
+                    "engine",
+                    &format!("giving up after {ENGINE_RESTART_MAX_ATTEMPTS} restart attempts"),
+                );
             }
-            _ => (0, OVERLAY_VERTICAL_MARGIN_PX),
-        };
+            return;
+        }
 
-        return native_overlay::configure(
-            OVERLAY_WIDTH_PX.max(1),
-            OVERLAY_HEIGHT_PX.max(1),
-            x,
-            y,
-            OVERLAY_HOVER_SCALE_X,
-            OVERLAY_HOVER_SCALE_Y,
-        );
-    }
+        if let Some(bytes) = over_limit_bytes {
+            emit_log(
+                &app_for_monitor,
+                "engine",
+                &format!(
+                    "engine using {} MiB, over the configured limit; restarting",
+                    bytes / (1024 * 1024)
+                ),
+            );
+            restart_from_monitor(&app_for_monitor, &state_for_monitor);
+            return;
+        }
 
-    #[cfg(not(windows))]
-    {
-        let _ = app;
-        Ok(())
+        if check_engine_stall(&app_for_monitor) {
+            emit_log(&app_for_monitor, "engine", "restarting stalled engine");
+            restart_from_monitor(&app_for_monitor, &state_for_monitor);
+            return;
+        }
+
+        std::thread::sleep(Duration::from_millis(250));
+    });
+
+    Ok(())
+}
+
+/// Replace a still-running engine; the new engine gets its own monitor, so the
+/// calling monitor is done either way
+fn restart_from_monitor(app: &AppHandle, state: &AppState) {
+    if let Ok(mut guard) = state.0.lock() {
+        guard.restart_count += 1;
+    }
+    if let Err(err) = stop_engine_inner(app, state) {
+        emit_log(app, "engine", &format!("restart failed: {err}"));
+        emit_error(app, start_error_kind(app), &err);
+        return;
     }
+    start_engine_with_retry(app, state);
 }
 
-#[cfg_attr(windows, allow(unused_variables))]
-fn set_overlay_visibility(app: &AppHandle, visible: bool) -> Result<(), String> {
-    #[cfg(windows)]
-    {
-        // Avoid redundant show/hide operations
-        let was = overlay_visible_flag().swap(visible, Ordering::SeqCst);
-        if was == visible {
-            return Ok(());
-        }
-        if visible {
-            configure_overlay(app)?;
-            native_overlay::show()
-        } else {
-            native_overlay::hide()
-        }
+/// Take the next crash restart attempt, or `None` once `ENGINE_RESTART_MAX_ATTEMPTS`
+/// have run without a healthy stretch in between
+fn next_restart_attempt(inner: &mut InnerState) -> Option<u32> {
+    let attempt = inner.restart_attempts.fetch_add(1, Ordering::SeqCst);
+    if attempt >= ENGINE_RESTART_MAX_ATTEMPTS {
+        // Giving up; an engine started by hand gets the full backoff again
+        inner.restart_attempts.store(0, Ordering::SeqCst);
+        return None;
     }
+    inner.restart_count += 1;
+    Some(attempt)
+}
 
-    #[cfg(not(windows))]
-    {
-        if let Some(window) = app.get_webview_window("overlay") {
-            if visible {
-                let _: tauri::Result<()> = window.show();
-                let _: tauri::Result<()> = window.set_focus();
-            } else {
-                let _: tauri::Result<()> = window.hide();
-            }
+/// Start the engine for an automatic restart; a spawn failure waits out the next
+/// backoff and tries again until the attempts run out or `auto_restart` is turned off
+fn start_engine_with_retry(app: &AppHandle, state: &AppState) {
+    loop {
+        // A no-op if the engine was started by hand in the meantime
+        let Err(err) = start_engine_inner(app, state) else {
+            return;
+        };
+        emit_log(app, "engine", &format!("restart failed: {err}"));
+        emit_error(app, start_error_kind(app), &err);
+        if !current_config(app).auto_restart {
+            return;
+        }
+        let attempt = match state.0.lock() {
+            Ok(mut guard) => next_restart_attempt(&mut guard),
+            Err(_) => return,
+        };
+        let Some(attempt) = attempt else {
+            emit_log(
+                app,
+                "engine",
+                &format!("giving up after {ENGINE_RESTART_MAX_ATTEMPTS} restart attempts"),
+            );
+            return;
+        };
+        let delay_ms = restart_delay_ms(attempt);
+        emit_log(
+            app,
+            "engine",
+            &format!(
+                "restarting engine in {delay_ms} ms (attempt {})",
+                attempt + 1
+            ),
+        );
+        std::thread::sleep(Duration::from_millis(delay_ms));
+        if !current_config(app).auto_restart {
+            return;
         }
-        Ok(())
     }
 }
 
-fn dev_workspace_root() -> PathBuf {
-    // CARGO_MANIFEST_DIR points to src-tauri; go up one level to workspace root
-    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .parent()
-        .expect("src-tauri should have a parent directory")
-        .to_path_buf()
+/// Backoff before crash restart `attempt` (0-based): 1s, 2s, 4s, ... capped at 30s
+fn restart_delay_ms(attempt: u32) -> u64 {
+    ENGINE_RESTART_BASE_MS
+        .saturating_mul(1u64 << attempt.min(16))
+        .min(ENGINE_RESTART_MAX_MS)
 }
 
-fn resolve_script_path(app: &AppHandle) -> PathBuf {
-    // In dev mode, always use workspace root; in production, use Resource directory
-    let resource_path = app
-        .path()
-        .resolve("python/main.py", tauri::path::BaseDirectory::Resource);
+/// Report `ready_timeout` if the engine hasn't sent `ready` within `ENGINE_READY_TIMEOUT_MS`
+fn schedule_ready_timeout(app: &AppHandle, state: &AppState) {
+    let seq = engine_ready_seq().fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    let state = state.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(ENGINE_READY_TIMEOUT_MS));
+        if engine_ready_seq().load(Ordering::SeqCst) != seq {
+            return;
+        }
+        let waiting = state
+            .0
+            .lock()
+            .map(|g| g.child.is_some() && !g.ready)
+            .unwrap_or(false);
+        if waiting {
+            let message = format!(
+                "engine did not report ready within {} s",
+                ENGINE_READY_TIMEOUT_MS / 1000
+            );
+            emit_log(&app, "engine", &message);
+            emit_error(&app, "ready_timeout", &message);
+        }
+    });
+}
 
-    match resource_path {
-        Ok(path) if path.exists() => path,
-        _ => dev_workspace_root().join("python").join("main.py"),
+/// Poll until the child exits or `timeout` passes; true if it exited
+fn wait_for_exit(child: &mut Child, timeout: Duration) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        if !matches!(child.try_wait(), Ok(None)) {
+            return true;
+        }
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(50));
     }
 }
 
-fn resolve_model_dir(app: &AppHandle) -> PathBuf {
-    let resource_path = app
-        .path()
-        .resolve("data/parakeet_model", tauri::path::BaseDirectory::Resource);
-
-    match resource_path {
-        Ok(path) if path.exists() => path,
-        _ => dev_workspace_root().join("data").join("parakeet_model"),
-    }
+/// Ask the engine to exit on its own so it releases the microphone, falling back
+/// to a kill after the grace period
+fn stop_engine_inner(app: &AppHandle, state: &AppState) -> Result<(), String> {
+    let lifecycle = engine_lifecycle_lock()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    stop_engine_locked(app, state, &lifecycle)
 }
 
-fn resolve_embedded_python_dir(app: &AppHandle) -> Option<PathBuf> {
-    let resource_path = app
-        .path()
-        .resolve("python_embedded", tauri::path::BaseDirectory::Resource)
-        .ok();
+/// The body of `stop_engine_inner`; the guard proves the caller holds the lifecycle lock
+fn stop_engine_locked(
+    app: &AppHandle,
+    state: &AppState,
+    _lifecycle: &MutexGuard<'_, ()>,
+) -> Result<(), String> {
+    // Taking the child also retires its monitor thread
+    let (mut child, stdin) = {
+        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        guard.pending_requests.clear();
+        (guard.child.take(), guard.stdin.take())
+    };
 
-    if let Some(path) = resource_path {
-        if path.exists() {
-            return Some(path);
+    if let Some(child) = child.as_mut() {
+        let requested = stdin.is_some_and(|mut stdin| {
+            writeln!(stdin, "{}", serde_json::json!({ "type": "shutdown" }))
+                .and_then(|()| stdin.flush())
+                .is_ok()
+        });
+        let exited =
+            requested && wait_for_exit(child, Duration::from_millis(ENGINE_SHUTDOWN_GRACE_MS));
+        let outcome = if exited {
+            "engine exited after shutdown request".to_string()
+        } else if requested {
+            format!("engine did not exit within {ENGINE_SHUTDOWN_GRACE_MS} ms; killed")
+        } else {
+            "could not send shutdown request; engine killed".to_string()
+        };
+        if !exited {
+            let _ = child.kill();
+        }
+        emit_log(app, "engine", &outcome);
+        let exit_code = child.wait().ok().and_then(|status| status.code());
+        if let Ok(mut guard) = state.0.lock() {
+            guard.started_at = None;
+            guard.ready = false;
+            guard.last_exit_code = exit_code;
         }
     }
+    engine_ready_seq().fetch_add(1, Ordering::SeqCst);
 
-    let dev_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("python_embedded");
-    if dev_path.exists() {
-        return Some(dev_path);
+    reset_engine_activity();
+    overlay_hover_requested_flag().store(false, Ordering::SeqCst);
+    let _ = emit_status(app);
+    reconcile_overlay(app);
+    if let Err(err) = set_ducking_in_order(app, false) {
+        emit_log(
+            app,
+            "audio",
+            &format!("failed to restore audio mute state: {err}"),
+        );
     }
-
-    None
+    Ok(())
 }
 
-fn emit_status(app: &AppHandle, running: bool) {
-    let _ = app.emit("stt:status", SttStatus { running });
+#[tauri::command]
+fn stt_get_config(state: State<'_, AppState>) -> Result<SttConfig, String> {
+    let guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+    Ok(guard.config.clone())
 }
 
-fn emit_log(app: &AppHandle, stream: &str, line: &str) {
-    let _ = app.emit(
-        "stt:log",
-        LogEvent {
-            stream: stream.to_string(),
-            line: line.to_string(),
-        },
-    );
+#[tauri::command]
+fn stt_set_config(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    config: SttConfig,
+) -> Result<(), String> {
+    store_config(&app, &state, config)
 }
 
-fn emit_transcript(app: &AppHandle, text: &str) {
-    let _ = app.emit(
-        "stt:transcript",
-        TranscriptEvent {
-            text: text.to_string(),
-        },
-    );
+/// Save `config` and restart the engine only if a launch setting changed; restarts
+/// are debounced so rapid edits cause one
+#[tauri::command]
+fn stt_apply_config(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    config: SttConfig,
+) -> Result<(), String> {
+    store_config(&app, &state, config.clone())?;
+    sync_engine_restart(&app, &state, &config)
 }
 
-fn log_to_file(message: &str) {
-    let log_path = dev_workspace_root().join("jargon_engine.log");
-    if let Some(parent) = log_path.parent() {
-        let _ = std::fs::create_dir_all(parent);
+/// Re-read the saved config file, e.g. after editing it by hand, and apply it like
+/// `stt_apply_config`; returns the loaded config
+#[tauri::command]
+fn stt_reload_config(app: AppHandle, state: State<'_, AppState>) -> Result<SttConfig, String> {
+    let path = config_path(&app).ok_or("Config directory unavailable")?;
+    if !path.exists() {
+        return Err(format!("No saved config at {}", path.display()));
     }
-    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(log_path) {
-        let _ = writeln!(file, "{}", message);
+    let config = read_config_file(&path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    store_config(&app, &state, config.clone())?;
+    {
+        // The file is the source of truth now; don't write it straight back
+        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        take_dirty_config(&mut guard, None);
     }
+    sync_engine_restart(&app, &state, &config)?;
+    Ok(config)
 }
 
-fn spawn_reader_thread<R: std::io::Read + Send + 'static>(
-    app: AppHandle,
-    stream_name: &'static str,
-    reader: R,
-) {
-    std::thread::spawn(move || {
-        let buf = BufReader::new(reader);
-        for line in buf.lines().flatten() {
-            log_to_file(&format!("[python:{stream_name}] {line}"));
-            if let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) {
-                if value.get("type").and_then(|v| v.as_str()) == Some("overlay") {
-                    if let Some(hover) = value.get("hover").and_then(|v| v.as_bool()) {
-                        if hover {
-                            let _ = set_overlay_visibility(&app, true);
-                            hover_dwell_seq().fetch_add(1, Ordering::SeqCst);
-                            let _ = crate::native_overlay::set_hover(true);
-                        } else {
-                            // Dwell for 30ms before collapsing; cancel if another event arrives
-                            let seq = hover_dwell_seq().fetch_add(1, Ordering::SeqCst) + 1;
-                            std::thread::spawn(move || {
-                                std::thread::sleep(std::time::Duration::from_millis(30));
-                                if hover_dwell_seq().load(Ordering::SeqCst) == seq {
-                                    let _ = crate::native_overlay::set_hover(false);
-                                }
-                            });
-                        }
-                        continue;
-                    }
-                } else if value.get("type").and_then(|v| v.as_str()) == Some("dictation_start") {
-                    // Emit event first so the frontend can play the sound effect
-                    emit_dictation_start(&app);
-                    // Pause any playing media
-                    if let Err(err) = system_audio::set_music_muted(true) {
-                        emit_log(&app, "audio", &format!("failed to pause media: {err}"));
-                    }
-                    continue;
-                } else if value.get("type").and_then(|v| v.as_str()) == Some("dictation_stop") {
-                    if let Err(err) = system_audio::set_music_muted(false) {
-                        emit_log(
-                            &app,
-                            "audio",
-                            &format!("failed to restore audio mute state: {err}"),
-                        );
-                    }
-                    emit_dictation_stop(&app);
-                    continue;
-                } else if value.get("type").and_then(|v| v.as_str()) == Some("overlay_level") {
-                    if let Some(level) = value.get("level").and_then(|v| v.as_f64()) {
-                        let _ = crate::native_overlay::set_level(level as f32);
-                        continue;
-                    }
-                } else if value.get("type").and_then(|v| v.as_str()) == Some("transcript") {
-                    if let Some(text) = value.get("text").and_then(|v| v.as_str()) {
-                        emit_transcript(&app, text);
-                        continue;
-                    }
-                }
-            }
+/// Schedule a restart if the running engine was launched differently from `config`,
+/// otherwise cancel any restart an earlier edit scheduled
+fn sync_engine_restart(
+    app: &AppHandle,
+    state: &AppState,
+    config: &SttConfig,
+) -> Result<(), String> {
+    let restart = {
+        let guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        launch_config_changed(guard.launch_config.as_ref(), config)
+    };
+    if restart {
+        schedule_engine_restart(app, state);
+    } else {
+        config_restart_seq().fetch_add(1, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// True when an engine launched with `running` needs a restart to pick up `config`;
+/// never before the first spawn
+fn launch_config_changed(running: Option<&SttConfig>, config: &SttConfig) -> bool {
+    running.is_some_and(|running| config_requires_restart(running, config))
+}
 
-            emit_log(&app, stream_name, &line);
+/// Restart a running engine once config edits stop arriving for
+/// `CONFIG_RESTART_DEBOUNCE_MS`
+fn schedule_engine_restart(app: &AppHandle, state: &AppState) {
+    let seq = config_restart_seq().fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    let state = state.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(CONFIG_RESTART_DEBOUNCE_MS));
+        if config_restart_seq().load(Ordering::SeqCst) != seq {
+            return;
+        }
+        let running = state.0.lock().map(|g| g.child.is_some()).unwrap_or(false);
+        if !running {
+            return;
+        }
+        emit_log(&app, "engine", "restarting engine to apply new settings");
+        if let Err(err) =
+            stop_engine_inner(&app, &state).and_then(|()| start_engine_inner(&app, &state))
+        {
+            emit_log(&app, "engine", &format!("restart failed: {err}"));
+            emit_error(&app, start_error_kind(&app), &err);
         }
     });
 }
 
-fn start_engine_inner(app: &AppHandle, state: &AppState) -> Result<(), String> {
-    let config = {
-        let guard = state.0.lock().map_err(|_| "State lock poisoned")?;
-        if guard.child.is_some() {
-            emit_status(app, true);
-            return Ok(());
+/// Validate and store `config`, apply its live settings and schedule a debounced save
+fn store_config(app: &AppHandle, state: &AppState, config: SttConfig) -> Result<(), String> {
+    validate_audio_format(config.sample_rate, config.channels)?;
+    validate_python_path(config.python_path.as_deref())?;
+    validate_engine_env(&config.engine_env)?;
+    validate_language(
+        config.language.as_deref(),
+        active_engine_def(&config).is_some(),
+    )?;
+    validate_model_name(config.model_name.as_deref())?;
+    hotkey::parse_hotkey(&config.hotkey)?;
+    if let Some(cancel) = &config.cancel_hotkey {
+        hotkey::parse_hotkey(cancel)?;
+    }
+    for hotkey in [&config.commit_hotkey, &config.discard_hotkey]
+        .into_iter()
+        .chain(&config.toggle_engine_hotkey)
+    {
+        hotkey
+            .parse::<Shortcut>()
+            .map_err(|e| format!("Invalid hotkey {hotkey}: {e}"))?;
+    }
+    let overlay_enabled = config.overlay_enabled;
+    let generation = {
+        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        if !config.keep_history {
+            // Turning history off drops what was already kept, not just future entries
+            guard.transcript_history.clear();
+            set_copy_transcript_enabled(false);
         }
-        guard.config.clone()
+        guard.config = config;
+        mark_config_dirty(&mut guard)
     };
-
-    let script_path = resolve_script_path(app);
-    log_to_file(&format!("[setup] resolved Python script path: {}", script_path.display()));
-    eprintln!(
-        "[setup] resolved Python script path: {}",
-        script_path.display()
-    );
-    if !script_path.exists() {
-        let msg = format!("Python script not found at {}", script_path.display());
-        log_to_file(&format!("[error] {msg}"));
-        return Err(msg);
+    sync_shortcuts(app);
+    sync_control_server(app);
+    let _ = configure_overlay(app);
+    if let Some(item) = OVERLAY_TRAY_ITEM.get() {
+        let _ = item.set_text(overlay_tray_label(overlay_enabled));
     }
+    reconcile_overlay(app);
+    schedule_config_save(app, state, generation);
+    Ok(())
+}
 
-    let model_dir = resolve_model_dir(app);
-    let python_dir = script_path
-        .parent()
-        .map(|p| p.to_path_buf())
-        .unwrap_or_else(|| dev_workspace_root().join("python"));
-    log_to_file(&format!("[setup] python cwd: {}", python_dir.display()));
-    log_to_file(&format!("[setup] model dir: {}", model_dir.display()));
+#[tauri::command]
+fn stt_get_status(app: AppHandle) -> Result<SttStatus, String> {
+    emit_status(&app)
+}
 
-    // Build common args: run unbuffered for immediate stdout
-    let mut args: Vec<std::ffi::OsString> = Vec::new();
-    args.push("-u".into());
+#[tauri::command]
+fn stt_start(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    start_engine_inner(&app, &state)
+}
 
-    // On Windows prefer embedded python; fallback to pyw/pythonw/python
-    #[cfg(windows)]
-    let mut child = {
-        let embedded_child = if let Some(embedded_dir) = resolve_embedded_python_dir(app) {
-            let pythonw = embedded_dir.join("pythonw.exe");
-            if pythonw.exists() {
-                let mut command = Command::new(&pythonw);
-                eprintln!("[engine] spawn cwd: {}", python_dir.display());
-                
-                // For embedded, pass the script path directly to avoid ._pth ignoring PYTHONPATH
-                let mut embedded_args = args.clone();
-                embedded_args.push(script_path.clone().into());
-                embedded_args.push("--hotkey".into());
-                embedded_args.push(config.hotkey.clone().into());
-                embedded_args.push("--model-dir".into());
-                embedded_args.push(model_dir.as_os_str().to_owned());
-                embedded_args.push("--type-into-active-app".into());
-                embedded_args.push(if config.type_into_active_app {
-                    "true".into()
-                } else {
-                    "false".into()
-                });
+#[tauri::command]
+fn stt_stop(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    flush_config(&app, &state)?;
+    stop_engine_inner(&app, &state)
+}
 
-                eprintln!("[engine] spawn cmd: {:?} {:?}", pythonw, embedded_args);
-                log_to_file(&format!(
-                    "[engine] using embedded python at {}",
-                    pythonw.display()
-                ));
-                command
-                    .args(&embedded_args)
-                    .stdin(Stdio::null())
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .current_dir(python_dir.clone())
-                    .creation_flags(CREATE_NO_WINDOW)
-                    .env("PYTHONHOME", &embedded_dir)
-                    .env("PYTHONNOUSERSITE", "1");
-                
-                if let Ok(path) = std::env::var("PATH") {
-                    command.env("PATH", format!("{};{}", embedded_dir.display(), path));
-                } else {
-                    command.env("PATH", embedded_dir.display().to_string());
-                }
-                match command.spawn() {
-                    Ok(ch) => {
-                        eprintln!("[engine] started with embedded pythonw");
-                        log_to_file("[engine] started with embedded pythonw");
-                        Some(ch)
-                    }
-                    Err(err) => {
-                        log_to_file(&format!("[error] embedded python spawn failed: {err}"));
-                        None
-                    }
-                }
-            } else {
-                log_to_file("[warn] embedded pythonw.exe not found; falling back");
-                None
+/// Start or stop the engine in one call; returns the status after the switch
+#[tauri::command]
+fn stt_toggle(app: AppHandle, state: State<'_, AppState>) -> Result<SttStatus, String> {
+    toggle_engine(&app, &state)
+}
+
+#[tauri::command]
+fn stt_restart(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    stop_engine_inner(&app, &state)?;
+    start_engine_inner(&app, &state)?;
+    Ok(())
+}
+
+#[tauri::command]
+fn stt_list_engines(state: State<'_, AppState>) -> Result<EngineList, String> {
+    let guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+    Ok(EngineList {
+        engines: guard.config.engines.clone(),
+        active: guard.config.active_engine.clone(),
+    })
+}
+
+/// Stop the current engine and start `name` (or the bundled engine when `None`),
+/// saving the choice for the next launch
+#[tauri::command]
+fn stt_switch_engine(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    name: Option<String>,
+) -> Result<(), String> {
+    let generation = {
+        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        if let Some(name) = name.as_deref() {
+            if !guard.config.engines.iter().any(|def| def.name == name) {
+                return Err(format!("Unknown engine: {name}"));
             }
-        } else {
-            None
-        };
+        }
+        guard.config.active_engine = name;
+        mark_config_dirty(&mut guard)
+    };
+    schedule_config_save(&app, &state, generation);
+    stop_engine_inner(&app, &state)?;
+    start_engine_inner(&app, &state)
+}
+
+#[tauri::command]
+fn stt_prewarm(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    spawn_engine(&app, &state, false)
+}
+
+#[tauri::command]
+fn stt_arm(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    state.write_engine_command(&serde_json::json!({ "type": "arm" }))?;
+    schedule_ready_timeout(&app, &state);
+    Ok(())
+}
+
+/// Verbose: mirrors every engine line as `stt:raw` before any parsing/routing
+#[tauri::command]
+fn stt_tap_raw_stream(enabled: bool) -> Result<(), String> {
+    raw_tap_enabled_flag().store(enabled, Ordering::SeqCst);
+    Ok(())
+}
+
+#[tauri::command]
+fn stt_compatibility(state: State<'_, AppState>) -> Result<CompatReport, String> {
+    let engine_protocol = state
+        .0
+        .lock()
+        .map_err(|_| "State lock poisoned")?
+        .engine_protocol;
+    Ok(compat_report(ENGINE_PROTOCOL_VERSION, engine_protocol))
+}
 
-        if let Some(ch) = embedded_child {
-            ch
-        } else {
-        // Fallback to system python using -m main
-        let mut py_args = args.clone();
-        py_args.push("-m".into());
-        py_args.push("main".into());
-        py_args.push("--hotkey".into());
-        py_args.push(config.hotkey.clone().into());
-        py_args.push("--model-dir".into());
-        py_args.push(model_dir.as_os_str().to_owned());
-        py_args.push("--type-into-active-app".into());
-        py_args.push(if config.type_into_active_app {
-            "true".into()
-        } else {
-            "false".into()
-        });
+/// Leave nothing behind before the updater replaces the binary: write any pending config
+/// change, stop the engine, restore ducked audio, hide the overlay and write the window
+/// placement to disk. Returns once all of it is persisted. Logs are appended
+/// line-by-line, so there is nothing buffered to flush there.
+#[tauri::command]
+fn stt_prepare_for_update(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    // A debounced save still waiting would be lost when the updater kills the process
+    flush_config(&app, &state)?;
+    stop_engine_inner(&app, &state)?;
 
-        let mut pyw_cmd = Command::new("pyw");
-        let mut pyw_args = Vec::with_capacity(py_args.len() + 1);
-        pyw_args.push("-3".into());
-        pyw_args.extend(py_args.iter().cloned());
-        eprintln!("[engine] spawn cwd: {}", python_dir.display());
-        eprintln!("[engine] spawn cmd: pyw {:?}", pyw_args);
-        pyw_cmd
-            .args(&pyw_args)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(python_dir.clone())
-            .creation_flags(CREATE_NO_WINDOW);
-        match pyw_cmd.spawn() {
-            Ok(ch) => {
-                eprintln!("[engine] started with 'pyw -3 -m main'");
-                log_to_file("[engine] started with 'pyw -3 -m main'");
-                ch
-            }
-            Err(pyw_err) => {
-                log_to_file(&format!("[error] pyw spawn failed: {pyw_err}"));
-                let mut command = Command::new("pythonw");
-                eprintln!("[engine] fallback spawn cmd: pythonw {:?}", py_args);
-                command
-                    .args(&py_args)
-                    .stdin(Stdio::null())
-                    .stdout(Stdio::piped())
-                    .stderr(Stdio::piped())
-                    .current_dir(python_dir.clone())
-                    .creation_flags(CREATE_NO_WINDOW);
-                match command.spawn() {
-                    Ok(ch) => {
-                        eprintln!("[engine] started with 'pythonw -m main'");
-                        log_to_file("[engine] started with 'pythonw -m main'");
-                        ch
-                    }
-                    Err(py_err) => {
-                        log_to_file(&format!("[error] pythonw spawn failed: {py_err}"));
-                        let mut fallback = Command::new("python");
-                        fallback
-                            .args(&py_args)
-                            .stdin(Stdio::null())
-                            .stdout(Stdio::piped())
-                            .stderr(Stdio::piped())
-                            .current_dir(python_dir.clone())
-                            .creation_flags(CREATE_NO_WINDOW);
-                        match fallback.spawn() {
-                            Ok(ch) => {
-                                eprintln!("[engine] started with 'python -m main'");
-                                log_to_file("[engine] started with 'python -m main'");
-                                ch
-                            }
-                            Err(err) => {
-                                let msg = format!(
-                                    "Failed to start Python: pyw error: {pyw_err}; pythonw error: {py_err}; python error: {err}"
-                                );
-                                log_to_file(&format!("[error] {msg}"));
-                                return Err(msg);
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        }
-    };
+    // Suppress indefinitely so the reconcile poll doesn't bring the bar back
+    overlay_suppress_seq().fetch_add(1, Ordering::SeqCst);
+    overlay_suppressed_flag().store(true, Ordering::SeqCst);
+    native_overlay::stop_pulse();
+    reconcile_overlay(&app);
 
-    #[cfg(not(windows))]
-    let mut child = {
-        let mut command = Command::new("python");
-        eprintln!("[engine] spawn cwd: {}", python_dir.display());
-        eprintln!("[engine] spawn cmd: python {:?}", args);
-        command
-            .args(&args)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(python_dir.clone());
-        match command.spawn() {
-            Ok(ch) => ch,
-            Err(err) => return Err(format!("Failed to start Python: {err}")),
+    if current_config(&app).remember_window_geometry {
+        // Supersede any debounced save still in flight
+        window_geometry_seq().fetch_add(1, Ordering::SeqCst);
+        if let Some(geometry) = app
+            .get_webview_window("main")
+            .and_then(|window| window_geometry(&window))
+        {
+            save_window_geometry(&app, geometry)?;
         }
-    };
+    }
+    Ok(())
+}
 
-    if let Some(stdout) = child.stdout.take() {
-        spawn_reader_thread(app.clone(), "stdout", stdout);
+/// `read` applied to the locked state, or the poisoned-lock marker
+fn locked_section<T>(
+    guard: &std::sync::LockResult<MutexGuard<'_, InnerState>>,
+    read: impl FnOnce(&InnerState) -> T,
+) -> SnapshotSection<T> {
+    match guard {
+        Ok(guard) => SnapshotSection::Ok(read(guard)),
+        Err(_) => Err("State lock poisoned".to_string()).into(),
     }
-    if let Some(stderr) = child.stderr.take() {
-        spawn_reader_thread(app.clone(), "stderr", stderr);
+}
+
+fn state_snapshot(state: &AppState) -> StateSnapshot {
+    // One guard for every app-state section, so they all describe the same moment
+    let guard = state.0.lock();
+    let config = locked_section(&guard, |g| g.config.clone());
+    let status = locked_section(&guard, engine_status);
+    let stats = locked_section(&guard, |g| latency_stats(&g.transcript_history));
+    let transcripts = locked_section(&guard, |g| recent_transcripts(&g.transcript_history, None));
+    let engine = locked_section(&guard, |g| EngineSnapshot {
+        running: g.child.is_some(),
+        compat: compat_report(ENGINE_PROTOCOL_VERSION, g.engine_protocol),
+        dictation_active: dictation_active_flag().load(Ordering::SeqCst),
+        last_activity_ms: engine_last_activity_ms().load(Ordering::SeqCst),
+        stalled: engine_stalled_flag().load(Ordering::SeqCst),
+        last_spawn: g.last_spawn.clone(),
+        info: g.engine_info.clone(),
+    });
+    let audio_format = guard
+        .as_ref()
+        .map(|g| g.audio_format)
+        .map_err(|_| "State lock poisoned".to_string());
+    // Device queries can be slow; don't hold up the engine threads for them
+    drop(guard);
+
+    let recent_logs = recent_logs_storage()
+        .lock()
+        .map(|logs| recent_logs(&logs, None))
+        .map_err(|_| "Log buffer lock poisoned".to_string());
+    let audio = audio_format.map(|format| AudioSnapshot {
+        format,
+        output_device: system_audio::has_output_device(),
+        output_volume: system_audio::output_volume().into(),
+        awaiting_restore: system_audio::awaiting_restore(),
+    });
+    StateSnapshot {
+        taken_at_ms: now_millis(),
+        config,
+        status,
+        stats,
+        recent_logs: recent_logs.into(),
+        transcripts,
+        engine,
+        audio: audio.into(),
+        overlay: OverlaySnapshot {
+            backend: native_overlay::BACKEND.to_string(),
+            visible: overlay_visible_flag().load(Ordering::SeqCst),
+            hover_requested: overlay_hover_requested_flag().load(Ordering::SeqCst),
+            suppressed: overlay_suppressed_flag().load(Ordering::SeqCst),
+        },
     }
+}
 
+/// Aggregate config, status, stats, recent logs and transcripts, engine, audio and
+/// overlay state into one blob for issue reports.
+/// Never fails; sections that can't be read carry an error marker instead.
+#[tauri::command]
+fn stt_snapshot(state: State<'_, AppState>) -> StateSnapshot {
+    state_snapshot(&state)
+}
+
+/// Decode the engine's last captured utterance again with `overrides` applied,
+/// returning the new text without typing or routing it. Refused with `keep_history` off.
+#[tauri::command]
+async fn stt_retranscribe_last(
+    state: State<'_, AppState>,
+    overrides: Option<RetranscribeOverrides>,
+) -> Result<String, String> {
+    if !state
+        .0
+        .lock()
+        .map_err(|_| "State lock poisoned")?
+        .config
+        .keep_history
     {
-        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
-        guard.child = Some(child);
+        return Err("Transcript history is off".to_string());
     }
+    let state = state.inner().clone();
+    let cmd = serde_json::json!({
+        "type": "retranscribe",
+        "overrides": overrides.unwrap_or_default(),
+    });
+    tauri::async_runtime::spawn_blocking(move || {
+        let reply = state.request_engine(cmd, Duration::from_millis(ENGINE_REQUEST_TIMEOUT_MS))?;
+        reply_text(&reply)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    emit_status(app, true);
-
-    let app_for_monitor = app.clone();
-    let state_for_monitor = state.clone();
-    std::thread::spawn(move || loop {
-        let exit_status = {
-            let mut guard = match state_for_monitor.0.lock() {
-                Ok(g) => g,
-                Err(_) => return,
-            };
-            let Some(child) = guard.child.as_mut() else {
-                return;
-            };
+/// Record one utterance, stopping at a pause after speech or after `max_ms`, and return
+/// its transcript without typing or routing it; empty when nothing was said
+#[tauri::command]
+async fn stt_dictate_once(state: State<'_, AppState>, max_ms: u64) -> Result<String, String> {
+    if !(1..=DICTATE_ONCE_MAX_MS).contains(&max_ms) {
+        return Err(format!(
+            "maxMs must be between 1 and {DICTATE_ONCE_MAX_MS}, got {max_ms}"
+        ));
+    }
+    let state = state.inner().clone();
+    let cmd = serde_json::json!({ "type": "dictate_once", "maxMs": max_ms });
+    tauri::async_runtime::spawn_blocking(move || {
+        // Decoding only starts once recording ends, so it gets the usual allowance on top
+        let timeout = Duration::from_millis(max_ms + ENGINE_REQUEST_TIMEOUT_MS);
+        reply_text(&state.request_engine(cmd, timeout)?)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-            match child.try_wait() {
-                Ok(Some(status)) => Some(status),
-                Ok(None) => None,
-                Err(_) => Some(std::process::ExitStatus::from_raw(1)),
-            }
-        };
+fn reply_text(reply: &serde_json::Value) -> Result<String, String> {
+    reply
+        .get("text")
+        .and_then(|v| v.as_str())
+        .map(|text| text.to_string())
+        .ok_or_else(|| "Engine reply had no text".to_string())
+}
 
-        if let Some(status) = exit_status {
-            {
-                let mut guard = match state_for_monitor.0.lock() {
-                    Ok(g) => g,
-                    Err(_) => return,
-                };
-                guard.child = None;
-            }
-            emit_status(&app_for_monitor, false);
-            emit_log(
-                &app_for_monitor,
-                "engine",
-                &format!("python exited: {status}"),
-            );
-            if let Err(err) = system_audio::set_music_muted(false) {
-                emit_log(
-                    &app_for_monitor,
-                    "audio",
-                    &format!("failed to restore audio mute state: {err}"),
-                );
-            }
-            return;
-        }
+/// Whether Python and the engine's packages are installed, as a checklist for the UI
+#[tauri::command]
+async fn stt_doctor(app: AppHandle) -> Result<DoctorReport, String> {
+    tauri::async_runtime::spawn_blocking(move || run_doctor(&app))
+        .await
+        .map_err(|e| e.to_string())
+}
 
-        std::thread::sleep(Duration::from_millis(250));
-    });
+/// Language codes accepted for `language` by the bundled engine
+#[tauri::command]
+fn stt_list_languages() -> Vec<String> {
+    SUPPORTED_LANGUAGES
+        .iter()
+        .map(|code| code.to_string())
+        .collect()
+}
 
-    Ok(())
+/// Capture devices the engine can open, for the microphone picker
+#[tauri::command]
+async fn stt_list_audio_devices(app: AppHandle) -> Result<Vec<AudioDevice>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        parse_audio_devices(&run_engine_probe(&app, &["--list-devices"])?)
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
-fn stop_engine_inner(app: &AppHandle, state: &AppState) -> Result<(), String> {
-    let mut child = {
-        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
-        guard.child.take()
+/// Type `text` into the focused window, or the last transcript when `text` is empty
+#[tauri::command]
+async fn stt_type_text(state: State<'_, AppState>, text: String) -> Result<(), String> {
+    let text = if text.is_empty() {
+        state
+            .0
+            .lock()
+            .map_err(|_| "State lock poisoned")?
+            .last_transcript()
+            .ok_or("No transcript to type yet")?
+    } else {
+        text
     };
+    tauri::async_runtime::spawn_blocking(move || text_injection::send_unicode_text(&text))
+        .await
+        .map_err(|e| e.to_string())?
+}
 
-    if let Some(child) = child.as_mut() {
-        let _ = child.kill();
-        let _ = child.wait();
+/// `{"type": name}` with the fields of an object `payload` merged in
+fn engine_command(name: &str, payload: serde_json::Value) -> Result<serde_json::Value, String> {
+    if name.is_empty() {
+        return Err("Command name must not be empty".to_string());
     }
+    let mut cmd = match payload {
+        serde_json::Value::Null => serde_json::Map::new(),
+        serde_json::Value::Object(fields) => fields,
+        _ => return Err("Command payload must be a JSON object".to_string()),
+    };
+    cmd.insert("type".to_string(), name.into());
+    Ok(serde_json::Value::Object(cmd))
+}
 
-    emit_status(app, false);
-    if let Err(err) = system_audio::set_music_muted(false) {
-        emit_log(
-            app,
-            "audio",
-            &format!("failed to restore audio mute state: {err}"),
-        );
+/// Send a fire-and-forget command line to the running engine's stdin
+#[tauri::command]
+fn stt_send_command(
+    state: State<'_, AppState>,
+    name: String,
+    payload: serde_json::Value,
+) -> Result<(), String> {
+    state.write_engine_command(&engine_command(&name, payload)?)
+}
+
+/// Recent transcripts, newest first, for the history panel
+#[tauri::command]
+fn stt_get_transcripts(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<TranscriptEntry>, String> {
+    let guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+    Ok(recent_transcripts(&guard.transcript_history, limit))
+}
+
+/// Min/avg/max/p95 latency over the stored transcripts; `None` with `keep_history` off
+/// or before any transcript reported its capture time
+#[tauri::command]
+fn stt_get_latency_stats(state: State<'_, AppState>) -> Result<Option<LatencyStats>, String> {
+    let guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+    Ok(latency_stats(&guard.transcript_history))
+}
+
+/// Log lines emitted before the caller subscribed, oldest first; at most `RECENT_LOG_LIMIT`
+#[tauri::command]
+fn stt_get_recent_logs(limit: Option<usize>) -> Result<Vec<LogEvent>, String> {
+    let logs = recent_logs_storage()
+        .lock()
+        .map_err(|_| "Log buffer lock poisoned")?;
+    Ok(recent_logs(&logs, limit))
+}
+
+/// Forget the transcript history, along with the engine's copy of the last utterance, and
+/// tell open history views to refresh; history is only held in memory, so there is no
+/// file to remove. A no-op when `keep_history` is off.
+#[tauri::command]
+fn stt_clear_transcripts(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+    {
+        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        if !guard.config.keep_history {
+            return Ok(());
+        }
+        guard.transcript_history.clear();
     }
+    set_copy_transcript_enabled(false);
+    // Nothing to forget when the engine isn't running
+    let _ = state.write_engine_command(&serde_json::json!({ "type": "forget_last" }));
+    let _ = app.emit("stt:transcripts_cleared", ());
     Ok(())
 }
 
+/// Format the engine negotiated the last time it opened the microphone
 #[tauri::command]
-fn stt_get_config(state: State<'_, AppState>) -> Result<SttConfig, String> {
-    let guard = state.0.lock().map_err(|_| "State lock poisoned")?;
-    Ok(guard.config.clone())
+fn stt_get_audio_format(state: State<'_, AppState>) -> Result<Option<AudioFormat>, String> {
+    Ok(state
+        .0
+        .lock()
+        .map_err(|_| "State lock poisoned")?
+        .audio_format)
+}
+
+/// Mic, sample rate, model and compute device the running engine reported;
+/// `None` until it does
+#[tauri::command]
+fn stt_get_engine_info(state: State<'_, AppState>) -> Result<Option<EngineInfo>, String> {
+    Ok(state
+        .0
+        .lock()
+        .map_err(|_| "State lock poisoned")?
+        .engine_info
+        .clone())
 }
 
+/// Set capture hints for the next engine start; `None` clears a hint
 #[tauri::command]
-fn stt_set_config(state: State<'_, AppState>, config: SttConfig) -> Result<(), String> {
-    let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
-    guard.config = config;
+fn stt_set_audio_format(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    sample_rate: Option<u32>,
+    channels: Option<u16>,
+) -> Result<(), String> {
+    validate_audio_format(sample_rate, channels)?;
+    let generation = {
+        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        guard.config.sample_rate = sample_rate;
+        guard.config.channels = channels;
+        mark_config_dirty(&mut guard)
+    };
+    schedule_config_save(&app, &state, generation);
     Ok(())
 }
 
+/// Each command tried during the last engine launch, with its exact OS error
 #[tauri::command]
-fn stt_get_status(app: AppHandle, state: State<'_, AppState>) -> Result<SttStatus, String> {
-    let running = state
+fn stt_spawn_diagnostics(state: State<'_, AppState>) -> Result<SpawnDiagnostics, String> {
+    Ok(state
         .0
         .lock()
         .map_err(|_| "State lock poisoned")?
-        .child
-        .is_some();
-    emit_status(&app, running);
-    Ok(SttStatus { running })
+        .last_spawn
+        .clone())
 }
 
+/// Duck, hold for two seconds, then restore, independent of the engine
 #[tauri::command]
-fn stt_start(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
-    start_engine_inner(&app, &state)
+fn stt_test_duck(app: AppHandle) -> Result<(), String> {
+    if !system_audio::has_output_device() {
+        return Err("No audio output device available".to_string());
+    }
+    std::thread::spawn(move || {
+        if let Err(err) = set_ducking_in_order(&app, true) {
+            emit_log(&app, "audio", &format!("test duck failed: {err}"));
+            return;
+        }
+        std::thread::sleep(Duration::from_secs(2));
+        if let Err(err) = set_ducking_in_order(&app, false) {
+            emit_log(&app, "audio", &format!("test restore failed: {err}"));
+        }
+    });
+    Ok(())
 }
 
+/// Master volume that ducking operates on; `None` where it can't be read (non-Windows)
 #[tauri::command]
-fn stt_stop(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
-    stop_engine_inner(&app, &state)
+fn stt_get_output_volume() -> Result<Option<system_audio::OutputVolume>, String> {
+    system_audio::output_volume()
 }
 
+/// Switch the microphone and save it; an empty `id` means the system default input.
+/// A running engine is sent `{"type":"set_device","id":<string|null>}` and must use that
+/// device from its next recording on, then report it with `engine_info`; no restart.
 #[tauri::command]
-fn stt_restart(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
-    stop_engine_inner(&app, &state)?;
-    start_engine_inner(&app, &state)?;
+fn stt_set_mic_device(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    let device = Some(id.trim().to_string()).filter(|id| !id.is_empty());
+    let (generation, running) = {
+        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        guard.config.mic_device_id = device.clone();
+        (mark_config_dirty(&mut guard), guard.stdin.is_some())
+    };
+    schedule_config_save(&app, &state, generation);
+    if running {
+        state.write_engine_command(&serde_json::json!({ "type": "set_device", "id": device }))?;
+    }
+    Ok(())
+}
+
+/// Set the duck ratio and apply it immediately if audio is currently ducked
+#[tauri::command]
+fn stt_set_duck_level(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    ratio: f32,
+) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err(format!("Duck level {ratio} is outside 0.0..=1.0"));
+    }
+    let generation = {
+        let mut guard = state.0.lock().map_err(|_| "State lock poisoned")?;
+        guard.config.duck_ratio = ratio;
+        mark_config_dirty(&mut guard)
+    };
+    system_audio::set_duck_level(ratio)?;
+    schedule_config_save(&app, &state, generation);
     Ok(())
 }
 
@@ -715,13 +5237,180 @@ fn overlay_show(app: AppHandle, show: bool) -> Result<(), String> {
     set_overlay_visibility(&app, show)
 }
 
+/// Connected displays, in the order `overlay_monitor` indexes them
+#[tauri::command]
+fn stt_list_monitors(app: AppHandle) -> Result<Vec<MonitorInfo>, String> {
+    let monitors = app.available_monitors().map_err(|e| e.to_string())?;
+    Ok(monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| MonitorInfo {
+            index,
+            name: monitor.name().cloned(),
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+            scale_factor: monitor.scale_factor(),
+        })
+        .collect())
+}
+
+/// Where the engine script, model and interpreter working directory resolve to
+#[tauri::command]
+fn stt_get_paths(app: AppHandle) -> EnginePaths {
+    let config = current_config(&app);
+    let script_path = resolve_script_path(&app, &config);
+    let model_dir = resolve_model_dir(&app, &config);
+    let python_dir = engine_python_dir(&script_path);
+    EnginePaths {
+        script_path: script_path.display().to_string(),
+        script_exists: script_path.is_file(),
+        model_dir: model_dir.display().to_string(),
+        model_dir_exists: model_dir.is_dir(),
+        python_dir: python_dir.display().to_string(),
+        python_dir_exists: python_dir.is_dir(),
+    }
+}
+
+/// Whether the resolved model directory and its files exist
+#[tauri::command]
+fn stt_model_status(app: AppHandle) -> ModelStatus {
+    let config = current_config(&app);
+    model_status(
+        &resolve_model_dir(&app, &config),
+        active_engine_def(&config).is_some() || config.model_name.is_some(),
+    )
+}
+
+/// Open the resolved model directory in the file manager
+#[tauri::command]
+fn stt_open_model_dir(app: AppHandle) -> Result<(), String> {
+    let model_dir = resolve_model_dir(&app, &current_config(&app));
+    if !model_dir.is_dir() {
+        return Err(format!("Model folder not found at {}", model_dir.display()));
+    }
+    app.opener()
+        .open_path(model_dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
+/// Open the folder holding `jargon.log` and its rotated copies
+#[tauri::command]
+fn stt_open_log_dir(app: AppHandle) -> Result<(), String> {
+    let dir = match log_file::dir() {
+        Some(dir) => dir.to_path_buf(),
+        None => app.path().app_log_dir().map_err(|e| e.to_string())?,
+    };
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create log folder: {e}"))?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
+/// Reposition the overlay from the current anchor/offset settings
+#[tauri::command]
+fn overlay_reconfigure(app: AppHandle) -> Result<(), String> {
+    configure_overlay(&app)
+}
+
+/// Overlay implementation in use, so the UI can hide backend-specific options
+#[tauri::command]
+fn stt_overlay_backend() -> String {
+    native_overlay::BACKEND.to_string()
+}
+
+/// Hide the overlay for `secs` seconds, then restore it per the visibility mode.
+/// A later call replaces the pending restore; `secs == 0` lifts suppression now.
+#[tauri::command]
+fn stt_suppress_overlay(app: AppHandle, secs: u64) -> Result<(), String> {
+    let seq = overlay_suppress_seq().fetch_add(1, Ordering::SeqCst) + 1;
+    overlay_suppressed_flag().store(secs > 0, Ordering::SeqCst);
+    reconcile_overlay(&app);
+    if secs == 0 {
+        return Ok(());
+    }
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_secs(secs));
+        if overlay_suppress_seq().load(Ordering::SeqCst) == seq {
+            overlay_suppressed_flag().store(false, Ordering::SeqCst);
+            reconcile_overlay(&app);
+        }
+    });
+    Ok(())
+}
+
+/// Preview bar height at `elapsed_ms`: swings between 10% and 100% of `peak`
+fn preview_level(peak: f32, elapsed_ms: u64) -> f32 {
+    let phase = (elapsed_ms % OVERLAY_PREVIEW_PERIOD_MS) as f32 / OVERLAY_PREVIEW_PERIOD_MS as f32;
+    let swing = 0.5 - 0.5 * (phase * std::f32::consts::TAU).cos();
+    peak.clamp(0.0, 1.0) * (0.1 + 0.9 * swing)
+}
+
+/// Hold the overlay expanded with bars animating up to `level`, for tuning its look
+/// without dictating. Turning it off hands hover back to the real pointer.
+#[tauri::command]
+fn overlay_preview(app: AppHandle, active: bool, level: f32) -> Result<(), String> {
+    let seq = overlay_preview_seq().fetch_add(1, Ordering::SeqCst) + 1;
+    if !active {
+        if !dictation_active_flag().load(Ordering::SeqCst) {
+            overlay_hover_requested_flag().store(false, Ordering::SeqCst);
+            native_overlay::set_level(0.0)?;
+        }
+        native_overlay::set_hover(false)?;
+        reconcile_overlay(&app);
+        return Ok(());
+    }
+
+    overlay_hover_requested_flag().store(true, Ordering::SeqCst);
+    reconcile_overlay(&app);
+    native_overlay::set_hover(true)?;
+    let started = std::time::Instant::now();
+    std::thread::spawn(move || {
+        while overlay_preview_seq().load(Ordering::SeqCst) == seq {
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            let _ = native_overlay::set_level(preview_level(level, elapsed_ms));
+            std::thread::sleep(Duration::from_millis(OVERLAY_PREVIEW_FRAME_MS));
+        }
+    });
+    Ok(())
+}
+
+/// Show `text` in the expanded overlay, sized between the configured width bounds
+#[tauri::command]
+fn overlay_set_preview_text(text: Option<String>) -> Result<(), String> {
+    native_overlay::set_preview_text(text)
+}
+
 // Removed: wave activation command; overlay remains minimal
 
+/// Put the last final transcript on the clipboard; nothing to do before the first one
+fn copy_last_transcript(app: &AppHandle) {
+    let (config, text) = match app.state::<AppState>().0.lock() {
+        Ok(guard) => (guard.config.clone(), guard.last_transcript()),
+        Err(_) => return,
+    };
+    let Some(text) = text else {
+        return;
+    };
+    if let Err(err) = copy_transcript(&config, &text) {
+        emit_log(app, "clipboard", &err);
+    }
+}
+
 fn setup_tray(app: &tauri::App) -> Result<(), tauri::Error> {
     let show = MenuItemBuilder::with_id("show", "Show").build(app)?;
     let hide = MenuItemBuilder::with_id("hide", "Hide").build(app)?;
     let start = MenuItemBuilder::with_id("start", "Start").build(app)?;
     let stop = MenuItemBuilder::with_id("stop", "Stop").build(app)?;
+    let overlay_enabled = current_config(app.handle()).overlay_enabled;
+    let overlay =
+        MenuItemBuilder::with_id("overlay", overlay_tray_label(overlay_enabled)).build(app)?;
+    let _ = OVERLAY_TRAY_ITEM.set(overlay.clone());
+    let copy_transcript = MenuItemBuilder::with_id("copy_transcript", "Copy last transcript")
+        .enabled(false)
+        .build(app)?;
+    let _ = COPY_TRANSCRIPT_TRAY_ITEM.set(copy_transcript.clone());
     let quit = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
     let menu = MenuBuilder::new(app)
         .item(&show)
@@ -730,11 +5419,14 @@ fn setup_tray(app: &tauri::App) -> Result<(), tauri::Error> {
         .item(&start)
         .item(&stop)
         .separator()
+        .item(&overlay)
+        .item(&copy_transcript)
+        .separator()
         .item(&quit)
         .build()?;
 
-    let tray_icon = Image::from_bytes(include_bytes!("../icons/icon.png"))
-        .expect("failed to load tray icon");
+    let tray_icon =
+        Image::from_bytes(include_bytes!("../icons/icon.png")).expect("failed to load tray icon");
 
     TrayIconBuilder::new()
         .icon(tray_icon)
@@ -747,13 +5439,13 @@ fn setup_tray(app: &tauri::App) -> Result<(), tauri::Error> {
                         let _: tauri::Result<()> = window.show();
                         let _ = window.set_focus();
                     }
-                    let _ = set_overlay_visibility(app_handle, false);
+                    reconcile_overlay(app_handle);
                 }
                 "hide" => {
                     if let Some(window) = app_handle.get_webview_window("main") {
                         let _: tauri::Result<()> = window.hide();
                     }
-                    let _ = set_overlay_visibility(app_handle, true);
+                    reconcile_overlay(app_handle);
                 }
                 "start" => {
                     let state = app_handle.state::<AppState>();
@@ -763,6 +5455,12 @@ fn setup_tray(app: &tauri::App) -> Result<(), tauri::Error> {
                     let state = app_handle.state::<AppState>();
                     let _ = stop_engine_inner(app_handle, &state);
                 }
+                "overlay" => {
+                    let state = app_handle.state::<AppState>();
+                    let enabled = current_config(app_handle).overlay_enabled;
+                    let _ = set_overlay_enabled(app_handle, &state, !enabled);
+                }
+                "copy_transcript" => copy_last_transcript(app_handle),
                 "quit" => app_handle.exit(0),
                 _ => {}
             },
@@ -774,8 +5472,14 @@ fn setup_tray(app: &tauri::App) -> Result<(), tauri::Error> {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    // Best effort: don't leave the user's music quiet if we crash mid-dictation
+    let default_panic_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        system_audio::restore_now();
+        default_panic_hook(info);
+    }));
+
     tauri::Builder::default()
-        .manage(AppState::new())
         .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
             if let Some(window) = app.get_webview_window("main") {
                 let _: tauri::Result<()> = window.show();
@@ -783,10 +5487,42 @@ pub fn run() {
             }
         }))
         .plugin(tauri_plugin_opener::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(handle_shortcut)
+                .build(),
+        )
         .setup(|app| {
+            if let Ok(dir) = app.path().app_log_dir() {
+                log_file::init(dir);
+            }
+            app.manage(AppState::new(load_config(app.handle())));
+            if let Ok(dir) = app.path().app_config_dir() {
+                system_audio::init_sidecar(dir);
+            }
+            match system_audio::recover_stranded_duck() {
+                Ok(true) => emit_log(
+                    app.handle(),
+                    "audio",
+                    "restored volume left ducked by the previous session",
+                ),
+                Ok(false) => {}
+                Err(e) => emit_log(
+                    app.handle(),
+                    "audio",
+                    &format!("failed to restore a stranded duck: {e}"),
+                ),
+            }
+            if let Err(e) = system_audio::watch_default_device() {
+                emit_log(
+                    app.handle(),
+                    "audio",
+                    &format!("failed to watch for output device changes: {e}"),
+                );
+            }
             setup_tray(app)?;
 
-            #[cfg(not(windows))]
+            #[cfg(not(any(windows, target_os = "macos")))]
             {
                 let default_width = OVERLAY_WIDTH_PX as f64;
                 let default_height = OVERLAY_HEIGHT_PX as f64;
@@ -804,31 +5540,41 @@ pub fn run() {
                 .inner_size(default_width, default_height)
                 .min_inner_size(0.0, 0.0)
                 .build()?;
-
-                if let Ok(Some(monitor)) = app.primary_monitor() {
-                    let size = monitor.size();
-                    let position = monitor.position();
-                    let mut x = position.x as f64 + (size.width as f64 - default_width) / 2.0
-                        - OVERLAY_HORIZONTAL_OFFSET_PX as f64;
-                    if x < position.x as f64 {
-                        x = position.x as f64;
-                    }
-                    let y = position.y as f64 + OVERLAY_VERTICAL_MARGIN_PX as f64;
-                    let _ = overlay.set_position(LogicalPosition::new(x, y));
-                }
+                // Positioned by configure_overlay below
                 let _: tauri::Result<()> = overlay.hide();
             }
 
+            sync_shortcuts(app.handle());
+            sync_control_server(app.handle());
+
+            let (overlay_events, overlay_event_rx) = std::sync::mpsc::channel();
+            native_overlay::set_overlay_click_handler(overlay_events);
+            let handle_for_clicks = app.handle().clone();
+            std::thread::spawn(move || {
+                for event in overlay_event_rx {
+                    handle_overlay_event(&handle_for_clicks, event);
+                }
+            });
+
             let handle_for_overlay = app.handle().clone();
             let _ = configure_overlay(&handle_for_overlay);
-            let _ = set_overlay_visibility(&handle_for_overlay, false);
+            reconcile_overlay(&handle_for_overlay);
 
-            // Auto-start the Python engine on app launch
-            eprintln!("[setup] auto-starting Python engine...");
-            let state_for_engine = app.state::<AppState>();
+            // Auto-start the Python engine on app launch (disarmed when pre-warming)
             let handle_for_engine = app.handle().clone();
-            if let Err(e) = start_engine_inner(&handle_for_engine, &state_for_engine) {
-                eprintln!("[setup] failed to start Python engine: {}", e);
+            let launch_config = current_config(&handle_for_engine);
+            if launch_config.auto_start_engine {
+                eprintln!("[setup] auto-starting Python engine...");
+                log_to_file("[setup] auto-starting Python engine...");
+                let state_for_engine = app.state::<AppState>();
+                let armed = !launch_config.prewarm_on_start;
+                if let Err(e) = spawn_engine(&handle_for_engine, &state_for_engine, armed) {
+                    eprintln!("[setup] failed to start Python engine: {}", e);
+                    log_to_file(&format!("[setup] failed to start Python engine: {e}"));
+                    emit_error(&handle_for_engine, start_error_kind(&handle_for_engine), &e);
+                }
+            } else {
+                log_to_file("[setup] auto-start disabled; engine left stopped");
             }
 
             if let Some(window) = app.get_webview_window("main") {
@@ -840,8 +5586,12 @@ pub fn run() {
                 let overlay_event_handle = app.handle().clone();
                 let overlay_poll_handle = app.handle().clone();
 
-                window.on_window_event(move |event| {
-                    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                // The window starts hidden so it appears directly at its restored placement
+                restore_window_geometry(&window);
+                let _: tauri::Result<()> = window.show();
+
+                window.on_window_event(move |event| match event {
+                    tauri::WindowEvent::CloseRequested { api, .. } => {
                         let run_in_background = state
                             .0
                             .lock()
@@ -850,17 +5600,18 @@ pub fn run() {
                         if run_in_background {
                             api.prevent_close();
                             let _: tauri::Result<()> = window_for_event.hide();
-                            let _ = set_overlay_visibility(&overlay_event_handle, true);
+                            reconcile_overlay(&overlay_event_handle);
                         }
                     }
+                    tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => {
+                        schedule_window_geometry_save(&window_for_event);
+                    }
+                    _ => {}
                 });
 
-                // Keep overlay always visible regardless of window focus/visibility
-                let _main_handle = window.clone();
+                // Periodically reconcile so the overlay converges even if an event was missed
                 std::thread::spawn(move || loop {
-                    let show_overlay = true;
-
-                    let _ = set_overlay_visibility(&overlay_poll_handle, show_overlay);
+                    reconcile_overlay(&overlay_poll_handle);
 
                     std::thread::sleep(Duration::from_millis(250));
                 });
@@ -871,14 +5622,64 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             stt_get_config,
             stt_set_config,
+            stt_apply_config,
+            stt_reload_config,
             stt_get_status,
             stt_start,
             stt_stop,
+            stt_toggle,
             stt_restart,
+            stt_list_engines,
+            stt_switch_engine,
+            stt_prewarm,
+            stt_arm,
+            stt_compatibility,
+            stt_spawn_diagnostics,
+            stt_test_duck,
+            stt_set_duck_level,
+            stt_set_mic_device,
+            stt_get_output_volume,
+            stt_prepare_for_update,
+            stt_snapshot,
+            stt_retranscribe_last,
+            stt_dictate_once,
+            stt_get_audio_format,
+            stt_get_engine_info,
+            stt_list_audio_devices,
+            stt_list_languages,
+            stt_doctor,
+            stt_type_text,
+            stt_get_transcripts,
+            stt_get_latency_stats,
+            stt_get_recent_logs,
+            stt_clear_transcripts,
+            stt_send_command,
+            stt_open_log_dir,
+            stt_get_paths,
+            stt_model_status,
+            stt_open_model_dir,
+            stt_set_audio_format,
+            stt_tap_raw_stream,
             sound_get_enabled,
             sound_set_enabled,
-            overlay_show
+            overlay_show,
+            overlay_reconfigure,
+            stt_list_monitors,
+            stt_suppress_overlay,
+            stt_overlay_backend,
+            overlay_set_preview_text,
+            overlay_preview
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            tauri::RunEvent::ExitRequested { .. } => {
+                let _ = system_audio::set_music_muted(false, &duck_config(&current_config(app)));
+            }
+            tauri::RunEvent::Exit => {
+                let _ = flush_config(app, &app.state::<AppState>());
+                system_audio::restore_now();
+            }
+            _ => {}
+        });
 }
@@ -0,0 +1,75 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+const FILE_NAME: &str = "jargon.log";
+/// Size at which the current log is rotated out
+const MAX_BYTES: u64 = 5 * 1024 * 1024;
+/// Rotated files kept as `jargon.log.1` (newest) through `jargon.log.3`
+const KEEP_FILES: u32 = 3;
+
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+// Serializes appends so rotation never races a writer on another thread
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Set the directory logs go to; later calls are ignored
+pub fn init(dir: PathBuf) {
+    let _ = LOG_DIR.set(dir);
+}
+
+pub fn dir() -> Option<&'static Path> {
+    LOG_DIR.get().map(PathBuf::as_path)
+}
+
+fn rotated_path(dir: &Path, index: u32) -> PathBuf {
+    dir.join(format!("{FILE_NAME}.{index}"))
+}
+
+/// Shift `jargon.log` to `.1`, `.1` to `.2`, ..., dropping the oldest
+fn rotate(dir: &Path) {
+    let _ = std::fs::remove_file(rotated_path(dir, KEEP_FILES));
+    for index in (1..KEEP_FILES).rev() {
+        let _ = std::fs::rename(rotated_path(dir, index), rotated_path(dir, index + 1));
+    }
+    let _ = std::fs::rename(dir.join(FILE_NAME), rotated_path(dir, 1));
+}
+
+/// Append one line to `jargon.log` in `dir`, rotating first if it is over the size cap
+fn append_to(dir: &Path, line: &str) -> std::io::Result<()> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(FILE_NAME);
+    if std::fs::metadata(&path).is_ok_and(|meta| meta.len() >= MAX_BYTES) {
+        rotate(dir);
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{line}")
+}
+
+/// Append to the initialized log directory; a no-op before `init`
+pub fn append(line: &str) {
+    if let Some(dir) = dir() {
+        let _ = append_to(dir, line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_rotation_keeps_three_old_files() {
+        let dir = std::env::temp_dir().join(format!("jargon-log-test-{}", std::process::id()));
+        for generation in 0..5 {
+            append_to(&dir, &format!("generation {generation}")).unwrap();
+            rotate(&dir);
+        }
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).ok();
+        assert_eq!(read("jargon.log"), None);
+        assert_eq!(read("jargon.log.1").as_deref(), Some("generation 4\n"));
+        assert_eq!(read("jargon.log.3").as_deref(), Some("generation 2\n"));
+        assert_eq!(read("jargon.log.4"), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,29 +1,39 @@
+use std::sync::mpsc::Sender;
+use std::sync::OnceLock;
+
 #[cfg(windows)]
 mod platform {
     use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::{AtomicI32, AtomicU32};
+    use std::collections::VecDeque;
     use std::sync::{Mutex, OnceLock};
     use std::thread;
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
 
     use core::ffi::c_void;
 
+    use super::{OverlayColors, OverlayEvent};
+
     use windows::core::{w, Error, PCWSTR};
-    use windows::Win32::Foundation::{COLORREF, HWND, LPARAM, LRESULT, RECT, WPARAM};
+    use windows::Win32::Foundation::{COLORREF, HWND, LPARAM, LRESULT, RECT, SIZE, WPARAM};
     use windows::Win32::Graphics::Gdi::{
-        BeginPaint, CreateRoundRectRgn, CreateSolidBrush, DeleteObject, EndPaint, FillRect,
-        HRGN, PAINTSTRUCT,
+        BeginPaint, CreateRoundRectRgn, CreateSolidBrush, DeleteObject, DrawTextW, EndPaint, FillRect,
+        GetDC, GetStockObject, GetTextExtentPoint32W, ReleaseDC, SelectObject, SetBkMode, SetTextColor,
+        DEFAULT_GUI_FONT, DT_CENTER, DT_END_ELLIPSIS, DT_NOPREFIX, DT_SINGLELINE, DT_VCENTER, HDC, HRGN,
+        PAINTSTRUCT, TRANSPARENT, RedrawWindow, RDW_INVALIDATE, RDW_NOERASE,
     };
     use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
     use windows::Win32::UI::WindowsAndMessaging::{LoadCursorW, SetCursor, IDC_ARROW};
     use windows::Win32::UI::WindowsAndMessaging::{
-        self as winmsg, CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, RegisterClassW,
+        self as winmsg, CreateWindowExW, DefWindowProcW, DispatchMessageW, GetMessageW, KillTimer, RegisterClassW,
+        SetTimer,
         SetLayeredWindowAttributes, SetWindowPos, ShowWindow, TranslateMessage, MSG, WINDOW_EX_STYLE, WINDOW_STYLE,
         WNDCLASSW,
     };
 
     #[repr(C)]
-    #[allow(non_snake_case)]
+    #[allow(non_snake_case, clippy::upper_case_acronyms)]
     struct TRACKMOUSEEVENT {
         cbSize: u32,
         dwFlags: u32,
@@ -40,7 +50,6 @@ mod platform {
         fn TrackMouseEvent(lpEventTrack: *mut TRACKMOUSEEVENT) -> i32;
         fn InvalidateRect(hWnd: HWND, lpRect: *const RECT, bErase: i32) -> i32;
         fn SetWindowRgn(hWnd: HWND, hRgn: HRGN, bRedraw: i32) -> i32;
-        // No timer APIs needed
     }
 
     const CLASS_NAME: PCWSTR = w!("JargonNativeOverlayClass");
@@ -48,7 +57,21 @@ mod platform {
     const WINDOW_STYLE_FLAGS: WINDOW_STYLE = winmsg::WS_POPUP;
     const ANIMATION_STEPS: u32 = 8;
     const ANIMATION_FRAME_MS: u64 = 14;
-    const CORNER_RADIUS: i32 = 3;
+    // How far the collapsed level fill leans from the background toward the bar color
+    const COLLAPSED_LEVEL_TINT: f32 = 0.35;
+    // Show/hide fade: ~120ms in 8 alpha steps
+    const FADE_STEPS: u32 = 8;
+    const FADE_FRAME_MS: u64 = 15;
+    const TEXT_PADDING_X: i32 = 12;
+    const TEXT_PADDING_Y: i32 = 4;
+    /// Queued lines fade out over this last fraction of their display time
+    const QUEUE_FADE_FRACTION: f32 = 0.3;
+    // Level updates only mark the bars dirty; this timer repaints at a fixed rate so
+    // a burst of updates can't flood the pump with WM_PAINT and starve mouse messages
+    const LEVEL_REPAINT_TIMER_ID: usize = 1;
+    const LEVEL_REPAINT_MS: u32 = 33;
+    // Delays the collapse after WM_MOUSELEAVE so grazing the bar doesn't flicker it
+    const LEAVE_DWELL_TIMER_ID: usize = 2;
     // No wave/line animation constants; keep overlay minimal
     fn ensure_class_registered() -> Result<(), Error> {
         CLASS_REGISTERED
@@ -114,32 +137,141 @@ mod platform {
         }
     }
 
+    struct QueuedLine {
+        text: String,
+        width: i32,
+        added: Instant,
+    }
+
     struct OverlayMetrics {
         base: Geometry,
+        /// Hover target derived from the configured scale
+        scaled_expanded: Geometry,
+        /// Active hover target; widened to fit preview text when present
         expanded: Geometry,
         current: Geometry,
         hover: bool,
+        preview_text: Option<String>,
+        preview_text_width: i32,
+        /// Pending commit-mode text; kept apart from the preview so neither clobbers the other
+        commit_text: Option<String>,
+        commit_text_width: i32,
+        text_min_width: i32,
+        text_max_width: i32,
+        /// Recent transcript fragments, oldest first
+        transcript_queue: VecDeque<QueuedLine>,
+        queue_capacity: usize,
+        queue_display: Duration,
+        line_height: i32,
+        colors: OverlayColors,
+        bar_count: usize,
     }
 
     impl OverlayMetrics {
         fn new() -> Self {
             Self {
                 base: Geometry::default(),
+                scaled_expanded: Geometry::default(),
                 expanded: Geometry::default(),
                 current: Geometry::default(),
                 hover: false,
+                preview_text: None,
+                preview_text_width: 0,
+                commit_text: None,
+                commit_text_width: 0,
+                text_min_width: 1,
+                text_max_width: i32::MAX,
+                transcript_queue: VecDeque::new(),
+                queue_capacity: 0,
+                queue_display: Duration::ZERO,
+                line_height: 0,
+                colors: OverlayColors::default(),
+                bar_count: super::DEFAULT_BAR_COUNT,
             }
         }
+
+        fn text_geometry(&self, content_width: i32, height: i32) -> Geometry {
+            let width = super::clamp_text_width(
+                content_width + TEXT_PADDING_X * 2,
+                self.text_min_width,
+                self.text_max_width,
+            );
+            let center_x = self.base.x as f32 + self.base.width as f32 / 2.0;
+            Geometry::new(
+                (center_x - width as f32 / 2.0).round() as i32,
+                self.scaled_expanded.y,
+                width,
+                height,
+            )
+        }
+
+        /// Text to draw and its measured width; live preview text wins over the commit buffer
+        fn shown_text(&self) -> Option<(&str, i32)> {
+            self.preview_text
+                .as_deref()
+                .map(|text| (text, self.preview_text_width))
+                .or_else(|| self.commit_text.as_deref().map(|text| (text, self.commit_text_width)))
+        }
+
+        /// Text-bearing states size to the text within bounds; the level-bar state
+        /// keeps the scaled geometry
+        fn refresh_expanded(&mut self) {
+            self.expanded = if let Some((_, text_width)) = self.shown_text() {
+                self.text_geometry(text_width, self.scaled_expanded.height)
+            } else if !self.transcript_queue.is_empty() {
+                let widest = self.transcript_queue.iter().map(|line| line.width).max().unwrap_or(0);
+                let height = self.line_height * self.transcript_queue.len() as i32 + TEXT_PADDING_Y * 2;
+                self.text_geometry(widest, height.max(self.scaled_expanded.height))
+            } else {
+                self.scaled_expanded
+            };
+        }
+
+        /// Drop lines past their display time; true if the queue changed
+        fn prune_transcript_queue(&mut self, now: Instant) -> bool {
+            let before = self.transcript_queue.len();
+            let display = self.queue_display;
+            self.transcript_queue
+                .retain(|line| now.saturating_duration_since(line.added) < display);
+            self.transcript_queue.len() != before
+        }
+
+        /// Lines to draw with their gray level; older lines dim as they near expiry
+        fn queue_lines_for_paint(&self, now: Instant) -> Vec<(String, u8)> {
+            let display = self.queue_display.as_secs_f32().max(f32::EPSILON);
+            self.transcript_queue
+                .iter()
+                .map(|line| {
+                    let remaining = 1.0 - now.saturating_duration_since(line.added).as_secs_f32() / display;
+                    let alpha = (remaining / QUEUE_FADE_FRACTION).clamp(0.0, 1.0);
+                    (line.text.clone(), (alpha * 255.0).round() as u8)
+                })
+                .collect()
+        }
     }
 
     static OVERLAY_HWND: OnceLock<Mutex<Option<SharedHwnd>>> = OnceLock::new();
     static CLASS_REGISTERED: OnceLock<Result<(), Error>> = OnceLock::new();
     static METRICS: OnceLock<Mutex<OverlayMetrics>> = OnceLock::new();
     static ANIMATION_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+    // Separate from ANIMATION_SEQUENCE so a hover resize doesn't cut a fade short
+    static FADE_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+    static FADE_ALPHA: AtomicU32 = AtomicU32::new(255);
+    static EXCLUDE_FROM_CAPTURE: AtomicBool = AtomicBool::new(false);
+    static CORNER_RADIUS: AtomicI32 = AtomicI32::new(super::DEFAULT_CORNER_RADIUS);
     static LEVEL_MILLIS: AtomicU32 = AtomicU32::new(0);
     static LEVEL_TICK: AtomicU64 = AtomicU64::new(0);
+    static LEVEL_DIRTY: AtomicBool = AtomicBool::new(false);
     static FORCE_HOVER: AtomicBool = AtomicBool::new(false);
     static LAST_POINTER_INSIDE: AtomicBool = AtomicBool::new(false);
+    static LEAVE_DWELL_MS: AtomicU32 = AtomicU32::new(0);
+    static FLASH_ACTIVE: AtomicBool = AtomicBool::new(false);
+    static FLASH_RGB: AtomicU32 = AtomicU32::new(0);
+    static FLASH_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+    // The pulse has its own tint so a flash during it neither ends it nor is overwritten
+    static PULSE_LIT: AtomicBool = AtomicBool::new(false);
+    static PULSE_RGB: AtomicU32 = AtomicU32::new(0);
+    static PULSE_SEQUENCE: AtomicU64 = AtomicU64::new(0);
 
     fn storage() -> &'static Mutex<Option<SharedHwnd>> {
         OVERLAY_HWND.get_or_init(|| Mutex::new(None))
@@ -149,6 +281,14 @@ mod platform {
         METRICS.get_or_init(|| Mutex::new(OverlayMetrics::new()))
     }
 
+    /// Config colors are 0xRRGGBB; GDI wants 0x00BBGGRR
+    fn colorref_from_rgb(rgb: u32) -> COLORREF {
+        let r = (rgb >> 16) & 0xFF;
+        let g = (rgb >> 8) & 0xFF;
+        let b = rgb & 0xFF;
+        COLORREF(r | (g << 8) | (b << 16))
+    }
+
     fn decode_mouse_coords(l_param: LPARAM) -> (i32, i32) {
         let raw = l_param.0 as u32;
         let x = (raw & 0xFFFF) as u16 as i16 as i32;
@@ -169,29 +309,85 @@ mod platform {
             winmsg::WM_PAINT => {
                 let mut ps = PAINTSTRUCT::default();
                 let hdc = BeginPaint(hwnd, &mut ps);
-                let brush = CreateSolidBrush(COLORREF(0x000000));
-                let _ = FillRect(hdc, &RECT::from(ps.rcPaint), brush);
-                let _ = DeleteObject(brush.into());
 
-                let (hover, width, height) = {
+                let (hover, width, height, preview_text, queue_lines, line_height, colors, bar_count) = {
                     let guard = metrics_storage().lock().unwrap();
-                    (guard.hover, guard.current.width.max(1), guard.current.height.max(1))
+                    (
+                        guard.hover,
+                        guard.current.width.max(1),
+                        guard.current.height.max(1),
+                        guard.shown_text().map(|(text, _)| text.to_string()),
+                        guard.queue_lines_for_paint(Instant::now()),
+                        guard.line_height,
+                        guard.colors,
+                        guard.bar_count,
+                    )
                 };
 
-                if hover && height >= 12 {
+                let background = if FLASH_ACTIVE.load(Ordering::SeqCst) {
+                    colorref_from_rgb(FLASH_RGB.load(Ordering::Relaxed))
+                } else if PULSE_LIT.load(Ordering::SeqCst) {
+                    colorref_from_rgb(PULSE_RGB.load(Ordering::Relaxed))
+                } else {
+                    colorref_from_rgb(colors.background)
+                };
+                let brush = CreateSolidBrush(background);
+                let _ = FillRect(hdc, &ps.rcPaint, brush);
+                let _ = DeleteObject(brush.into());
+
+                if let (true, Some(text)) = (hover && height >= 12, preview_text.as_deref()) {
+                    draw_preview_text(hdc, width, height, text);
+                } else if hover && height >= 12 && !queue_lines.is_empty() {
+                    draw_transcript_queue(hdc, width, line_height, &queue_lines);
+                } else if hover && height >= 12 {
                     let level = (LEVEL_MILLIS.load(Ordering::Relaxed) as f32 / 1000.0)
                         .clamp(0.0, 1.0);
                     let tick = LEVEL_TICK.load(Ordering::Relaxed);
-                    draw_level_bars(hdc, width, height, level, tick);
+                    draw_level_bars(hdc, width, height, level, tick, colors.bar, bar_count);
+                } else if !hover {
+                    let level = (LEVEL_MILLIS.load(Ordering::Relaxed) as f32 / 1000.0)
+                        .clamp(0.0, 1.0);
+                    draw_collapsed_level(hdc, width, height, level, colors);
                 }
 
                 let _ = EndPaint(hwnd, &ps);
                 LRESULT(0)
             }
+            winmsg::WM_TIMER if _w_param.0 == LEVEL_REPAINT_TIMER_ID => {
+                let (hover, queue_changed, queue_active, expanded) = {
+                    let mut guard = metrics_storage().lock().unwrap();
+                    let changed = guard.prune_transcript_queue(Instant::now());
+                    if changed {
+                        guard.refresh_expanded();
+                    }
+                    (guard.hover, changed, !guard.transcript_queue.is_empty(), guard.expanded)
+                };
+                if queue_changed && hover {
+                    let _ = animate_to(expanded);
+                }
+                let level_dirty = LEVEL_DIRTY.swap(false, Ordering::Relaxed);
+                // Levels repaint in either state since the collapsed bar shows a fill too.
+                // Queued lines repaint every tick so their fade stays smooth.
+                if level_dirty || (hover && queue_active) {
+                    // The paint handler fills the whole client area, so skip the erase pass
+                    let _ = RedrawWindow(Some(hwnd), None, None, RDW_INVALIDATE | RDW_NOERASE);
+                }
+                LRESULT(0)
+            }
+            winmsg::WM_TIMER if _w_param.0 == LEAVE_DWELL_TIMER_ID => {
+                let _ = unsafe { KillTimer(Some(hwnd), LEAVE_DWELL_TIMER_ID) };
+                if !LAST_POINTER_INSIDE.load(Ordering::Relaxed) && !FORCE_HOVER.load(Ordering::Relaxed) {
+                    let _ = handle_hover_change(false);
+                }
+                LRESULT(0)
+            }
             winmsg::WM_MOUSEMOVE => {
                 let (x, y) = decode_mouse_coords(l_param);
                 let inside = pointer_inside_current(x, y);
                 LAST_POINTER_INSIDE.store(inside, Ordering::Relaxed);
+                if inside {
+                    let _ = unsafe { KillTimer(Some(hwnd), LEAVE_DWELL_TIMER_ID) };
+                }
                 if !FORCE_HOVER.load(Ordering::Relaxed) {
                     let _ = handle_hover_change(inside);
                 }
@@ -216,14 +412,35 @@ mod platform {
                     Err(_) => unsafe { DefWindowProcW(hwnd, msg, _w_param, l_param) },
                 }
             }
+            winmsg::WM_MOUSEACTIVATE => {
+                // Clicks must not pull focus away from the app being dictated into
+                LRESULT(winmsg::MA_NOACTIVATE as isize)
+            }
+            winmsg::WM_LBUTTONUP => {
+                super::notify(OverlayEvent::Clicked);
+                LRESULT(0)
+            }
+            winmsg::WM_SETTINGCHANGE => {
+                // lParam names the changed section; the light/dark switch sends "ImmersiveColorSet"
+                let section = PCWSTR(l_param.0 as *const u16);
+                if !section.is_null() && unsafe { section.to_string() }.is_ok_and(|s| s == "ImmersiveColorSet") {
+                    super::notify(OverlayEvent::ThemeChanged);
+                }
+                unsafe { DefWindowProcW(hwnd, msg, _w_param, l_param) }
+            }
             WM_MOUSELEAVE => {
                 LAST_POINTER_INSIDE.store(false, Ordering::Relaxed);
-                if !FORCE_HOVER.load(Ordering::Relaxed) {
+                let dwell_ms = LEAVE_DWELL_MS.load(Ordering::Relaxed);
+                if dwell_ms > 0 {
+                    let _ = unsafe { SetTimer(Some(hwnd), LEAVE_DWELL_TIMER_ID, dwell_ms, None) };
+                } else if !FORCE_HOVER.load(Ordering::Relaxed) {
                     let _ = handle_hover_change(false);
                 }
                 LRESULT(0)
             }
             winmsg::WM_DESTROY => {
+                let _ = unsafe { KillTimer(Some(hwnd), LEVEL_REPAINT_TIMER_ID) };
+                let _ = unsafe { KillTimer(Some(hwnd), LEAVE_DWELL_TIMER_ID) };
                 if let Some(mutex) = OVERLAY_HWND.get() {
                     let mut guard = mutex.lock().unwrap();
                     *guard = None;
@@ -273,6 +490,12 @@ mod platform {
                 // Configure layering and start hidden
                 let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), 255, winmsg::LWA_ALPHA);
                 let _ = ShowWindow(hwnd, winmsg::SW_HIDE);
+                if EXCLUDE_FROM_CAPTURE.load(Ordering::Relaxed) {
+                    if let Err(err) = apply_capture_exclusion(hwnd, true) {
+                        eprintln!("[overlay] capture exclusion unavailable: {err}");
+                    }
+                }
+                let _ = SetTimer(Some(hwnd), LEVEL_REPAINT_TIMER_ID, LEVEL_REPAINT_MS, None);
                 let _ = tx.send(hwnd.0 as isize);
 
                 // Message pump
@@ -302,8 +525,32 @@ mod platform {
         Ok(hwnd)
     }
 
-    fn draw_level_bars(hdc: windows::Win32::Graphics::Gdi::HDC, width: i32, height: i32, level: f32, tick: u64) {
-        let bar_count: i32 = 9;
+    /// Mix two 0xRRGGBB colors; `t` of 0 is `from`, 1 is `to`
+    fn blend_rgb(from: u32, to: u32, t: f32) -> u32 {
+        let channel = |shift: u32| {
+            let a = ((from >> shift) & 0xFF) as f32;
+            let b = ((to >> shift) & 0xFF) as f32;
+            ((a + (b - a) * t).round() as u32) << shift
+        };
+        channel(16) | channel(8) | channel(0)
+    }
+
+    /// Progress-style fill across the collapsed bar, in a faint tint of the bar color
+    fn draw_collapsed_level(hdc: HDC, width: i32, height: i32, level: f32, colors: OverlayColors) {
+        let fill_width = (width as f32 * level).round() as i32;
+        if fill_width <= 0 {
+            return;
+        }
+        let rect = RECT { left: 0, top: 0, right: fill_width.min(width), bottom: height };
+        unsafe {
+            let brush = CreateSolidBrush(colorref_from_rgb(blend_rgb(colors.background, colors.bar, COLLAPSED_LEVEL_TINT)));
+            let _ = FillRect(hdc, &rect, brush);
+            let _ = DeleteObject(brush.into());
+        }
+    }
+
+    fn draw_level_bars(hdc: windows::Win32::Graphics::Gdi::HDC, width: i32, height: i32, level: f32, tick: u64, bar_rgb: u32, bar_count: usize) {
+        let count = bar_count as i32;
         let gap: i32 = 2;
         let bar_width: i32 = 3;
         let padding_y: i32 = 3;
@@ -312,15 +559,14 @@ mod platform {
         let min_bar_height = 2.min(available_height);
         let max_bar_height = available_height.max(min_bar_height);
 
-        let total_width = bar_count * bar_width + (bar_count - 1) * gap;
+        let total_width = count * bar_width + (count - 1) * gap;
         let start_x = (((width - total_width) as f32) / 2.0).round() as i32;
         let center_y = (height as f32 / 2.0).round() as i32;
 
-        let weights: [f32; 9] = [0.35, 0.55, 0.75, 0.95, 1.0, 0.95, 0.75, 0.55, 0.35];
         let base_level = level.clamp(0.0, 1.0).powf(0.65);
-        let brush = unsafe { CreateSolidBrush(COLORREF(0x00FFFFFF)) };
-        for i in 0..bar_count {
-            let weight = weights.get(i as usize).copied().unwrap_or(1.0);
+        let brush = unsafe { CreateSolidBrush(colorref_from_rgb(bar_rgb)) };
+        for i in 0..count {
+            let weight = super::bar_weight(i as usize, bar_count);
             let phase = (tick as f32 * 0.22) + (i as f32 * 0.85);
             let wobble = 0.75 + 0.25 * phase.sin();
             let bar_level = (base_level * wobble * weight).clamp(0.0, 1.0);
@@ -341,6 +587,68 @@ mod platform {
         let _ = unsafe { DeleteObject(brush.into()) };
     }
 
+    /// (width, height) of `text` in the overlay font
+    fn measure_text(hwnd: HWND, text: &str) -> (i32, i32) {
+        let wide: Vec<u16> = text.encode_utf16().collect();
+        let mut size = SIZE::default();
+        unsafe {
+            let hdc = GetDC(Some(hwnd));
+            let previous = SelectObject(hdc, GetStockObject(DEFAULT_GUI_FONT));
+            let _ = GetTextExtentPoint32W(hdc, &wide, &mut size);
+            SelectObject(hdc, previous);
+            ReleaseDC(Some(hwnd), hdc);
+        }
+        (size.cx.max(0), size.cy.max(0))
+    }
+
+    fn draw_preview_text(hdc: HDC, width: i32, height: i32, text: &str) {
+        let mut wide: Vec<u16> = text.encode_utf16().collect();
+        let mut rect = RECT {
+            left: TEXT_PADDING_X,
+            top: 0,
+            right: (width - TEXT_PADDING_X).max(TEXT_PADDING_X),
+            bottom: height,
+        };
+        unsafe {
+            let previous = SelectObject(hdc, GetStockObject(DEFAULT_GUI_FONT));
+            SetBkMode(hdc, TRANSPARENT);
+            SetTextColor(hdc, COLORREF(0x00FFFFFF));
+            DrawTextW(
+                hdc,
+                &mut wide,
+                &mut rect,
+                DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_END_ELLIPSIS | DT_NOPREFIX,
+            );
+            SelectObject(hdc, previous);
+        }
+    }
+
+    fn draw_transcript_queue(hdc: HDC, width: i32, line_height: i32, lines: &[(String, u8)]) {
+        unsafe {
+            let previous = SelectObject(hdc, GetStockObject(DEFAULT_GUI_FONT));
+            SetBkMode(hdc, TRANSPARENT);
+            for (index, (text, gray)) in lines.iter().enumerate() {
+                let gray = *gray as u32;
+                SetTextColor(hdc, COLORREF(gray | (gray << 8) | (gray << 16)));
+                let top = TEXT_PADDING_Y + index as i32 * line_height;
+                let mut rect = RECT {
+                    left: TEXT_PADDING_X,
+                    top,
+                    right: (width - TEXT_PADDING_X).max(TEXT_PADDING_X),
+                    bottom: top + line_height,
+                };
+                let mut wide: Vec<u16> = text.encode_utf16().collect();
+                DrawTextW(
+                    hdc,
+                    &mut wide,
+                    &mut rect,
+                    DT_CENTER | DT_VCENTER | DT_SINGLELINE | DT_END_ELLIPSIS | DT_NOPREFIX,
+                );
+            }
+            SelectObject(hdc, previous);
+        }
+    }
+
     fn apply_geometry(hwnd: HWND, geom: Geometry) -> Result<(), Error> {
         let width = geom.width.max(1);
         let height = geom.height.max(1);
@@ -356,7 +664,8 @@ mod platform {
             )?;
 
             // Update rounded window region to maintain rounded borders on resize
-            let hrgn = CreateRoundRectRgn(0, 0, width, height, CORNER_RADIUS * 2, CORNER_RADIUS * 2);
+            let diameter = CORNER_RADIUS.load(Ordering::Relaxed) * 2;
+            let hrgn = CreateRoundRectRgn(0, 0, width, height, diameter, diameter);
             let _ = SetWindowRgn(hwnd, hrgn, 1);
 
             // Request a repaint after geometry changes
@@ -397,13 +706,204 @@ mod platform {
         let clamped = level.clamp(0.0, 1.0);
         LEVEL_MILLIS.store((clamped * 1000.0).round() as u32, Ordering::Relaxed);
         LEVEL_TICK.fetch_add(1, Ordering::Relaxed);
+        ensure_window()?;
+        LEVEL_DIRTY.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn set_bar_count_platform(count: usize) {
+        metrics_storage().lock().unwrap().bar_count = count;
+        LEVEL_DIRTY.store(true, Ordering::Relaxed);
+    }
+
+    /// WDA_EXCLUDEFROMCAPTURE needs Windows 10 2004 or later; older builds return an error
+    fn apply_capture_exclusion(hwnd: HWND, exclude: bool) -> Result<(), Error> {
+        let affinity = if exclude { winmsg::WDA_EXCLUDEFROMCAPTURE } else { winmsg::WDA_NONE };
+        unsafe { winmsg::SetWindowDisplayAffinity(hwnd, affinity) }
+    }
+
+    pub fn set_exclude_from_capture_platform(exclude: bool) -> Result<(), Error> {
+        let was = EXCLUDE_FROM_CAPTURE.swap(exclude, Ordering::Relaxed);
+        // Applied at window creation too; only a change needs the window now
+        if was == exclude {
+            return Ok(());
+        }
+        apply_capture_exclusion(ensure_window()?, exclude)
+    }
+
+    pub fn set_leave_dwell_platform(dwell_ms: u32) {
+        LEAVE_DWELL_MS.store(dwell_ms, Ordering::Relaxed);
+    }
+
+    /// Takes effect on the next `apply_geometry`, which `configure` always runs
+    pub fn set_corner_radius_platform(radius: i32) {
+        CORNER_RADIUS.store(radius, Ordering::Relaxed);
+    }
+
+    pub fn system_prefers_dark_platform() -> bool {
+        let mut light: u32 = 1;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let result = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                w!("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize"),
+                w!("AppsUseLightTheme"),
+                RRF_RT_REG_DWORD,
+                None,
+                Some(&mut light as *mut u32 as *mut c_void),
+                Some(&mut size),
+            )
+        };
+        // Missing before Windows 10 1809; those builds only have the light theme
+        result.is_ok() && light == 0
+    }
+
+    pub fn set_text_bounds_platform(min_width: i32, max_width: i32) {
+        let mut guard = metrics_storage().lock().unwrap();
+        guard.text_min_width = min_width;
+        guard.text_max_width = max_width;
+        guard.refresh_expanded();
+    }
+
+    pub fn set_preview_text_platform(text: Option<String>) -> Result<(), Error> {
+        set_text_platform(text, |metrics, text, width| {
+            metrics.preview_text = text;
+            metrics.preview_text_width = width;
+        })
+    }
+
+    pub fn set_commit_text_platform(text: Option<String>) -> Result<(), Error> {
+        set_text_platform(text, |metrics, text, width| {
+            metrics.commit_text = text;
+            metrics.commit_text_width = width;
+        })
+    }
+
+    /// Measure `text`, hand it to `store` and resize the expanded overlay to whatever is shown
+    fn set_text_platform(
+        text: Option<String>,
+        store: impl FnOnce(&mut OverlayMetrics, Option<String>, i32),
+    ) -> Result<(), Error> {
+        let hwnd = ensure_window()?;
+        let text = text.filter(|t| !t.trim().is_empty());
+        let text_width = text.as_deref().map(|t| measure_text(hwnd, t).0).unwrap_or(0);
+        let (target, hover) = {
+            let mut guard = metrics_storage().lock().unwrap();
+            store(&mut guard, text, text_width);
+            guard.refresh_expanded();
+            (guard.expanded, guard.hover)
+        };
+        unsafe {
+            let _ = InvalidateRect(hwnd, core::ptr::null(), 1);
+        }
+        if hover {
+            animate_to(target)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn set_transcript_queue_platform(lines: usize, display_ms: u64) {
+        let mut guard = metrics_storage().lock().unwrap();
+        guard.queue_capacity = lines;
+        guard.queue_display = Duration::from_millis(display_ms);
+        while guard.transcript_queue.len() > lines {
+            guard.transcript_queue.pop_front();
+        }
+        guard.refresh_expanded();
+    }
+
+    pub fn push_transcript_platform(text: &str) -> Result<(), Error> {
+        let text = text.trim();
+        if text.is_empty() || metrics_storage().lock().unwrap().queue_capacity == 0 {
+            return Ok(());
+        }
+        let hwnd = ensure_window()?;
+        let (width, height) = measure_text(hwnd, text);
+        let (target, hover) = {
+            let mut guard = metrics_storage().lock().unwrap();
+            guard.line_height = guard.line_height.max(height);
+            guard.transcript_queue.push_back(QueuedLine {
+                text: text.to_string(),
+                width,
+                added: Instant::now(),
+            });
+            while guard.transcript_queue.len() > guard.queue_capacity {
+                guard.transcript_queue.pop_front();
+            }
+            guard.refresh_expanded();
+            (guard.expanded, guard.hover)
+        };
+        unsafe {
+            let _ = InvalidateRect(hwnd, core::ptr::null(), 1);
+        }
+        if hover {
+            animate_to(target)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn flash_platform(rgb: u32, duration_ms: u64) -> Result<(), Error> {
         let hwnd = ensure_window()?;
+        let shared = SharedHwnd::new(hwnd);
+        FLASH_RGB.store(rgb & 0x00FF_FFFF, Ordering::Relaxed);
+        FLASH_ACTIVE.store(true, Ordering::SeqCst);
+        let sequence = FLASH_SEQUENCE.fetch_add(1, Ordering::SeqCst) + 1;
         unsafe {
             let _ = InvalidateRect(hwnd, core::ptr::null(), 1);
         }
+
+        // A newer flash takes over the timer; only the latest one clears the tint
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(duration_ms));
+            if FLASH_SEQUENCE.load(Ordering::SeqCst) == sequence {
+                FLASH_ACTIVE.store(false, Ordering::SeqCst);
+                unsafe {
+                    let _ = InvalidateRect(shared.hwnd(), core::ptr::null(), 1);
+                }
+            }
+        });
         Ok(())
     }
 
+    pub fn pulse_platform(rgb: u32, interval_ms: u64) -> Result<(), Error> {
+        let hwnd = ensure_window()?;
+        let shared = SharedHwnd::new(hwnd);
+        let sequence = PULSE_SEQUENCE.fetch_add(1, Ordering::SeqCst) + 1;
+        let interval = Duration::from_millis(interval_ms.max(50));
+        PULSE_RGB.store(rgb & 0x00FF_FFFF, Ordering::Relaxed);
+
+        // Alternate the tint on and off until a newer pulse or stop bumps the sequence
+        thread::spawn(move || {
+            let mut lit = false;
+            while PULSE_SEQUENCE.load(Ordering::SeqCst) == sequence {
+                lit = !lit;
+                PULSE_LIT.store(lit, Ordering::SeqCst);
+                // Stop may have landed between the check and the store; don't leave the tint on
+                if PULSE_SEQUENCE.load(Ordering::SeqCst) != sequence {
+                    PULSE_LIT.store(false, Ordering::SeqCst);
+                }
+                unsafe {
+                    let _ = InvalidateRect(shared.hwnd(), core::ptr::null(), 1);
+                }
+                thread::sleep(interval);
+            }
+        });
+        Ok(())
+    }
+
+    pub fn stop_pulse_platform() {
+        PULSE_SEQUENCE.fetch_add(1, Ordering::SeqCst);
+        PULSE_LIT.store(false, Ordering::SeqCst);
+        // Don't create the window just to clear a tint it never showed
+        if let Some(shared) = *storage().lock().unwrap() {
+            unsafe {
+                let _ = InvalidateRect(shared.hwnd(), core::ptr::null(), 1);
+            }
+        }
+    }
+
     fn animate_to(target: Geometry) -> Result<(), Error> {
         let hwnd = ensure_window()?;
         let shared = SharedHwnd::new(hwnd);
@@ -438,12 +938,12 @@ mod platform {
                 thread::sleep(Duration::from_millis(ANIMATION_FRAME_MS));
             }
 
-            if ANIMATION_SEQUENCE.load(Ordering::SeqCst) == sequence {
-                if apply_geometry(shared.hwnd(), target).is_ok() {
-                    let metrics = metrics_storage();
-                    let mut guard = metrics.lock().unwrap();
-                    guard.current = target;
-                }
+            if ANIMATION_SEQUENCE.load(Ordering::SeqCst) == sequence
+                && apply_geometry(shared.hwnd(), target).is_ok()
+            {
+                let metrics = metrics_storage();
+                let mut guard = metrics.lock().unwrap();
+                guard.current = target;
             }
         });
 
@@ -452,7 +952,7 @@ mod platform {
 
     // No wave-related functions; overlay remains minimal
 
-    pub fn configure(width: i32, height: i32, x: i32, y: i32, hover_scale_x: f32, hover_scale_y: f32) -> Result<(), Error> {
+    pub fn configure(width: i32, height: i32, x: i32, y: i32, hover_scale_x: f32, hover_scale_y: f32, colors: OverlayColors) -> Result<(), Error> {
         let hwnd = ensure_window()?;
 
         let scale_x = hover_scale_x.max(1.0);
@@ -474,8 +974,10 @@ mod platform {
             let metrics = metrics_storage();
             let mut guard = metrics.lock().unwrap();
             guard.base = base_geom;
-            guard.expanded = expanded_geom;
-            let target = if guard.hover { expanded_geom } else { base_geom };
+            guard.scaled_expanded = expanded_geom;
+            guard.colors = colors;
+            guard.refresh_expanded();
+            let target = if guard.hover { guard.expanded } else { base_geom };
             guard.current = target;
             target
         };
@@ -484,11 +986,45 @@ mod platform {
         apply_geometry(hwnd, target)
     }
 
+    /// Step the window alpha from where it is to `target`, hiding it afterwards when
+    /// `target` is 0; a newer show/hide supersedes the fade mid-way
+    fn fade_to(hwnd: HWND, target: u8) {
+        let sequence = FADE_SEQUENCE.fetch_add(1, Ordering::SeqCst) + 1;
+        let shared = SharedHwnd::new(hwnd);
+        let start = FADE_ALPHA.load(Ordering::SeqCst) as f32;
+
+        thread::spawn(move || {
+            for step in 1..=FADE_STEPS {
+                if FADE_SEQUENCE.load(Ordering::SeqCst) != sequence {
+                    return;
+                }
+                let t = step as f32 / FADE_STEPS as f32;
+                let alpha = (start + (target as f32 - start) * t).round() as u8;
+                FADE_ALPHA.store(alpha as u32, Ordering::SeqCst);
+                unsafe {
+                    let _ = SetLayeredWindowAttributes(shared.hwnd(), COLORREF(0), alpha, winmsg::LWA_ALPHA);
+                }
+                thread::sleep(Duration::from_millis(FADE_FRAME_MS));
+            }
+            if target == 0 && FADE_SEQUENCE.load(Ordering::SeqCst) == sequence {
+                unsafe {
+                    let _ = ShowWindow(shared.hwnd(), winmsg::SW_HIDE);
+                }
+            }
+        });
+    }
+
     pub fn show() -> Result<(), Error> {
         let hwnd = ensure_window()?;
         unsafe {
+            // Start from transparent unless interrupting a fade-out that is still on screen
+            if !winmsg::IsWindowVisible(hwnd).as_bool() {
+                FADE_ALPHA.store(0, Ordering::SeqCst);
+                let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), 0, winmsg::LWA_ALPHA);
+            }
             let _ = ShowWindow(hwnd, winmsg::SW_SHOWNA);
         }
+        fade_to(hwnd, 255);
         Ok(())
     }
 
@@ -502,32 +1038,393 @@ mod platform {
             guard.hover = false;
             guard.current = guard.base;
         }
-        unsafe {
-            let _ = ShowWindow(hwnd, winmsg::SW_HIDE);
-        }
+        fade_to(hwnd, 0);
         Ok(())
     }
 
 }
 
-#[cfg(not(windows))]
+#[cfg(target_os = "macos")]
 mod platform {
-    pub fn configure(_width: i32, _height: i32, _x: i32, _y: i32, _hover_scale_x: f32, _hover_scale_y: f32) -> Result<(), String> {
+    use std::cell::RefCell;
+    use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+    use std::sync::{Mutex, OnceLock};
+    use std::thread;
+    use std::time::Duration;
+
+    use dispatch2::DispatchQueue;
+    use objc2::rc::Retained;
+    use objc2::{MainThreadMarker, MainThreadOnly};
+    use objc2_app_kit::{
+        NSBackingStoreType, NSBox, NSBoxType, NSColor, NSScreen, NSStatusWindowLevel, NSTitlePosition, NSWindow,
+        NSWindowCollectionBehavior, NSWindowStyleMask,
+    };
+    use objc2_foundation::{NSPoint, NSRect, NSSize};
+
+    use super::OverlayColors;
+
+    const ANIMATION_STEPS: u32 = 8;
+    const ANIMATION_FRAME_MS: u64 = 14;
+
+    /// Top-left origin in points, like the Win32 module's pixels; flipped when applied
+    #[derive(Clone, Copy, Default, PartialEq, Eq)]
+    struct Geometry {
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    }
+
+    impl Geometry {
+        fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+            Self { x, y, width, height }
+        }
+
+        fn lerp(self, other: Geometry, t: f32) -> Self {
+            fn lerp_i32(start: i32, end: i32, t: f32) -> i32 {
+                (start as f32 + (end - start) as f32 * t).round() as i32
+            }
+
+            Geometry {
+                x: lerp_i32(self.x, other.x, t),
+                y: lerp_i32(self.y, other.y, t),
+                width: lerp_i32(self.width, other.width, t).max(1),
+                height: lerp_i32(self.height, other.height, t).max(1),
+            }
+        }
+    }
+
+    struct OverlayMetrics {
+        base: Geometry,
+        expanded: Geometry,
+        current: Geometry,
+        hover: bool,
+        colors: OverlayColors,
+        bar_count: usize,
+    }
+
+    impl Default for OverlayMetrics {
+        fn default() -> Self {
+            Self {
+                base: Geometry::default(),
+                expanded: Geometry::default(),
+                current: Geometry::default(),
+                hover: false,
+                colors: OverlayColors::default(),
+                bar_count: super::DEFAULT_BAR_COUNT,
+            }
+        }
+    }
+
+    /// AppKit objects are main-thread only, so they live in a main-thread local
+    struct OverlayViews {
+        window: Retained<NSWindow>,
+        background: Retained<NSBox>,
+        bars: Vec<Retained<NSBox>>,
+    }
+
+    thread_local! {
+        static VIEWS: RefCell<Option<OverlayViews>> = const { RefCell::new(None) };
+    }
+
+    static METRICS: OnceLock<Mutex<OverlayMetrics>> = OnceLock::new();
+    static ANIMATION_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+    static LEVEL_MILLIS: AtomicU32 = AtomicU32::new(0);
+    static LEVEL_TICK: AtomicU64 = AtomicU64::new(0);
+    static LEVEL_DIRTY: AtomicBool = AtomicBool::new(false);
+
+    fn metrics_storage() -> &'static Mutex<OverlayMetrics> {
+        METRICS.get_or_init(|| Mutex::new(OverlayMetrics::default()))
+    }
+
+    fn style_box(view: &NSBox, color: &NSColor, corner_radius: f64) {
+        view.setBoxType(NSBoxType::Custom);
+        view.setTitlePosition(NSTitlePosition::NoTitle);
+        view.setBorderWidth(0.0);
+        view.setCornerRadius(corner_radius);
+        view.setContentViewMargins(NSSize::new(0.0, 0.0));
+        view.setFillColor(color);
+    }
+
+    fn ns_color(rgb: u32) -> Retained<NSColor> {
+        let channel = |shift: u32| ((rgb >> shift) & 0xFF) as f64 / 255.0;
+        NSColor::colorWithSRGBRed_green_blue_alpha(channel(16), channel(8), channel(0), 1.0)
+    }
+
+    fn apply_colors(views: &OverlayViews, colors: OverlayColors) {
+        views.background.setFillColor(&ns_color(colors.background));
+        let bar_color = ns_color(colors.bar);
+        for bar in &views.bars {
+            bar.setFillColor(&bar_color);
+        }
+    }
+
+    fn create_views(mtm: MainThreadMarker) -> OverlayViews {
+        let frame = NSRect::new(NSPoint::new(0.0, 0.0), NSSize::new(1.0, 1.0));
+        let window = unsafe {
+            NSWindow::initWithContentRect_styleMask_backing_defer(
+                NSWindow::alloc(mtm),
+                frame,
+                NSWindowStyleMask::Borderless,
+                NSBackingStoreType::Buffered,
+                false,
+            )
+        };
+        unsafe { window.setReleasedWhenClosed(false) };
+        window.setOpaque(false);
+        window.setHasShadow(false);
+        window.setBackgroundColor(Some(&NSColor::clearColor()));
+        window.setLevel(NSStatusWindowLevel);
+        window.setIgnoresMouseEvents(true);
+        window.setCollectionBehavior(
+            NSWindowCollectionBehavior::CanJoinAllSpaces
+                | NSWindowCollectionBehavior::Stationary
+                | NSWindowCollectionBehavior::FullScreenAuxiliary,
+        );
+
+        let background = NSBox::initWithFrame(NSBox::alloc(mtm), frame);
+        style_box(&background, &NSColor::blackColor(), super::DEFAULT_CORNER_RADIUS as f64);
+        window.setContentView(Some(&background));
+
+        // Every possible bar exists up front; `draw_level_bars` hides the unused ones
+        let bars = (0..super::MAX_BAR_COUNT)
+            .map(|_| {
+                let bar = NSBox::initWithFrame(NSBox::alloc(mtm), frame);
+                style_box(&bar, &NSColor::whiteColor(), 0.0);
+                bar.setHidden(true);
+                background.addSubview(&bar);
+                bar
+            })
+            .collect();
+
+        OverlayViews { window, background, bars }
+    }
+
+    /// Run `f` against the overlay on the main thread, creating it on first use
+    fn with_views<F>(f: F)
+    where
+        F: FnOnce(&OverlayViews, MainThreadMarker) + Send + 'static,
+    {
+        DispatchQueue::main().exec_async(move || {
+            let Some(mtm) = MainThreadMarker::new() else {
+                return;
+            };
+            VIEWS.with(|cell| {
+                let mut slot = cell.borrow_mut();
+                let views = slot.get_or_insert_with(|| create_views(mtm));
+                f(views, mtm);
+            });
+        });
+    }
+
+    fn draw_level_bars(views: &OverlayViews, width: i32, height: i32, level: f32, tick: u64, visible: bool, bar_count: usize) {
+        let gap: i32 = 2;
+        let bar_width: i32 = 3;
+        let padding_y: i32 = 3;
+
+        let available_height = (height - padding_y * 2).max(1);
+        let min_bar_height = 2.min(available_height);
+        let max_bar_height = available_height.max(min_bar_height);
+
+        let count = bar_count as i32;
+        let total_width = count * bar_width + (count - 1) * gap;
+        let start_x = (((width - total_width) as f32) / 2.0).round() as i32;
+        let center_y = (height as f32 / 2.0).round() as i32;
+
+        let base_level = level.clamp(0.0, 1.0).powf(0.65);
+        for (i, bar) in views.bars.iter().enumerate() {
+            let shown = visible && i < bar_count;
+            bar.setHidden(!shown);
+            if !shown {
+                continue;
+            }
+            let phase = (tick as f32 * 0.22) + (i as f32 * 0.85);
+            let wobble = 0.75 + 0.25 * phase.sin();
+            let bar_level = (base_level * wobble * super::bar_weight(i, bar_count)).clamp(0.0, 1.0);
+            let h = (min_bar_height as f32
+                + (max_bar_height - min_bar_height) as f32 * bar_level)
+                .round() as i32;
+            let left = start_x + i as i32 * (bar_width + gap);
+            let top = (center_y - h / 2).max(0);
+            let bottom = (center_y + (h - h / 2)).min(height);
+            // NSView origins are bottom-left
+            bar.setFrame(NSRect::new(
+                NSPoint::new(left as f64, (height - bottom) as f64),
+                NSSize::new(bar_width as f64, (bottom - top) as f64),
+            ));
+        }
+    }
+
+    fn repaint_level_bars(views: &OverlayViews) {
+        let (hover, width, height, bar_count) = {
+            let guard = metrics_storage().lock().unwrap();
+            (guard.hover, guard.current.width.max(1), guard.current.height.max(1), guard.bar_count)
+        };
+        let level = (LEVEL_MILLIS.load(Ordering::Relaxed) as f32 / 1000.0).clamp(0.0, 1.0);
+        let tick = LEVEL_TICK.load(Ordering::Relaxed);
+        // Bars are only drawn while expanded, matching the Win32 overlay
+        draw_level_bars(views, width, height, level, tick, hover && height >= 12, bar_count);
+    }
+
+    fn apply_geometry(views: &OverlayViews, mtm: MainThreadMarker, geom: Geometry) {
+        let width = geom.width.max(1) as f64;
+        let height = geom.height.max(1) as f64;
+        // AppKit's global origin is the bottom-left of the primary screen
+        let primary_height = NSScreen::screens(mtm)
+            .firstObject()
+            .map(|screen| screen.frame().size.height)
+            .unwrap_or(height);
+        let frame = NSRect::new(
+            NSPoint::new(geom.x as f64, primary_height - geom.y as f64 - height),
+            NSSize::new(width, height),
+        );
+        views.window.setFrame_display(frame, true);
+        views.background.setFrame(NSRect::new(NSPoint::new(0.0, 0.0), frame.size));
+        repaint_level_bars(views);
+    }
+
+    fn handle_hover_change(hover: bool) {
+        let target = {
+            let mut guard = metrics_storage().lock().unwrap();
+            if guard.hover == hover {
+                return;
+            }
+            guard.hover = hover;
+            if hover {
+                guard.expanded
+            } else {
+                guard.base
+            }
+        };
+        animate_to(target);
+    }
+
+    pub fn set_hover_platform(active: bool) -> Result<(), String> {
+        handle_hover_change(active);
+        Ok(())
+    }
+
+    pub fn set_bar_count_platform(count: usize) {
+        metrics_storage().lock().unwrap().bar_count = count;
+        with_views(|views, _| repaint_level_bars(views));
+    }
+
+    pub fn set_corner_radius_platform(radius: i32) {
+        with_views(move |views, _| views.background.setCornerRadius(radius as f64));
+    }
+
+    pub fn system_prefers_dark_platform() -> bool {
+        false
+    }
+
+    pub fn set_level_platform(level: f32) -> Result<(), String> {
+        let clamped = level.clamp(0.0, 1.0);
+        LEVEL_MILLIS.store((clamped * 1000.0).round() as u32, Ordering::Relaxed);
+        LEVEL_TICK.fetch_add(1, Ordering::Relaxed);
+        // Coalesce bursts of levels into one main-thread repaint
+        if !LEVEL_DIRTY.swap(true, Ordering::Relaxed) {
+            with_views(|views, _| {
+                LEVEL_DIRTY.store(false, Ordering::Relaxed);
+                repaint_level_bars(views);
+            });
+        }
+        Ok(())
+    }
+
+    fn animate_to(target: Geometry) {
+        let start = metrics_storage().lock().unwrap().current;
+        if start == target {
+            return;
+        }
+
+        let sequence = ANIMATION_SEQUENCE.fetch_add(1, Ordering::SeqCst) + 1;
+
+        thread::spawn(move || {
+            let step_count = ANIMATION_STEPS.max(1);
+            for step in 1..=step_count {
+                if ANIMATION_SEQUENCE.load(Ordering::SeqCst) != sequence {
+                    return;
+                }
+
+                let t = step as f32 / step_count as f32;
+                let next = start.lerp(target, t);
+                metrics_storage().lock().unwrap().current = next;
+                with_views(move |views, mtm| apply_geometry(views, mtm, next));
+
+                thread::sleep(Duration::from_millis(ANIMATION_FRAME_MS));
+            }
+        });
+    }
+
+    pub fn configure(width: i32, height: i32, x: i32, y: i32, hover_scale_x: f32, hover_scale_y: f32, colors: OverlayColors) -> Result<(), String> {
+        let scale_x = hover_scale_x.max(1.0);
+        let scale_y = hover_scale_y.max(1.0);
+        let expanded_width = (((width as f32) * scale_x).round() as i32).max(width);
+        let expanded_height = (((height as f32) * scale_y).round() as i32).max(height);
+
+        let center_x = x as f32 + width as f32 / 2.0;
+        let center_y = y as f32 + height as f32 / 2.0;
+        let expanded_x = (center_x - expanded_width as f32 / 2.0).round() as i32;
+        let expanded_y = (center_y - expanded_height as f32 / 2.0).round() as i32;
+
+        let base_geom = Geometry::new(x, y, width, height);
+        let expanded_geom = Geometry::new(expanded_x, expanded_y, expanded_width, expanded_height);
+
+        let target = {
+            let mut guard = metrics_storage().lock().unwrap();
+            guard.base = base_geom;
+            guard.expanded = expanded_geom;
+            guard.colors = colors;
+            let target = if guard.hover { expanded_geom } else { base_geom };
+            guard.current = target;
+            target
+        };
+
+        ANIMATION_SEQUENCE.fetch_add(1, Ordering::SeqCst);
+        with_views(move |views, mtm| {
+            apply_colors(views, colors);
+            apply_geometry(views, mtm, target);
+        });
         Ok(())
     }
 
     pub fn show() -> Result<(), String> {
+        with_views(|views, _| views.window.orderFrontRegardless());
         Ok(())
     }
 
     pub fn hide() -> Result<(), String> {
+        ANIMATION_SEQUENCE.fetch_add(1, Ordering::SeqCst);
+        let base = {
+            let mut guard = metrics_storage().lock().unwrap();
+            guard.hover = false;
+            guard.current = guard.base;
+            guard.base
+        };
+        with_views(move |views, mtm| {
+            apply_geometry(views, mtm, base);
+            views.window.orderOut(None);
+        });
+        Ok(())
+    }
+}
+
+// Linux draws the overlay in a webview, so only the calls lib.rs makes on every platform
+// need stubs here; the geometry and styling API is Windows/macOS only
+#[cfg(not(any(windows, target_os = "macos")))]
+mod platform {
+    pub fn set_hover_platform(_active: bool) -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn set_level_platform(_level: f32) -> Result<(), String> {
         Ok(())
     }
 }
 
 #[cfg(windows)]
-pub fn configure(width: i32, height: i32, x: i32, y: i32, hover_scale_x: f32, hover_scale_y: f32) -> Result<(), String> {
-    platform::configure(width, height, x, y, hover_scale_x, hover_scale_y)
+pub fn configure(width: i32, height: i32, x: i32, y: i32, hover_scale_x: f32, hover_scale_y: f32, colors: OverlayColors) -> Result<(), String> {
+    platform::configure(width, height, x, y, hover_scale_x, hover_scale_y, colors)
         .map_err(|e: windows::core::Error| e.to_string())
 }
 
@@ -551,27 +1448,258 @@ pub fn set_level(level: f32) -> Result<(), String> {
     platform::set_level_platform(level).map_err(|e: windows::core::Error| e.to_string())
 }
 
-#[cfg(not(windows))]
-pub fn configure(width: i32, height: i32, x: i32, y: i32, hover_scale_x: f32, hover_scale_y: f32) -> Result<(), String> {
-    platform::configure(width, height, x, y, hover_scale_x, hover_scale_y)
+/// User interaction with the overlay, or a system change it reacts to, delivered to the
+/// handler from `set_overlay_click_handler`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub enum OverlayEvent {
+    Clicked,
+    /// The system switched between the light and dark app theme
+    ThemeChanged,
 }
 
-#[cfg(not(windows))]
+static EVENT_SENDER: OnceLock<Sender<OverlayEvent>> = OnceLock::new();
+
+/// Route overlay clicks to `sender`; only the first registration takes effect
+pub fn set_overlay_click_handler(sender: Sender<OverlayEvent>) {
+    let _ = EVENT_SENDER.set(sender);
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+fn notify(event: OverlayEvent) {
+    if let Some(sender) = EVENT_SENDER.get() {
+        let _ = sender.send(event);
+    }
+}
+
+/// Level bars drawn when the config doesn't say otherwise
+pub const DEFAULT_BAR_COUNT: usize = 9;
+/// Most level bars the overlay will draw
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+pub const MAX_BAR_COUNT: usize = 32;
+/// Corner radius of the overlay bar when the config doesn't say otherwise
+pub const DEFAULT_CORNER_RADIUS: i32 = 3;
+
+/// Height weight of bar `index` of `count`: a raised cosine from 0.35 at the ends to 1.0 mid-way
+#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+fn bar_weight(index: usize, count: usize) -> f32 {
+    let position = (index + 1) as f32 / (count + 1) as f32;
+    let window = 0.5 - 0.5 * (position * std::f32::consts::TAU).cos();
+    0.35 + 0.65 * window
+}
+
+/// Number of level bars in the expanded overlay, clamped to `1..=MAX_BAR_COUNT`
+#[cfg(any(windows, target_os = "macos"))]
+pub fn set_bar_count(count: usize) {
+    platform::set_bar_count_platform(count.clamp(1, MAX_BAR_COUNT))
+}
+
+/// Corner radius of the overlay bar in pixels (points on macOS); 0 is a plain rectangle
+#[cfg(any(windows, target_os = "macos"))]
+pub fn set_corner_radius(radius: i32) {
+    platform::set_corner_radius_platform(radius.max(0))
+}
+
+/// Whether the system app theme is dark; only read on Windows, false on macOS
+#[cfg(any(windows, target_os = "macos"))]
+pub fn system_prefers_dark() -> bool {
+    platform::system_prefers_dark_platform()
+}
+
+/// Background and level-bar fills as 0xRRGGBB
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverlayColors {
+    pub background: u32,
+    pub bar: u32,
+}
+
+impl OverlayColors {
+    /// Defaults that stand out against the theme: the black bar disappears on dark
+    /// wallpapers, so dark themes get a light one
+    #[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+    pub fn for_theme(dark: bool) -> Self {
+        if dark {
+            Self {
+                background: 0xE5E5E5,
+                bar: 0x1F1F1F,
+            }
+        } else {
+            Self::default()
+        }
+    }
+}
+
+impl Default for OverlayColors {
+    fn default() -> Self {
+        Self {
+            background: 0x000000,
+            bar: 0xFFFFFF,
+        }
+    }
+}
+
+/// Identifies which overlay implementation this build drives
+#[cfg(windows)]
+pub const BACKEND: &str = "win32-native";
+#[cfg(target_os = "macos")]
+pub const BACKEND: &str = "appkit-native";
+#[cfg(not(any(windows, target_os = "macos")))]
+pub const BACKEND: &str = "webview";
+
+/// Overlay width for a text-bearing state, clamped to the configured bounds
+#[cfg_attr(not(windows), allow(dead_code))]
+fn clamp_text_width(desired: i32, min_width: i32, max_width: i32) -> i32 {
+    let min_width = min_width.max(1);
+    desired.clamp(min_width, max_width.max(min_width))
+}
+
+/// Wait `dwell_ms` after the pointer leaves before collapsing; 0 collapses at once
+#[cfg(windows)]
+pub fn set_leave_dwell(dwell_ms: u64) {
+    platform::set_leave_dwell_platform(dwell_ms.min(u32::MAX as u64) as u32)
+}
+
+/// Hide the overlay from screen capture and recording (OBS, Teams, Snipping Tool)
+#[cfg(windows)]
+pub fn set_exclude_from_capture(exclude: bool) -> Result<(), String> {
+    platform::set_exclude_from_capture_platform(exclude).map_err(|e: windows::core::Error| e.to_string())
+}
+
+#[cfg(windows)]
+pub fn set_text_bounds(min_width: i32, max_width: i32) {
+    platform::set_text_bounds_platform(min_width, max_width)
+}
+
+#[cfg(windows)]
+pub fn set_preview_text(text: Option<String>) -> Result<(), String> {
+    platform::set_preview_text_platform(text).map_err(|e: windows::core::Error| e.to_string())
+}
+
+/// Show the commit-mode buffer, or `None` to clear it; live preview text stays on top
+#[cfg(windows)]
+pub fn set_commit_text(text: Option<String>) -> Result<(), String> {
+    platform::set_commit_text_platform(text).map_err(|e: windows::core::Error| e.to_string())
+}
+
+/// Blink the overlay in `rgb` every `interval_ms` until `stop_pulse`
+#[cfg(windows)]
+pub fn pulse(rgb: u32, interval_ms: u64) -> Result<(), String> {
+    platform::pulse_platform(rgb, interval_ms).map_err(|e: windows::core::Error| e.to_string())
+}
+
+#[cfg(windows)]
+pub fn stop_pulse() {
+    platform::stop_pulse_platform()
+}
+
+/// Keep the last `lines` transcripts in the expanded overlay for `display_ms` each
+#[cfg(windows)]
+pub fn set_transcript_queue(lines: usize, display_ms: u64) {
+    platform::set_transcript_queue_platform(lines, display_ms)
+}
+
+#[cfg(windows)]
+pub fn push_transcript(text: &str) -> Result<(), String> {
+    platform::push_transcript_platform(text).map_err(|e: windows::core::Error| e.to_string())
+}
+
+#[cfg(windows)]
+pub fn flash(rgb: u32, duration_ms: u64) -> Result<(), String> {
+    platform::flash_platform(rgb, duration_ms).map_err(|e: windows::core::Error| e.to_string())
+}
+
+#[cfg(target_os = "macos")]
+pub fn configure(width: i32, height: i32, x: i32, y: i32, hover_scale_x: f32, hover_scale_y: f32, colors: OverlayColors) -> Result<(), String> {
+    platform::configure(width, height, x, y, hover_scale_x, hover_scale_y, colors)
+}
+
+#[cfg(target_os = "macos")]
 pub fn show() -> Result<(), String> {
     platform::show()
 }
 
-#[cfg(not(windows))]
+#[cfg(target_os = "macos")]
 pub fn hide() -> Result<(), String> {
     platform::hide()
 }
 
 #[cfg(not(windows))]
-pub fn set_hover(_active: bool) -> Result<(), String> {
+pub fn set_hover(active: bool) -> Result<(), String> {
+    platform::set_hover_platform(active)
+}
+
+#[cfg(not(windows))]
+pub fn set_level(level: f32) -> Result<(), String> {
+    platform::set_level_platform(level)
+}
+
+#[cfg(not(windows))]
+pub fn pulse(_rgb: u32, _interval_ms: u64) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn stop_pulse() {}
+
+#[cfg(target_os = "macos")]
+pub fn set_transcript_queue(_lines: usize, _display_ms: u64) {}
+
+#[cfg(not(windows))]
+pub fn push_transcript(_text: &str) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn flash(_rgb: u32, _duration_ms: u64) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+pub fn set_leave_dwell(_dwell_ms: u64) {}
+
+#[cfg(target_os = "macos")]
+pub fn set_exclude_from_capture(_exclude: bool) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+pub fn set_text_bounds(_min_width: i32, _max_width: i32) {}
+
+#[cfg(not(windows))]
+pub fn set_preview_text(_text: Option<String>) -> Result<(), String> {
     Ok(())
 }
 
 #[cfg(not(windows))]
-pub fn set_level(_level: f32) -> Result<(), String> {
+pub fn set_commit_text(_text: Option<String>) -> Result<(), String> {
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlay_text_width_clamps_to_bounds() {
+        assert_eq!(clamp_text_width(50, 120, 480), 120);
+        assert_eq!(clamp_text_width(300, 120, 480), 300);
+        assert_eq!(clamp_text_width(900, 120, 480), 480);
+        // An inverted range collapses to the minimum rather than panicking
+        assert_eq!(clamp_text_width(300, 200, 100), 200);
+    }
+
+    #[test]
+    fn bar_weights_peak_in_the_middle_for_any_count() {
+        // Close to the hand-tuned curve the overlay used to hardcode for 9 bars
+        let nine: Vec<f32> = (0..9).map(|i| bar_weight(i, 9)).collect();
+        assert!((nine[4] - 1.0).abs() < 1e-6);
+        assert!((nine[0] - 0.35).abs() < 0.1);
+        for count in [1, 2, 9, 16, MAX_BAR_COUNT] {
+            for i in 0..count {
+                let weight = bar_weight(i, count);
+                assert!((0.35..=1.0).contains(&weight));
+                assert!((weight - bar_weight(count - 1 - i, count)).abs() < 1e-5);
+            }
+        }
+    }
+}
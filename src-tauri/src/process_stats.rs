@@ -0,0 +1,107 @@
+use std::process::Child;
+
+#[cfg(windows)]
+use std::os::windows::io::AsRawHandle;
+#[cfg(windows)]
+use windows::Win32::Foundation::HANDLE;
+#[cfg(windows)]
+use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+
+/// Resident set size of a running child in bytes (the working set on Windows)
+#[cfg(windows)]
+pub fn resident_bytes(child: &Child) -> Option<u64> {
+    let mut counters = PROCESS_MEMORY_COUNTERS {
+        cb: std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+        ..Default::default()
+    };
+    unsafe {
+        GetProcessMemoryInfo(HANDLE(child.as_raw_handle()), &mut counters, counters.cb).ok()?;
+    }
+    Some(counters.WorkingSetSize as u64)
+}
+
+#[cfg(target_os = "linux")]
+pub fn resident_bytes(child: &Child) -> Option<u64> {
+    let status = std::fs::read_to_string(format!("/proc/{}/status", child.id())).ok()?;
+    vm_rss_bytes(&status)
+}
+
+/// The `VmRSS` line of `/proc/<pid>/status`, which the kernel reports in kB whatever
+/// the page size, unlike statm's page counts
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn vm_rss_bytes(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+pub fn resident_bytes(_child: &Child) -> Option<u64> {
+    None
+}
+
+/// `root` plus every process descended from it, given `(pid, parent_pid)` pairs
+#[cfg_attr(not(windows), allow(dead_code))]
+fn process_tree(root: u32, processes: &[(u32, u32)]) -> Vec<u32> {
+    let mut tree = vec![root];
+    let mut index = 0;
+    while let Some(&parent) = tree.get(index) {
+        for &(pid, _) in processes
+            .iter()
+            .filter(|(pid, ppid)| *ppid == parent && *pid != parent)
+        {
+            if !tree.contains(&pid) {
+                tree.push(pid);
+            }
+        }
+        index += 1;
+    }
+    tree
+}
+
+/// This process plus everything it spawned (WebView2 renderers, the Python engine)
+#[cfg(windows)]
+pub fn own_process_tree() -> Vec<u32> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+
+    let mut processes = Vec::new();
+    unsafe {
+        if let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) {
+            let mut entry = PROCESSENTRY32W {
+                dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+                ..Default::default()
+            };
+            let mut next = Process32FirstW(snapshot, &mut entry);
+            while next.is_ok() {
+                processes.push((entry.th32ProcessID, entry.th32ParentProcessID));
+                next = Process32NextW(snapshot, &mut entry);
+            }
+            let _ = CloseHandle(snapshot);
+        }
+    }
+    process_tree(std::process::id(), &processes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_tree_collects_descendants() {
+        let processes = [(10, 1), (11, 10), (12, 11), (20, 1), (21, 20), (13, 10)];
+        let mut tree = process_tree(10, &processes);
+        tree.sort();
+        assert_eq!(tree, vec![10, 11, 12, 13]);
+    }
+
+    #[test]
+    fn vm_rss_is_read_in_kib() {
+        let status = "Name:\tpython3\nVmHWM:\t  204800 kB\nVmRSS:\t  153600 kB\nThreads:\t12\n";
+        assert_eq!(vm_rss_bytes(status), Some(153_600 * 1024));
+        assert_eq!(vm_rss_bytes("Name:\tzombie\n"), None);
+    }
+}
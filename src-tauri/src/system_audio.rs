@@ -1,27 +1,146 @@
+use std::path::{Path, PathBuf};
 #[cfg(windows)]
-use std::sync::{Mutex, OnceLock};
+use std::sync::Mutex;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
 
 #[cfg(windows)]
-use windows::core::Error;
+use windows::core::{implement, Error, Interface, HSTRING, PCWSTR};
 #[cfg(windows)]
-use windows::Win32::Foundation::RPC_E_CHANGED_MODE;
+use windows::Win32::Foundation::{PROPERTYKEY, RPC_E_CHANGED_MODE, S_OK};
 #[cfg(windows)]
 use windows::Win32::Media::Audio::{
-    eConsole, eRender, Endpoints::IAudioEndpointVolume, IMMDeviceEnumerator, MMDeviceEnumerator,
+    eConsole, eRender, EDataFlow, ERole, Endpoints::IAudioEndpointVolume, IAudioSessionControl2,
+    IAudioSessionManager2, IMMDevice, IMMDeviceEnumerator, IMMNotificationClient,
+    IMMNotificationClient_Impl, ISimpleAudioVolume, MMDeviceEnumerator, DEVICE_STATE,
 };
 #[cfg(windows)]
 use windows::Win32::System::Com::{
-    CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_ALL, COINIT_MULTITHREADED,
+    CoCreateInstance, CoInitializeEx, CoTaskMemFree, CoUninitialize, CLSCTX_ALL,
+    COINIT_MULTITHREADED,
 };
 
 const FADE_DURATION_MS: u64 = 150;
+#[cfg(windows)]
 const FADE_STEPS: u32 = 15;
 const DUCKED_VOLUME_RATIO: f32 = 0.5; // Duck to 50% of og vol
+/// The endpoint can be briefly unavailable right after resume from sleep
+#[cfg(windows)]
+const ENDPOINT_ATTEMPTS: u32 = 3;
+#[cfg(windows)]
+const ENDPOINT_RETRY_DELAY_MS: u64 = 50;
+/// Longest fade accepted from config
+pub const MAX_FADE_MS: u64 = 2000;
+
+/// Ducking settings sourced from `SttConfig`
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub struct DuckConfig {
+    /// Only duck when the current volume is above this scalar (0.0..=1.0)
+    pub min_volume: f32,
+    /// Fraction of the current volume to fade down to (0.0..=1.0)
+    pub ratio: f32,
+    /// Mute the endpoint instead of fading it
+    pub mute: bool,
+    /// Duration of the fade down when dictation starts
+    pub attack_ms: u64,
+    /// Duration of the fade back up when dictation stops
+    pub release_ms: u64,
+    /// When false, new ducks are skipped; an earlier duck is still restored
+    pub enabled: bool,
+    /// Fade other apps' audio sessions instead of the master volume
+    pub sessions: bool,
+}
+
+impl Default for DuckConfig {
+    fn default() -> Self {
+        Self {
+            min_volume: 0.0,
+            ratio: DUCKED_VOLUME_RATIO,
+            mute: false,
+            attack_ms: FADE_DURATION_MS,
+            release_ms: FADE_DURATION_MS,
+            enabled: true,
+            sessions: false,
+        }
+    }
+}
+
+/// Master volume of the default render endpoint, as ducking sees it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub struct OutputVolume {
+    /// Scalar 0.0..=1.0; reads the ducked level while dictation is ducking audio
+    pub level: f32,
+    pub muted: bool,
+}
+
+/// File in the config dir recording an active duck, so a crashed session can be undone
+const SIDECAR_FILE: &str = "duck-state.json";
+
+/// What an active duck changed, as persisted to the sidecar file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StrandedDuck {
+    original_volume: f32,
+    /// The duck muted the endpoint rather than fading it
+    silenced: bool,
+    /// Per-app sessions faded instead of the master volume, by instance id
+    #[serde(default)]
+    sessions: Vec<(String, f32)>,
+    /// Endpoint the duck applied to; `None` targets the current default
+    #[serde(default)]
+    device_id: Option<String>,
+}
+
+static SIDECAR_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Set the directory the duck sidecar lives in; later calls are ignored
+pub fn init_sidecar(dir: PathBuf) {
+    let _ = SIDECAR_DIR.set(dir);
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+fn write_sidecar(dir: &Path, duck: &StrandedDuck) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let json = serde_json::to_string(duck).map_err(std::io::Error::other)?;
+    std::fs::write(dir.join(SIDECAR_FILE), json)
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+fn read_sidecar(dir: &Path) -> Option<StrandedDuck> {
+    let raw = std::fs::read_to_string(dir.join(SIDECAR_FILE)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+fn clear_sidecar() {
+    if let Some(dir) = SIDECAR_DIR.get() {
+        let _ = std::fs::remove_file(dir.join(SIDECAR_FILE));
+    }
+}
+
+#[cfg(windows)]
+fn persist_duck(duck: &StrandedDuck) {
+    if let Some(dir) = SIDECAR_DIR.get() {
+        let _ = write_sidecar(dir, duck);
+    }
+}
 
 #[cfg(windows)]
 struct AudioState {
     original_volume: Option<f32>,
     was_muted: Option<bool>,
+    /// Whether the last duck actually faded; restore is a no-op otherwise
+    ducked: bool,
+    /// Whether the last duck muted the endpoint rather than fading it
+    silenced: bool,
+    /// Sessions the last duck faded, with their volumes before it; empty for a master duck
+    sessions: Vec<(String, f32)>,
+    /// Endpoint the last duck applied to, so restores reach it after the default moves
+    device_id: Option<String>,
+    /// Settings of the active duck, reapplied when the default device changes under it
+    config: Option<DuckConfig>,
 }
 
 #[cfg(windows)]
@@ -31,15 +150,47 @@ fn audio_state_storage() -> &'static Mutex<AudioState> {
         Mutex::new(AudioState {
             original_volume: None,
             was_muted: None,
+            ducked: false,
+            silenced: false,
+            sessions: Vec::new(),
+            device_id: None,
+            config: None,
         })
     })
 }
 
 #[cfg(windows)]
-fn with_endpoint_volume<F, T>(callback: F) -> Result<T, String>
+fn with_endpoint_volume<F, T>(device_id: Option<&str>, callback: F) -> Result<T, String>
 where
     F: FnOnce(&IAudioEndpointVolume) -> Result<T, Error>,
 {
+    with_endpoint(device_id, callback)
+}
+
+/// Call `attempt` up to `attempts` times, sleeping `delay` between failures; returns the
+/// first success or the last error
+#[cfg_attr(not(windows), allow(dead_code))]
+fn retry<T, E>(
+    attempts: u32,
+    delay: std::time::Duration,
+    mut attempt: impl FnMut() -> Result<T, E>,
+) -> Result<T, E> {
+    let mut remaining = attempts.max(1);
+    loop {
+        match attempt() {
+            Ok(value) => return Ok(value),
+            Err(err) if remaining <= 1 => return Err(err),
+            Err(_) => {
+                remaining -= 1;
+                std::thread::sleep(delay);
+            }
+        }
+    }
+}
+
+/// Run `f` with COM initialized on the calling thread
+#[cfg(windows)]
+fn with_com<T>(f: impl FnOnce() -> Result<T, Error>) -> Result<T, String> {
     unsafe {
         let init_result = CoInitializeEx(None, COINIT_MULTITHREADED);
         let mut needs_uninit = false;
@@ -49,13 +200,7 @@ where
             return Err(format!("CoInitializeEx failed: {:?}", init_result));
         }
 
-        let result = (|| {
-            let enumerator: IMMDeviceEnumerator =
-                CoCreateInstance::<_, IMMDeviceEnumerator>(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
-            let device = enumerator.GetDefaultAudioEndpoint(eRender, eConsole)?;
-            let endpoint: IAudioEndpointVolume = device.Activate(CLSCTX_ALL, None)?;
-            callback(&endpoint)
-        })();
+        let result = f();
 
         if needs_uninit {
             CoUninitialize();
@@ -65,109 +210,629 @@ where
     }
 }
 
+/// The render endpoint `device_id`, or the default one when `None`
 #[cfg(windows)]
-fn get_volume() -> Result<f32, String> {
-    with_endpoint_volume(|endpoint: &IAudioEndpointVolume| unsafe {
+unsafe fn render_device(device_id: Option<&str>) -> Result<IMMDevice, Error> {
+    let enumerator: IMMDeviceEnumerator =
+        CoCreateInstance::<_, IMMDeviceEnumerator>(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
+    match device_id {
+        Some(id) => enumerator.GetDevice(&HSTRING::from(id)),
+        None => enumerator.GetDefaultAudioEndpoint(eRender, eConsole),
+    }
+}
+
+/// Activate interface `I` on the render endpoint `device_id` (the default when `None`)
+/// for the duration of `callback`
+#[cfg(windows)]
+fn with_endpoint<I, F, T>(device_id: Option<&str>, callback: F) -> Result<T, String>
+where
+    I: Interface,
+    F: FnOnce(&I) -> Result<T, Error>,
+{
+    with_com(|| {
+        let endpoint = retry(
+            ENDPOINT_ATTEMPTS,
+            std::time::Duration::from_millis(ENDPOINT_RETRY_DELAY_MS),
+            || unsafe { render_device(device_id)?.Activate::<I>(CLSCTX_ALL, None) },
+        )?;
+        callback(&endpoint)
+    })
+}
+
+/// Endpoint id of the current default render device
+#[cfg(windows)]
+fn default_device_id() -> Option<String> {
+    with_com(|| unsafe {
+        let raw_id = retry(
+            ENDPOINT_ATTEMPTS,
+            std::time::Duration::from_millis(ENDPOINT_RETRY_DELAY_MS),
+            || render_device(None)?.GetId(),
+        )?;
+        let id = raw_id.to_string().ok();
+        CoTaskMemFree(Some(raw_id.0 as *const _));
+        Ok(id)
+    })
+    .ok()
+    .flatten()
+}
+
+#[cfg(windows)]
+fn get_volume(device_id: Option<&str>) -> Result<f32, String> {
+    with_endpoint_volume(device_id, |endpoint: &IAudioEndpointVolume| unsafe {
         endpoint.GetMasterVolumeLevelScalar()
     })
 }
 
 #[cfg(windows)]
-fn set_volume(level: f32) -> Result<(), String> {
-    with_endpoint_volume(|endpoint: &IAudioEndpointVolume| unsafe {
+fn set_volume(device_id: Option<&str>, level: f32) -> Result<(), String> {
+    with_endpoint_volume(device_id, |endpoint: &IAudioEndpointVolume| unsafe {
         endpoint.SetMasterVolumeLevelScalar(level, std::ptr::null())?;
         Ok(())
     })
 }
 
 #[cfg(windows)]
-fn get_mute() -> Result<bool, String> {
-    with_endpoint_volume(|endpoint: &IAudioEndpointVolume| unsafe {
+fn get_mute(device_id: Option<&str>) -> Result<bool, String> {
+    with_endpoint_volume(device_id, |endpoint: &IAudioEndpointVolume| unsafe {
         endpoint.GetMute().map(|m| m.as_bool())
     })
 }
 
 #[cfg(windows)]
-#[allow(dead_code)]
-fn set_mute(muted: bool) -> Result<(), String> {
-    with_endpoint_volume(|endpoint: &IAudioEndpointVolume| unsafe {
+fn set_mute(device_id: Option<&str>, muted: bool) -> Result<(), String> {
+    with_endpoint_volume(device_id, |endpoint: &IAudioEndpointVolume| unsafe {
         endpoint.SetMute(muted, std::ptr::null())?;
         Ok(())
     })
 }
 
-/// Fade volume from current level to target over FADE_DURATION_MS
+/// Fade volume from current level to target over `duration_ms`
 #[cfg(windows)]
-fn fade_volume(from: f32, to: f32) {
-    let step_duration = std::time::Duration::from_millis(FADE_DURATION_MS / FADE_STEPS as u64);
-    let step_size = (to - from) / FADE_STEPS as f32;
+fn fade_volume(device_id: Option<&str>, from: f32, to: f32, duration_ms: u64) {
+    fade_steps(duration_ms, |progress| {
+        let _ = set_volume(device_id, (from + (to - from) * progress).clamp(0.0, 1.0));
+    });
+}
 
+/// Call `apply` with progress from just above 0.0 up to 1.0 over `duration_ms`
+#[cfg(windows)]
+fn fade_steps(duration_ms: u64, mut apply: impl FnMut(f32)) {
+    if duration_ms == 0 {
+        apply(1.0);
+        return;
+    }
+    let step_duration = std::time::Duration::from_millis(duration_ms / FADE_STEPS as u64);
     for i in 1..=FADE_STEPS {
-        let level = from + step_size * i as f32;
-        let _ = set_volume(level.clamp(0.0, 1.0));
+        apply(i as f32 / FADE_STEPS as f32);
         if i < FADE_STEPS {
             std::thread::sleep(step_duration);
         }
     }
 }
 
+/// Audio sessions on the endpoint behind `manager`, except system sounds and `except_pids`
+#[cfg(windows)]
+fn audio_sessions(
+    manager: &IAudioSessionManager2,
+    except_pids: &[u32],
+) -> Result<Vec<(String, ISimpleAudioVolume)>, Error> {
+    let mut sessions = Vec::new();
+    unsafe {
+        let list = manager.GetSessionEnumerator()?;
+        for index in 0..list.GetCount()? {
+            let control = list.GetSession(index)?;
+            let control: IAudioSessionControl2 = control.cast()?;
+            // Notification and UI sounds live in the system sounds session
+            if control.IsSystemSoundsSession() == S_OK {
+                continue;
+            }
+            // Sessions spanning several processes report no single pid; keep them
+            if control
+                .GetProcessId()
+                .is_ok_and(|pid| except_pids.contains(&pid))
+            {
+                continue;
+            }
+            let raw_id = control.GetSessionInstanceIdentifier()?;
+            let id = raw_id.to_string();
+            CoTaskMemFree(Some(raw_id.0 as *const _));
+            let Ok(id) = id else {
+                continue;
+            };
+            sessions.push((id, control.cast::<ISimpleAudioVolume>()?));
+        }
+    }
+    Ok(sessions)
+}
+
+/// Fade every session except `except_pids` to `ratio` of its volume;
+/// returns each faded session's instance id and original volume
+#[cfg(windows)]
+pub fn duck_sessions(
+    device_id: Option<&str>,
+    ratio: f32,
+    except_pids: &[u32],
+    duration_ms: u64,
+) -> Result<Vec<(String, f32)>, String> {
+    with_endpoint(device_id, |manager: &IAudioSessionManager2| {
+        let mut fades = Vec::new();
+        for (id, volume) in audio_sessions(manager, except_pids)? {
+            let original = unsafe { volume.GetMasterVolume()? };
+            if original > 0.01 {
+                fades.push((id, volume, original, original * ratio));
+            }
+        }
+        fade_steps(duration_ms, |progress| {
+            for (_, volume, from, to) in &fades {
+                let level = (from + (to - from) * progress).clamp(0.0, 1.0);
+                let _ = unsafe { volume.SetMasterVolume(level, std::ptr::null()) };
+            }
+        });
+        Ok(fades
+            .into_iter()
+            .map(|(id, _, original, _)| (id, original))
+            .collect())
+    })
+}
+
+/// Fade the sessions in `targets` (instance id, volume) back to those volumes;
+/// sessions that have since closed are skipped
+#[cfg(windows)]
+fn fade_sessions_to(
+    device_id: Option<&str>,
+    targets: &[(String, f32)],
+    duration_ms: u64,
+) -> Result<(), String> {
+    with_endpoint(device_id, |manager: &IAudioSessionManager2| {
+        let mut fades = Vec::new();
+        for (id, volume) in audio_sessions(manager, &[])? {
+            if let Some((_, target)) = targets.iter().find(|(target_id, _)| *target_id == id) {
+                let from = unsafe { volume.GetMasterVolume()? };
+                fades.push((volume, from, *target));
+            }
+        }
+        fade_steps(duration_ms, |progress| {
+            for (volume, from, to) in &fades {
+                let level = (from + (to - from) * progress).clamp(0.0, 1.0);
+                let _ = unsafe { volume.SetMasterVolume(level, std::ptr::null()) };
+            }
+        });
+        Ok(())
+    })
+}
+
+/// Whether a default render endpoint exists to duck
+#[cfg(windows)]
+pub fn has_output_device() -> bool {
+    with_endpoint_volume(None, |_endpoint: &IAudioEndpointVolume| Ok(())).is_ok()
+}
+
+/// Whether a duck is in effect and the original volume still has to be restored
+#[cfg(windows)]
+pub fn awaiting_restore() -> bool {
+    audio_state_storage()
+        .lock()
+        .map(|guard| guard.original_volume.is_some())
+        .unwrap_or(false)
+}
+
+/// Current master volume and mute state of the default output device
+#[cfg(windows)]
+pub fn output_volume() -> Result<Option<OutputVolume>, String> {
+    Ok(Some(OutputVolume {
+        level: get_volume(None)?,
+        muted: get_mute(None)?,
+    }))
+}
+
 /// Duck or restore audio when dictation starts/stops
-/// When `duck` is true: fade volume down and store original
-/// When `duck` is false: fade volume back to original
+/// When `duck` is true: fade volume down (or mute, per `config.mute`) and store original
+/// When `duck` is false: fade volume back to original, or unmute, unless it was muted before
 #[cfg(windows)]
-pub fn set_music_muted(duck: bool) -> Result<(), String> {
+pub fn set_music_muted(duck: bool, config: &DuckConfig) -> Result<(), String> {
     let mut guard = audio_state_storage()
         .lock()
         .map_err(|_| "Audio state lock poisoned".to_string())?;
 
     if duck {
-        // Already ducked
-        if guard.original_volume.is_some() {
+        // Already ducked, or ducking is turned off
+        if guard.original_volume.is_some() || !config.enabled {
             return Ok(());
         }
+        return duck_default_device(&mut guard, config);
+    }
 
-        // Check if muted - if so, nothing to duck
-        let is_muted = get_mute().unwrap_or(false);
-        if is_muted {
-            guard.was_muted = Some(true);
-            guard.original_volume = Some(0.0);
+    // Restore: fade back to original volume
+    if let Some(original) = guard.original_volume.take() {
+        let was_muted = guard.was_muted.take().unwrap_or(false);
+        let ducked = std::mem::take(&mut guard.ducked);
+        let silenced = std::mem::take(&mut guard.silenced);
+        let sessions = std::mem::take(&mut guard.sessions);
+        let device_id = guard.device_id.take();
+        guard.config = None;
+        clear_sidecar();
+
+        // If it was muted before or never ducked, don't restore
+        if was_muted || !ducked {
             return Ok(());
         }
 
-        // Get current volume and fade down
-        let current_volume = get_volume()?;
-        guard.original_volume = Some(current_volume);
-        guard.was_muted = Some(false);
+        let device_id = device_id.as_deref();
+        if silenced {
+            return set_mute(device_id, false);
+        }
 
-        // Only fade if there's meaningful volume
-        if current_volume > 0.01 {
-            let target = current_volume * DUCKED_VOLUME_RATIO;
-            fade_volume(current_volume, target);
+        if !sessions.is_empty() {
+            return fade_sessions_to(device_id, &sessions, config.release_ms);
         }
 
+        // Get current (ducked) volume and fade back up
+        let current = get_volume(device_id).unwrap_or(original * config.ratio);
+        fade_volume(device_id, current, original, config.release_ms);
+    }
+
+    Ok(())
+}
+
+/// Duck the current default device per `config`, recording what changed in `state`
+#[cfg(windows)]
+fn duck_default_device(state: &mut AudioState, config: &DuckConfig) -> Result<(), String> {
+    state.config = Some(*config);
+    state.device_id = default_device_id();
+    let device_id = state.device_id.clone();
+    let device_id = device_id.as_deref();
+
+    // Check if muted - if so, nothing to duck
+    let is_muted = get_mute(device_id).unwrap_or(false);
+    if is_muted {
+        state.was_muted = Some(true);
+        state.original_volume = Some(0.0);
+        state.ducked = false;
         return Ok(());
     }
 
-    // Restore: fade back to original volume
-    if let Some(original) = guard.original_volume.take() {
-        let was_muted = guard.was_muted.take().unwrap_or(false);
+    // Get current volume and fade down
+    let current_volume = get_volume(device_id)?;
+    state.original_volume = Some(current_volume);
+    state.was_muted = Some(false);
 
-        // If it was muted before, don't restore
-        if was_muted {
+    // Only act if there's meaningful volume above the configured threshold
+    state.ducked = current_volume > 0.01 && current_volume > config.min_volume;
+    state.silenced = state.ducked && config.mute;
+    if state.ducked && !state.silenced && config.sessions {
+        // Falls through to the master fade if sessions can't be enumerated
+        let except = crate::process_stats::own_process_tree();
+        if let Ok(sessions) = duck_sessions(device_id, config.ratio, &except, config.attack_ms) {
+            state.ducked = !sessions.is_empty();
+            if state.ducked {
+                persist_duck(&StrandedDuck {
+                    original_volume: current_volume,
+                    silenced: false,
+                    sessions: sessions.clone(),
+                    device_id: state.device_id.clone(),
+                });
+            }
+            state.sessions = sessions;
             return Ok(());
         }
+    }
+    if state.ducked {
+        persist_duck(&StrandedDuck {
+            original_volume: current_volume,
+            silenced: state.silenced,
+            sessions: Vec::new(),
+            device_id: state.device_id.clone(),
+        });
+    }
+    if state.silenced {
+        if let Err(err) = set_mute(device_id, true) {
+            // Leave nothing recorded, so the next dictation tries to duck again
+            state.original_volume = None;
+            state.was_muted = None;
+            state.ducked = false;
+            state.silenced = false;
+            state.config = None;
+            state.device_id = None;
+            clear_sidecar();
+            return Err(err);
+        }
+    } else if state.ducked {
+        let target = current_volume * config.ratio;
+        fade_volume(device_id, current_volume, target, config.attack_ms);
+    }
 
-        // Get current (ducked) volume and fade back up
-        let current = get_volume().unwrap_or(original * DUCKED_VOLUME_RATIO);
-        if original > 0.01 {
-            fade_volume(current, original);
+    Ok(())
+}
+
+/// Move an active duck onto `new_device_id` after the default output device changes:
+/// the old device gets its volume back and the new one is ducked from its own level
+#[cfg(windows)]
+fn follow_default_device(new_device_id: &str) -> Result<(), String> {
+    let mut guard = audio_state_storage()
+        .lock()
+        .map_err(|_| "Audio state lock poisoned".to_string())?;
+    let Some(config) = guard.config else {
+        return Ok(());
+    };
+    if guard.device_id.as_deref() == Some(new_device_id) {
+        return Ok(());
+    }
+
+    let original = guard.original_volume.take();
+    guard.was_muted = None;
+    let ducked = std::mem::take(&mut guard.ducked);
+    let silenced = std::mem::take(&mut guard.silenced);
+    let sessions = std::mem::take(&mut guard.sessions);
+    let old_device_id = guard.device_id.take();
+    if let (true, Some(original)) = (ducked, original) {
+        // The old device may be gone entirely (unplugged headphones)
+        let old_device_id = old_device_id.as_deref();
+        let _ = if silenced {
+            set_mute(old_device_id, false)
+        } else if !sessions.is_empty() {
+            fade_sessions_to(old_device_id, &sessions, 0)
+        } else {
+            set_volume(old_device_id, original)
+        };
+    }
+    clear_sidecar();
+
+    duck_default_device(&mut guard, &config)
+}
+
+/// Receives endpoint notifications on a COM worker thread and forwards default render
+/// device changes to the watcher thread
+#[cfg(windows)]
+#[implement(IMMNotificationClient)]
+struct DefaultDeviceWatcher {
+    changes: std::sync::mpsc::Sender<String>,
+}
+
+#[cfg(windows)]
+impl IMMNotificationClient_Impl for DefaultDeviceWatcher_Impl {
+    fn OnDeviceStateChanged(&self, _device_id: &PCWSTR, _state: DEVICE_STATE) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn OnDeviceAdded(&self, _device_id: &PCWSTR) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn OnDeviceRemoved(&self, _device_id: &PCWSTR) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn OnDefaultDeviceChanged(
+        &self,
+        flow: EDataFlow,
+        role: ERole,
+        device_id: &PCWSTR,
+    ) -> Result<(), Error> {
+        // Null when the last render device went away; there is nothing to duck then
+        if flow != eRender || role != eConsole || device_id.is_null() {
+            return Ok(());
+        }
+        // Re-ducking takes the state lock and fades, so it can't run on this callback
+        if let Ok(id) = unsafe { device_id.to_string() } {
+            let _ = self.changes.send(id);
+        }
+        Ok(())
+    }
+
+    fn OnPropertyValueChanged(&self, _device_id: &PCWSTR, _key: &PROPERTYKEY) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Follow default output device changes for the rest of the process, so an active duck
+/// moves to the new device; later calls are ignored
+#[cfg(windows)]
+pub fn watch_default_device() -> Result<(), String> {
+    static STARTED: OnceLock<()> = OnceLock::new();
+    if STARTED.set(()).is_err() {
+        return Ok(());
+    }
+    let (changes, received) = std::sync::mpsc::channel::<String>();
+    let (ready, registered) = std::sync::mpsc::channel::<Result<(), String>>();
+    std::thread::spawn(move || unsafe {
+        // COM stays initialized on this thread for as long as the registration lives
+        let init_result = CoInitializeEx(None, COINIT_MULTITHREADED);
+        if init_result.is_err() && init_result != RPC_E_CHANGED_MODE {
+            let _ = ready.send(Err(format!("CoInitializeEx failed: {:?}", init_result)));
+            return;
+        }
+        let client: IMMNotificationClient = DefaultDeviceWatcher { changes }.into();
+        let registration =
+            CoCreateInstance::<_, IMMDeviceEnumerator>(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                .and_then(|enumerator| {
+                    enumerator.RegisterEndpointNotificationCallback(&client)?;
+                    Ok(enumerator)
+                });
+        let _enumerator = match registration {
+            Ok(enumerator) => enumerator,
+            Err(err) => {
+                let _ = ready.send(Err(format!("{err:?}")));
+                return;
+            }
+        };
+        let _ = ready.send(Ok(()));
+        // The client holds the sender, so this never ends
+        for device_id in received {
+            let _ = follow_default_device(&device_id);
         }
+    });
+    registered
+        .recv()
+        .map_err(|_| "Device watcher exited before registering".to_string())?
+}
+
+/// Move an active fade-duck to `ratio` of the original volume right away;
+/// a no-op when nothing is ducked or the duck muted instead of fading
+#[cfg(windows)]
+pub fn set_duck_level(ratio: f32) -> Result<(), String> {
+    // Holding the lock for the fade keeps it from interleaving with a duck or restore
+    let guard = audio_state_storage()
+        .lock()
+        .map_err(|_| "Audio state lock poisoned".to_string())?;
+    let Some(original) = guard.original_volume else {
+        return Ok(());
+    };
+    if !guard.ducked || guard.silenced {
+        return Ok(());
+    }
+    let device_id = guard.device_id.as_deref();
+    if !guard.sessions.is_empty() {
+        let targets: Vec<(String, f32)> = guard
+            .sessions
+            .iter()
+            .map(|(id, original)| (id.clone(), original * ratio.clamp(0.0, 1.0)))
+            .collect();
+        return fade_sessions_to(device_id, &targets, FADE_DURATION_MS);
+    }
+    let current = get_volume(device_id)?;
+    fade_volume(
+        device_id,
+        current,
+        original * ratio.clamp(0.0, 1.0),
+        FADE_DURATION_MS,
+    );
+    Ok(())
+}
+
+/// Undo an active duck immediately, without a fade; for panic and exit paths
+#[cfg(windows)]
+pub fn restore_now() {
+    // try_lock: a panic can fire while this thread already holds the state lock
+    let mut guard = match audio_state_storage().try_lock() {
+        Ok(guard) => guard,
+        Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(std::sync::TryLockError::WouldBlock) => return,
+    };
+    let Some(original) = guard.original_volume.take() else {
+        return;
+    };
+    guard.was_muted = None;
+    guard.config = None;
+    let ducked = std::mem::take(&mut guard.ducked);
+    let silenced = std::mem::take(&mut guard.silenced);
+    let sessions = std::mem::take(&mut guard.sessions);
+    let device_id = guard.device_id.take();
+    let device_id = device_id.as_deref();
+    if ducked {
+        let _ = if silenced {
+            set_mute(device_id, false)
+        } else if !sessions.is_empty() {
+            fade_sessions_to(device_id, &sessions, 0)
+        } else {
+            set_volume(device_id, original)
+        };
+    }
+    clear_sidecar();
+}
+
+/// Undo a duck a previous session left behind; true when one was found and restored
+#[cfg(windows)]
+pub fn recover_stranded_duck() -> Result<bool, String> {
+    let Some(duck) = SIDECAR_DIR.get().and_then(|dir| read_sidecar(dir)) else {
+        return Ok(false);
+    };
+    clear_sidecar();
+    let device_id = duck.device_id.as_deref();
+    if duck.silenced {
+        set_mute(device_id, false)?;
+    } else if !duck.sessions.is_empty() {
+        fade_sessions_to(device_id, &duck.sessions, 0)?;
+    } else {
+        set_volume(device_id, duck.original_volume.clamp(0.0, 1.0))?;
     }
+    Ok(true)
+}
+
+#[cfg(not(windows))]
+pub fn has_output_device() -> bool {
+    false
+}
 
+#[cfg(not(windows))]
+pub fn awaiting_restore() -> bool {
+    false
+}
+
+#[cfg(not(windows))]
+pub fn output_volume() -> Result<Option<OutputVolume>, String> {
+    Ok(None)
+}
+
+#[cfg(not(windows))]
+pub fn set_music_muted(_duck: bool, _config: &DuckConfig) -> Result<(), String> {
     Ok(())
 }
 
 #[cfg(not(windows))]
-pub fn set_music_muted(_duck: bool) -> Result<(), String> {
+pub fn set_duck_level(_ratio: f32) -> Result<(), String> {
     Ok(())
 }
+
+#[cfg(not(windows))]
+pub fn restore_now() {}
+
+#[cfg(not(windows))]
+pub fn recover_stranded_duck() -> Result<bool, String> {
+    Ok(false)
+}
+
+#[cfg(not(windows))]
+pub fn watch_default_device() -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duck_sidecar_round_trips() {
+        let dir = std::env::temp_dir().join(format!("jargon-duck-test-{}", std::process::id()));
+        assert_eq!(read_sidecar(&dir), None);
+        let duck = StrandedDuck {
+            original_volume: 0.8,
+            silenced: false,
+            sessions: vec![("spotify-session".to_string(), 0.6)],
+            device_id: Some("{0.0.0.00000000}.{headphones}".to_string()),
+        };
+        write_sidecar(&dir, &duck).unwrap();
+        assert_eq!(read_sidecar(&dir), Some(duck));
+        // Sidecars from before the device id was recorded restore on the default device
+        std::fs::write(
+            dir.join(SIDECAR_FILE),
+            r#"{"original_volume":0.5,"silenced":true}"#,
+        )
+        .unwrap();
+        let legacy = read_sidecar(&dir).unwrap();
+        assert_eq!(legacy.device_id, None);
+        assert!(legacy.sessions.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn retry_stops_at_the_first_success_or_the_last_error() {
+        let mut calls = 0;
+        let result = retry(3, std::time::Duration::ZERO, || {
+            calls += 1;
+            if calls < 3 {
+                Err(calls)
+            } else {
+                Ok("endpoint")
+            }
+        });
+        assert_eq!(result, Ok("endpoint"));
+        assert_eq!(calls, 3);
+
+        let mut calls = 0;
+        let result: Result<(), u32> = retry(3, std::time::Duration::ZERO, || {
+            calls += 1;
+            Err(calls)
+        });
+        assert_eq!(result, Err(3));
+    }
+}
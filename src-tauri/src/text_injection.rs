@@ -0,0 +1,204 @@
+#[cfg(windows)]
+use windows::core::{Error, BSTR};
+#[cfg(windows)]
+use windows::Win32::Foundation::RPC_E_CHANGED_MODE;
+#[cfg(windows)]
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER, COINIT_MULTITHREADED,
+};
+#[cfg(windows)]
+use windows::Win32::UI::Accessibility::{
+    CUIAutomation, IUIAutomation, IUIAutomationElement, IUIAutomationTextPattern,
+    IUIAutomationValuePattern, TextPatternRangeEndpoint_End, TextPatternRangeEndpoint_Start,
+    UIA_TextPatternId, UIA_ValuePatternId,
+};
+#[cfg(windows)]
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, KEYEVENTF_UNICODE,
+    VIRTUAL_KEY,
+};
+
+#[cfg(windows)]
+fn with_automation<F, T>(callback: F) -> Result<T, String>
+where
+    F: FnOnce(&IUIAutomation) -> Result<T, String>,
+{
+    unsafe {
+        let init_result = CoInitializeEx(None, COINIT_MULTITHREADED);
+        let mut needs_uninit = false;
+        if init_result.is_ok() {
+            needs_uninit = true;
+        } else if init_result != RPC_E_CHANGED_MODE {
+            return Err(format!("CoInitializeEx failed: {:?}", init_result));
+        }
+
+        let result =
+            CoCreateInstance::<_, IUIAutomation>(&CUIAutomation, None, CLSCTX_INPROC_SERVER)
+                .map_err(|err: Error| format!("{err:?}"))
+                .and_then(|automation| callback(&automation));
+
+        if needs_uninit {
+            CoUninitialize();
+        }
+
+        result
+    }
+}
+
+/// Text before and after the focused element's selection, read through its TextPattern;
+/// `None` when the element doesn't expose one or has no selection
+#[cfg(windows)]
+unsafe fn text_around_selection(element: &IUIAutomationElement) -> Option<(String, String)> {
+    let pattern = element
+        .GetCurrentPatternAs::<IUIAutomationTextPattern>(UIA_TextPatternId)
+        .ok()?;
+    let selection = pattern.GetSelection().ok()?;
+    if selection.Length().ok()? < 1 {
+        return None;
+    }
+    let selected = selection.GetElement(0).ok()?;
+    let document = pattern.DocumentRange().ok()?;
+
+    let before = document.Clone().ok()?;
+    before
+        .MoveEndpointByRange(
+            TextPatternRangeEndpoint_End,
+            &selected,
+            TextPatternRangeEndpoint_Start,
+        )
+        .ok()?;
+    let after = document.Clone().ok()?;
+    after
+        .MoveEndpointByRange(
+            TextPatternRangeEndpoint_Start,
+            &selected,
+            TextPatternRangeEndpoint_End,
+        )
+        .ok()?;
+    Some((
+        before.GetText(-1).ok()?.to_string(),
+        after.GetText(-1).ok()?.to_string(),
+    ))
+}
+
+/// Insert text at the focused element's caret, replacing any selection, via UI
+/// Automation's ValuePattern. Used for inputs (Electron/browser fields) that drop
+/// synthetic keystrokes; falls back to keystrokes when the caret can't be placed
+/// in the value.
+#[cfg(windows)]
+pub fn insert_into_focused_element(text: &str) -> Result<(), String> {
+    with_automation(|automation: &IUIAutomation| unsafe {
+        let element = automation
+            .GetFocusedElement()
+            .map_err(|err| format!("no focused element: {err:?}"))?;
+        let pattern = element
+            .GetCurrentPatternAs::<IUIAutomationValuePattern>(UIA_ValuePatternId)
+            .map_err(|err| format!("focused element does not support ValuePattern: {err:?}"))?;
+
+        if pattern
+            .CurrentIsReadOnly()
+            .map(|r| r.as_bool())
+            .unwrap_or(false)
+        {
+            return Err("focused element is read-only".to_string());
+        }
+
+        let existing = pattern
+            .CurrentValue()
+            .map(|value| value.to_string())
+            .unwrap_or_default();
+        let combined = match text_around_selection(&element) {
+            Some((before, after))
+                if before.len() + after.len() <= existing.len()
+                    && existing.starts_with(&before)
+                    && existing.ends_with(&after) =>
+            {
+                format!("{before}{text}{after}")
+            }
+            // TextPattern and ValuePattern disagree on the text, so the caret can't be
+            // mapped into the value; typing lands at the caret instead
+            Some(_) => return send_unicode_text(text),
+            // Without a TextPattern the caret can't be located, so append
+            None => format!("{existing}{text}"),
+        };
+        pattern
+            .SetValue(&BSTR::from(combined))
+            .map_err(|err| format!("SetValue failed: {err:?}"))
+    })
+}
+
+#[cfg(not(windows))]
+pub fn insert_into_focused_element(_text: &str) -> Result<(), String> {
+    Err("Accessibility injection is only supported on Windows".to_string())
+}
+
+#[cfg(windows)]
+fn unicode_key(unit: u16, key_up: bool) -> INPUT {
+    let mut flags = KEYEVENTF_UNICODE;
+    if key_up {
+        flags |= KEYEVENTF_KEYUP;
+    }
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(0),
+                wScan: unit,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+/// Type text into whatever has keyboard focus as synthetic Unicode key events
+#[cfg(windows)]
+pub fn send_unicode_text(text: &str) -> Result<(), String> {
+    let inputs: Vec<INPUT> = text
+        .encode_utf16()
+        .flat_map(|unit| [unicode_key(unit, false), unicode_key(unit, true)])
+        .collect();
+    if inputs.is_empty() {
+        return Ok(());
+    }
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize == inputs.len() {
+        Ok(())
+    } else {
+        // UIPI blocks input into elevated windows without reporting which event failed
+        Err(format!(
+            "SendInput delivered {sent} of {} events",
+            inputs.len()
+        ))
+    }
+}
+
+/// Type text into whatever has keyboard focus as Unicode-carrying Quartz key events
+#[cfg(target_os = "macos")]
+pub fn send_unicode_text(text: &str) -> Result<(), String> {
+    use objc2_core_graphics::{CGEvent, CGEventTapLocation};
+
+    let units: Vec<u16> = text.encode_utf16().collect();
+    // Quartz truncates the string attached to a single key event at 20 UTF-16 units
+    for chunk in units.chunks(20) {
+        for key_down in [true, false] {
+            let event = CGEvent::new_keyboard_event(None, 0, key_down)
+                .ok_or("CGEventCreateKeyboardEvent failed")?;
+            unsafe {
+                CGEvent::keyboard_set_unicode_string(
+                    Some(&event),
+                    chunk.len() as _,
+                    chunk.as_ptr(),
+                );
+            }
+            CGEvent::post(CGEventTapLocation::HIDEventTap, Some(&event));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+pub fn send_unicode_text(_text: &str) -> Result<(), String> {
+    Err("Keystroke injection is only supported on Windows and macOS".to_string())
+}
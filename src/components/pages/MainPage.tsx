@@ -6,6 +6,9 @@ import confetti from 'canvas-confetti';
 
 type TranscriptEvent = {
   text: string;
+  // Interim text is replaced by later events until a final one arrives
+  isFinal: boolean;
+  segmentId: string | null;
 };
 
 type HistoryItem = {
@@ -81,6 +84,7 @@ async function copyToClipboard(text: string): Promise<boolean> {
 export const MainPage: React.FC = () => {
   const [history, setHistory] = useState<HistoryItem[]>(() => loadHistory());
   const [copiedId, setCopiedId] = useState<string | null>(null);
+  const [interimText, setInterimText] = useState('');
   const clearCopiedTimer = useRef<number | null>(null);
 
   const timeFormatter = useMemo(() => {
@@ -114,6 +118,12 @@ export const MainPage: React.FC = () => {
 
     listen<TranscriptEvent>('stt:transcript', (event) => {
       const text = event.payload?.text?.trim();
+      // Older engines don't send isFinal; everything they emit is final
+      if (event.payload?.isFinal === false) {
+        setInterimText(text ?? '');
+        return;
+      }
+      setInterimText('');
       if (!text) {
         return;
       }
@@ -185,7 +195,15 @@ export const MainPage: React.FC = () => {
         
         {/* Card Container */}
         <div className="bg-white dark:bg-gray-800 rounded-xl border border-gray-200 dark:border-gray-700 shadow-sm overflow-hidden">
-          {history.length === 0 ? (
+          {interimText && (
+            <div className="flex gap-6 p-5 border-b border-gray-100 dark:border-gray-700/50">
+              <span className="text-xs font-medium text-gray-400 w-16 pt-1">
+                {timeFormatter.format(Date.now())}
+              </span>
+              <p className="flex-1 text-gray-400 dark:text-gray-500 italic leading-relaxed pr-2">{interimText}</p>
+            </div>
+          )}
+          {history.length === 0 && !interimText ? (
             <div className="flex gap-6 p-5 hover:bg-gray-50 dark:hover:bg-gray-700/30 transition-colors">
               <span className="text-xs font-medium text-gray-400 w-16 pt-1">
                 {timeFormatter.format(Date.now())}
@@ -61,6 +61,350 @@ const ChangeButton = ({ onClick }: { onClick?: () => void }) => (
 );
 
 
+// The microphone picker, populated from the engine's device list
+interface AudioDevice {
+  id: string;
+  name: string;
+}
+
+const MicrophoneSelect = () => {
+  const [devices, setDevices] = useState<AudioDevice[]>([]);
+  const [selected, setSelected] = useState('');
+
+  useEffect(() => {
+    let active = true;
+    (async () => {
+      try {
+        const core = await import('@tauri-apps/api/core');
+        if (!(core.isTauri?.() ?? true)) {
+          return;
+        }
+        const [list, config] = await Promise.all([
+          core.invoke<AudioDevice[]>('stt_list_audio_devices'),
+          core.invoke<{ micDeviceId?: string | null }>('stt_get_config'),
+        ]);
+        if (active) {
+          setDevices(list);
+          setSelected(config.micDeviceId ?? '');
+        }
+      } catch (err) {
+        console.warn('Failed to list microphones', err);
+      }
+    })();
+    return () => {
+      active = false;
+    };
+  }, []);
+
+  const handleChange = useCallback((next: string) => {
+    setSelected(next);
+    void (async () => {
+      try {
+        const core = await import('@tauri-apps/api/core');
+        const config = await core.invoke<Record<string, unknown>>('stt_get_config');
+        await core.invoke('stt_set_config', {
+          config: { ...config, micDeviceId: next || null },
+        });
+      } catch (err) {
+        console.warn('Failed to update microphone', err);
+      }
+    })();
+  }, []);
+
+  return (
+    <select
+      value={selected}
+      onChange={(event) => handleChange(event.target.value)}
+      className="max-w-xs px-3 py-2 bg-gray-100 dark:bg-gray-800 text-gray-700 dark:text-gray-200 text-sm font-medium rounded-lg focus:outline-none focus:ring-2 focus:ring-green-500"
+    >
+      <option value="">Auto-detect</option>
+      {devices.map((device) => (
+        <option key={device.id} value={device.id}>
+          {device.name}
+        </option>
+      ))}
+    </select>
+  );
+};
+
+// The dictation language; stt_apply_config restarts the engine to pick it up
+const languageName = (code: string) => {
+  try {
+    return new Intl.DisplayNames(undefined, { type: 'language' }).of(code) ?? code;
+  } catch {
+    return code;
+  }
+};
+
+const LanguageSelect = () => {
+  const [languages, setLanguages] = useState<string[]>([]);
+  const [selected, setSelected] = useState('');
+
+  useEffect(() => {
+    let active = true;
+    (async () => {
+      try {
+        const core = await import('@tauri-apps/api/core');
+        if (!(core.isTauri?.() ?? true)) {
+          return;
+        }
+        const [list, config] = await Promise.all([
+          core.invoke<string[]>('stt_list_languages'),
+          core.invoke<{ language?: string | null }>('stt_get_config'),
+        ]);
+        if (active) {
+          setLanguages(list);
+          setSelected(config.language ?? '');
+        }
+      } catch (err) {
+        console.warn('Failed to list languages', err);
+      }
+    })();
+    return () => {
+      active = false;
+    };
+  }, []);
+
+  const handleChange = useCallback((next: string) => {
+    setSelected(next);
+    void (async () => {
+      try {
+        const core = await import('@tauri-apps/api/core');
+        const config = await core.invoke<Record<string, unknown>>('stt_get_config');
+        await core.invoke('stt_apply_config', {
+          config: { ...config, language: next || null },
+        });
+      } catch (err) {
+        console.warn('Failed to update language', err);
+      }
+    })();
+  }, []);
+
+  return (
+    <select
+      value={selected}
+      onChange={(event) => handleChange(event.target.value)}
+      className="max-w-xs px-3 py-2 bg-gray-100 dark:bg-gray-800 text-gray-700 dark:text-gray-200 text-sm font-medium rounded-lg focus:outline-none focus:ring-2 focus:ring-green-500"
+    >
+      <option value="">Auto-detect</option>
+      {languages.map((code) => (
+        <option key={code} value={code}>
+          {languageName(code)}
+        </option>
+      ))}
+    </select>
+  );
+};
+
+// The overlay bar's screen edge; stt_set_config repositions the bar immediately
+type OverlayAnchor = 'top' | 'bottom';
+
+const OverlayPositionSelect = () => {
+  const [anchor, setAnchor] = useState<OverlayAnchor>('top');
+
+  useEffect(() => {
+    let active = true;
+    (async () => {
+      try {
+        const core = await import('@tauri-apps/api/core');
+        if (!(core.isTauri?.() ?? true)) {
+          return;
+        }
+        const config = await core.invoke<{ overlayAnchor?: OverlayAnchor }>('stt_get_config');
+        if (active) {
+          setAnchor(config.overlayAnchor ?? 'top');
+        }
+      } catch (err) {
+        console.warn('Failed to load overlay position', err);
+      }
+    })();
+    return () => {
+      active = false;
+    };
+  }, []);
+
+  const handleChange = useCallback((next: OverlayAnchor) => {
+    setAnchor(next);
+    void (async () => {
+      try {
+        const core = await import('@tauri-apps/api/core');
+        const config = await core.invoke<Record<string, unknown>>('stt_get_config');
+        await core.invoke('stt_set_config', {
+          config: { ...config, overlayAnchor: next },
+        });
+      } catch (err) {
+        console.warn('Failed to update overlay position', err);
+      }
+    })();
+  }, []);
+
+  return (
+    <select
+      value={anchor}
+      onChange={(event) => handleChange(event.target.value as OverlayAnchor)}
+      className="max-w-xs px-3 py-2 bg-gray-100 dark:bg-gray-800 text-gray-700 dark:text-gray-200 text-sm font-medium rounded-lg focus:outline-none focus:ring-2 focus:ring-green-500"
+    >
+      <option value="top">Top of screen</option>
+      <option value="bottom">Bottom of screen</option>
+    </select>
+  );
+};
+
+// Distance in logical pixels between the overlay bar and its screen edge
+const OVERLAY_OFFSET_MAX_PX = 400;
+
+const OverlayOffsetInput = () => {
+  const [offset, setOffset] = useState(16);
+
+  useEffect(() => {
+    let active = true;
+    (async () => {
+      try {
+        const core = await import('@tauri-apps/api/core');
+        if (!(core.isTauri?.() ?? true)) {
+          return;
+        }
+        const config = await core.invoke<{ overlayOffsetPx?: number }>('stt_get_config');
+        if (active) {
+          setOffset(config.overlayOffsetPx ?? 16);
+        }
+      } catch (err) {
+        console.warn('Failed to load overlay offset', err);
+      }
+    })();
+    return () => {
+      active = false;
+    };
+  }, []);
+
+  const handleChange = useCallback((raw: string) => {
+    const parsed = Number.parseInt(raw, 10);
+    if (Number.isNaN(parsed)) {
+      return;
+    }
+    const next = Math.min(Math.max(parsed, 0), OVERLAY_OFFSET_MAX_PX);
+    setOffset(next);
+    void (async () => {
+      try {
+        const core = await import('@tauri-apps/api/core');
+        const config = await core.invoke<Record<string, unknown>>('stt_get_config');
+        await core.invoke('stt_set_config', {
+          config: { ...config, overlayOffsetPx: next },
+        });
+      } catch (err) {
+        console.warn('Failed to update overlay offset', err);
+      }
+    })();
+  }, []);
+
+  return (
+    <input
+      type="number"
+      min={0}
+      max={OVERLAY_OFFSET_MAX_PX}
+      value={offset}
+      onChange={(event) => handleChange(event.target.value)}
+      className="w-24 px-3 py-2 bg-gray-100 dark:bg-gray-800 text-gray-700 dark:text-gray-200 text-sm font-medium rounded-lg focus:outline-none focus:ring-2 focus:ring-green-500"
+    />
+  );
+};
+
+// Holds the overlay expanded with moving bars; switched off again when the page closes
+const OverlayPreviewToggle = () => {
+  const [active, setActive] = useState(false);
+
+  const setPreview = useCallback(async (next: boolean) => {
+    try {
+      const core = await import('@tauri-apps/api/core');
+      if (!(core.isTauri?.() ?? true)) {
+        return;
+      }
+      await core.invoke('overlay_preview', { active: next, level: 0.8 });
+    } catch (err) {
+      console.warn('Failed to toggle overlay preview', err);
+    }
+  }, []);
+
+  useEffect(() => {
+    if (!active) {
+      return;
+    }
+    return () => {
+      void setPreview(false);
+    };
+  }, [active, setPreview]);
+
+  const handleChange = useCallback(
+    (next: boolean) => {
+      setActive(next);
+      if (next) {
+        void setPreview(true);
+      }
+    },
+    [setPreview],
+  );
+
+  return <ToggleSwitch checked={active} onChange={handleChange} />;
+};
+
+// Engine state: a spawned process is only "starting" until it reports ready
+type EngineState = 'stopped' | 'starting' | 'listening';
+
+interface SttStatus {
+  running: boolean;
+  ready: boolean;
+}
+
+const engineState = (status: SttStatus): EngineState =>
+  status.ready ? 'listening' : status.running ? 'starting' : 'stopped';
+
+const ENGINE_STATE_LABELS: Record<EngineState, string> = {
+  stopped: 'Stopped',
+  starting: 'Starting…',
+  listening: 'Listening',
+};
+
+const EngineStatus = () => {
+  const [state, setState] = useState<EngineState>('stopped');
+
+  useEffect(() => {
+    let active = true;
+    const unlisteners: Array<() => void> = [];
+    (async () => {
+      try {
+        const core = await import('@tauri-apps/api/core');
+        if (!(core.isTauri?.() ?? true)) {
+          return;
+        }
+        const event = await import('@tauri-apps/api/event');
+        unlisteners.push(
+          await event.listen<SttStatus>('stt:status', ({ payload }) => setState(engineState(payload))),
+          await event.listen('stt:ready', () => setState('listening')),
+        );
+        if (!active) {
+          unlisteners.forEach((unlisten) => unlisten());
+          return;
+        }
+        const status = await core.invoke<SttStatus>('stt_get_status');
+        if (active) {
+          setState(engineState(status));
+        }
+      } catch (err) {
+        console.warn('Failed to load engine status', err);
+      }
+    })();
+    return () => {
+      active = false;
+      unlisteners.forEach((unlisten) => unlisten());
+    };
+  }, []);
+
+  return (
+    <span className="text-sm font-medium text-gray-700 dark:text-gray-300">{ENGINE_STATE_LABELS[state]}</span>
+  );
+};
+
+
 // --- 2. Page Sections ---
 
 const GeneralSection = () => (
@@ -73,11 +417,14 @@ const GeneralSection = () => (
       >
         <ChangeButton />
       </SettingsRow>
-      <SettingsRow label="Microphone" description="Auto-detect">
-        <ChangeButton />
+      <SettingsRow label="Speech engine">
+        <EngineStatus />
       </SettingsRow>
-      <SettingsRow label="Languages" description="English" isLast={true}>
-        <ChangeButton />
+      <SettingsRow label="Microphone" description="Takes effect the next time the engine starts">
+        <MicrophoneSelect />
+      </SettingsRow>
+      <SettingsRow label="Language" description="Restarts the engine when changed" isLast={true}>
+        <LanguageSelect />
       </SettingsRow>
     </div>
   </div>
@@ -152,6 +499,15 @@ const SystemSection = () => {
           <SettingsRow label="Show Flow bar at all times">
             <ToggleSwitch checked={showBar} onChange={setShowBar} />
           </SettingsRow>
+          <SettingsRow label="Flow bar position">
+            <OverlayPositionSelect />
+          </SettingsRow>
+          <SettingsRow label="Flow bar distance from edge" description="In pixels">
+            <OverlayOffsetInput />
+          </SettingsRow>
+          <SettingsRow label="Preview Flow bar" description="Keep the bar expanded with sample levels">
+            <OverlayPreviewToggle />
+          </SettingsRow>
           <SettingsRow label="Show app in dock" isLast={true}>
             <ToggleSwitch checked={showInDock} onChange={setShowInDock} />
           </SettingsRow>